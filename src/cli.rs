@@ -3,29 +3,55 @@
 use crate::error::ScaffoldError;
 use crate::file_tree::load_directory_into_memory;
 use crate::scaffold::config::ScaffoldConfig;
+use crate::scaffold::web_app::template_hooks::TemplateHooks;
+use crate::scaffold::web_app::template_manifest::{TemplateManifest, TemplateVars};
 use crate::scaffold::web_app::template_type::TemplateType;
 
 use colored::Colorize;
 use std::{path::Path, str::FromStr};
 use structopt::StructOpt;
 
+mod bridge;
+mod capability;
 mod collection;
 mod dna;
+mod dna_properties;
 mod entry_type;
 mod example;
+mod file_storage;
+mod function;
 mod link_type;
+mod membrane;
+mod relation;
+mod remote_signal;
+mod scheduled;
 mod template;
 mod web_app;
 mod zome;
+mod zome_call;
 
 #[derive(Debug, StructOpt)]
 pub struct HcScaffold {
     #[structopt(short, long, parse(try_from_str = TemplateType::from_str))]
     /// The template to use for the hc-scaffold commands.
     /// Can either be an option from the built-in templates: "svelte", "headless",
-    /// or a path to a custom template.
+    /// a path to a custom template or to a ".tar.gz" tarball of one,
+    /// or a git repository as "git+<url>#<branch, tag or commit>".
     template: Option<TemplateType>,
 
+    #[structopt(long)]
+    /// Fetch the git template again and use the latest commit of its branch or tag,
+    /// instead of the commit recorded when the hApp was scaffolded
+    template_update: bool,
+
+    #[structopt(long = "var", number_of_values = 1, parse(try_from_str = parse_template_var))]
+    /// Value for a variable declared by the template, as "key=value". Can be given multiple times.
+    vars: Vec<(String, String)>,
+
+    #[structopt(long)]
+    /// Skip running the hooks declared by the template after scaffolding
+    no_hooks: bool,
+
     #[structopt(subcommand)]
     command: HcScaffoldCommand,
 }
@@ -37,9 +63,19 @@ pub enum HcScaffoldCommand {
     WebApp(web_app::WebApp),
     Template(template::Template),
     Dna(dna::Dna),
+    DnaProperties(dna_properties::DnaProperties),
+    Membrane(membrane::Membrane),
     Zome(zome::Zome),
+    ZomeCall(zome_call::ZomeCall),
     EntryType(entry_type::EntryType),
     LinkType(link_type::LinkType),
+    Relation(relation::Relation),
+    Function(function::Function),
+    Capability(capability::Capability),
+    Bridge(bridge::Bridge),
+    FileStorage(file_storage::FileStorage),
+    RemoteSignal(remote_signal::RemoteSignal),
+    Scheduled(scheduled::Scheduled),
     Collection(collection::Collection),
     Example(example::Example),
 }
@@ -50,15 +86,57 @@ impl HcScaffold {
         let scaffold_config = ScaffoldConfig::from_package_json_path(&current_dir)?;
         let template_type = self.get_template_type(&current_dir, scaffold_config.as_ref())?;
 
+        // The template subcommands check the template themselves, even if its manifest is broken
+        let manifest = match self.command {
+            HcScaffoldCommand::Template(_) => None,
+            _ => TemplateManifest::from_template_file_tree(&template_type.file_tree()?)?,
+        };
+        let template_vars = self.resolve_template_vars(
+            &template_type,
+            manifest.as_ref(),
+            scaffold_config.as_ref(),
+        )?;
+        self.update_scaffold_config(
+            &current_dir,
+            scaffold_config,
+            &template_type,
+            &template_vars,
+        )?;
+        let hooks = if self.no_hooks {
+            TemplateHooks::disabled()
+        } else {
+            TemplateHooks::new(manifest.as_ref(), self.command.name(), &template_vars)
+        };
+
         match self.command {
-            HcScaffoldCommand::WebApp(web_app) => web_app.run(&template_type).await,
+            HcScaffoldCommand::WebApp(web_app) => {
+                web_app.run(&template_type, template_vars, &hooks).await
+            }
             HcScaffoldCommand::Template(template) => template.run(&template_type),
-            HcScaffoldCommand::Dna(dna) => dna.run(&template_type),
-            HcScaffoldCommand::Zome(zome) => zome.run(&template_type),
-            HcScaffoldCommand::EntryType(entry_type) => entry_type.run(&template_type),
-            HcScaffoldCommand::LinkType(link_type) => link_type.run(&template_type),
-            HcScaffoldCommand::Collection(collection) => collection.run(&template_type),
-            HcScaffoldCommand::Example(example) => example.run(&template_type).await,
+            HcScaffoldCommand::Dna(dna) => dna.run(&template_type, &hooks),
+            HcScaffoldCommand::DnaProperties(dna_properties) => {
+                dna_properties.run(&template_type, &hooks)
+            }
+            HcScaffoldCommand::Membrane(membrane) => membrane.run(&template_type, &hooks),
+            HcScaffoldCommand::Zome(zome) => zome.run(&template_type, &hooks),
+            HcScaffoldCommand::ZomeCall(zome_call) => zome_call.run(&hooks),
+            HcScaffoldCommand::EntryType(entry_type) => entry_type.run(&template_type, &hooks),
+            HcScaffoldCommand::LinkType(link_type) => link_type.run(&template_type, &hooks),
+            HcScaffoldCommand::Relation(relation) => relation.run(&template_type, &hooks),
+            HcScaffoldCommand::Function(function) => function.run(&template_type, &hooks),
+            HcScaffoldCommand::Capability(capability) => capability.run(&template_type, &hooks),
+            HcScaffoldCommand::Bridge(bridge) => bridge.run(&template_type, &hooks),
+            HcScaffoldCommand::FileStorage(file_storage) => {
+                file_storage.run(&template_type, &hooks)
+            }
+            HcScaffoldCommand::RemoteSignal(remote_signal) => {
+                remote_signal.run(&template_type, &hooks)
+            }
+            HcScaffoldCommand::Scheduled(scheduled) => scheduled.run(&template_type, &hooks),
+            HcScaffoldCommand::Collection(collection) => collection.run(&template_type, &hooks),
+            HcScaffoldCommand::Example(example) => {
+                example.run(&template_type, template_vars, &hooks).await
+            }
         }
     }
 
@@ -70,20 +148,39 @@ impl HcScaffold {
         // Read template_type config if no `--template` flag is provided and use it or
         // ensure that if a `--template` is explicity provided, it matches the original
         // template the app was scaffolded with
-        let template = match (scaffold_config, &self.template) {
-            (Some(config), Some(template)) if config.template != *template => {
+        let config_template = scaffold_config.and_then(|config| config.template.as_ref());
+        let template = match (config_template, &self.template) {
+            (Some(config_template), Some(template)) if config_template != template => {
                 return Err(ScaffoldError::InvalidArguments(format!(
                     "The value {} passed with `--template` does not match the template the web-app was scaffolded with: {}",
                     template.name().italic(),
-                    config.template.name().italic(),
+                    config_template.name().italic(),
                 )));
             }
-            (Some(config), _) => Some(&config.template),
+            (Some(config_template), _) => Some(config_template),
             (_, t) => t.as_ref(),
         };
 
         match template {
-            Some(template) => Ok(template.clone()),
+            Some(template) => {
+                // Templates of a new hApp are resolved again, rather than using the commit
+                // pinned by a hApp they might be created in
+                let (commit, base_commits) = match (&self.command, scaffold_config) {
+                    (HcScaffoldCommand::WebApp(_) | HcScaffoldCommand::Example(_), _)
+                    | (_, None) => Default::default(),
+                    (_, Some(config)) => (
+                        config.template_commit.clone(),
+                        config.template_base_commits.clone(),
+                    ),
+                };
+                // Upgrading the hApp is meant to use the latest version of the template
+                let update = self.template_update
+                    || matches!(
+                        self.command,
+                        HcScaffoldCommand::Template(template::Template::Upgrade)
+                    );
+                template.clone().pin(commit, base_commits, update)
+            }
             None => {
                 let template_type = match &self.command {
                     HcScaffoldCommand::WebApp { .. } => TemplateType::choose()?,
@@ -94,4 +191,114 @@ impl HcScaffold {
             }
         }
     }
+
+    /// Gets the values of the variables the template declares for this command, prompting for
+    /// the ones that were neither passed with `--var` nor persisted in the `package.json` file
+    fn resolve_template_vars(
+        &self,
+        template_type: &TemplateType,
+        manifest: Option<&TemplateManifest>,
+        scaffold_config: Option<&ScaffoldConfig>,
+    ) -> Result<TemplateVars, ScaffoldError> {
+        let mut vars = scaffold_config
+            .map(|config| config.vars.clone())
+            .unwrap_or_default();
+
+        let command = match &self.command {
+            HcScaffoldCommand::Template(_) => return Ok(vars),
+            command => command.name(),
+        };
+
+        if self.command.creates_app() {
+            vars.clear();
+        }
+
+        let Some(manifest) = manifest else {
+            if let Some((name, _)) = self.vars.first() {
+                return Err(ScaffoldError::InvalidArguments(format!(
+                    "Template {template_type} doesn't declare any variables, but \"{name}\" was given"
+                )));
+            }
+            return Ok(vars);
+        };
+
+        manifest.resolve_variables(command, &self.vars, &mut vars)?;
+
+        Ok(vars)
+    }
+
+    /// Persists newly given template variables and the commit a git template was updated to
+    /// in the `package.json` file of the hApp
+    fn update_scaffold_config(
+        &self,
+        current_dir: &Path,
+        scaffold_config: Option<ScaffoldConfig>,
+        template_type: &TemplateType,
+        template_vars: &TemplateVars,
+    ) -> Result<(), ScaffoldError> {
+        // The web-app and example commands create a new hApp, which writes its own config
+        if self.command.creates_app()
+            || matches!(self.command, HcScaffoldCommand::Template(_))
+            || !current_dir.join("package.json").exists()
+        {
+            return Ok(());
+        }
+
+        let persisted_config = scaffold_config.unwrap_or_default();
+        let mut scaffold_config = persisted_config.clone();
+        scaffold_config.vars = template_vars.clone();
+        if scaffold_config.template.is_some() {
+            scaffold_config.template_commit = template_type.commit().map(String::from);
+            scaffold_config.template_base_commits = template_type.base_commits();
+        }
+
+        if scaffold_config != persisted_config {
+            scaffold_config.write_to_package_json_path(current_dir)?;
+        }
+        Ok(())
+    }
+}
+
+impl HcScaffoldCommand {
+    /// Whether the subcommand creates a new hApp
+    fn creates_app(&self) -> bool {
+        matches!(
+            self,
+            HcScaffoldCommand::WebApp(_) | HcScaffoldCommand::Example(_)
+        )
+    }
+
+    /// Gets the name of the subcommand, as typed in the command line
+    fn name(&self) -> &'static str {
+        match self {
+            HcScaffoldCommand::WebApp(_) => "web-app",
+            HcScaffoldCommand::Template(_) => "template",
+            HcScaffoldCommand::Dna(_) => "dna",
+            HcScaffoldCommand::DnaProperties(_) => "dna-properties",
+            HcScaffoldCommand::Membrane(_) => "membrane",
+            HcScaffoldCommand::Zome(_) => "zome",
+            HcScaffoldCommand::ZomeCall(_) => "zome-call",
+            HcScaffoldCommand::EntryType(_) => "entry-type",
+            HcScaffoldCommand::LinkType(_) => "link-type",
+            HcScaffoldCommand::Relation(_) => "relation",
+            HcScaffoldCommand::Function(_) => "function",
+            HcScaffoldCommand::Capability(_) => "capability",
+            HcScaffoldCommand::Bridge(_) => "bridge",
+            HcScaffoldCommand::FileStorage(_) => "file-storage",
+            HcScaffoldCommand::RemoteSignal(_) => "remote-signal",
+            HcScaffoldCommand::Scheduled(_) => "scheduled",
+            HcScaffoldCommand::Collection(_) => "collection",
+            HcScaffoldCommand::Example(_) => "example",
+        }
+    }
+}
+
+fn parse_template_var(var: &str) -> Result<(String, String), ScaffoldError> {
+    var.split_once('=')
+        .map(|(key, value)| (key.trim().to_string(), value.to_string()))
+        .ok_or_else(|| {
+            ScaffoldError::InvalidArguments(format!(
+                "Invalid variable \"{var}\", expected the format key=value"
+            ))
+        })
 }
@@ -0,0 +1,72 @@
+use std::str::FromStr;
+
+use colored::Colorize;
+use structopt::StructOpt;
+
+use crate::{
+    file_tree::{build_file_tree, load_directory_into_memory},
+    scaffold::{
+        bridge::{scaffold_bridge, ZomeTarget},
+        web_app::{template_hooks::TemplateHooks, template_type::TemplateType},
+    },
+    templates::ScaffoldedTemplate,
+    utils::run_cargo_fmt_if_available,
+};
+
+#[derive(Debug, StructOpt)]
+/// Scaffold a typed function calling a zome function of another DNA of the same hApp
+pub struct Bridge {
+    #[structopt(long, parse(try_from_str = ZomeTarget::from_str))]
+    /// Coordinator zome in which the bridge function is scaffolded, eg. "teams/teams"
+    pub from: Option<ZomeTarget>,
+
+    #[structopt(long, parse(try_from_str = ZomeTarget::from_str))]
+    /// Coordinator zome of the other DNA whose function is called, eg. "forum/posts"
+    pub to: Option<ZomeTarget>,
+
+    #[structopt(long)]
+    /// Name of the function of the other DNA to call
+    pub function: Option<String>,
+
+    #[structopt(long)]
+    /// Skips test generation for the bridge
+    pub no_spec: bool,
+}
+
+impl Bridge {
+    pub fn run(self, template_type: &TemplateType, hooks: &TemplateHooks) -> anyhow::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let file_tree = load_directory_into_memory(&current_dir)?;
+
+        let ScaffoldedTemplate {
+            file_tree,
+            next_instructions,
+        } = scaffold_bridge(
+            file_tree,
+            &template_type.file_tree()?,
+            self.from.as_ref(),
+            self.to.as_ref(),
+            self.function.as_deref(),
+            self.no_spec,
+        )?;
+
+        build_file_tree(file_tree, ".")?;
+
+        if let Err(e) = run_cargo_fmt_if_available() {
+            println!(
+                "{}: {}",
+                "rustfmt exec failed: ".yellow(),
+                e.to_string().yellow()
+            );
+        }
+
+        hooks.run(&current_dir, &[]);
+
+        println!("\nBridge scaffolded!\n");
+        if let Some(i) = next_instructions {
+            println!("{i}");
+        }
+
+        Ok(())
+    }
+}
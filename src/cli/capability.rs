@@ -0,0 +1,96 @@
+use std::str::FromStr;
+
+use colored::Colorize;
+use convert_case::Case;
+use structopt::StructOpt;
+
+use crate::{
+    file_tree::{build_file_tree, load_directory_into_memory},
+    scaffold::{
+        capability::{scaffold_capability, CapabilityAccess},
+        dna::DnaFileTree,
+        web_app::{template_hooks::TemplateHooks, template_type::TemplateType},
+        zome::ZomeFileTree,
+    },
+    templates::ScaffoldedTemplate,
+    utils::{check_case, run_cargo_fmt_if_available},
+};
+
+#[derive(Debug, StructOpt)]
+/// Scaffold a capability grant allowing other agents to call functions of a coordinator zome
+pub struct Capability {
+    #[structopt(long)]
+    /// Name of the dna in which you want to scaffold the capability
+    pub dna: Option<String>,
+
+    #[structopt(long)]
+    /// Name of the coordinator zome whose functions are granted
+    pub zome: Option<String>,
+
+    #[structopt(long)]
+    /// Tag of the capability grant, used to name the generated functions (defaults to the access)
+    pub tag: Option<String>,
+
+    #[structopt(long, value_delimiter = ",")]
+    /// Functions of the coordinator zome that other agents can call, eg. "get_post,get_all_posts"
+    pub functions: Option<Vec<String>>,
+
+    #[structopt(long, parse(try_from_str = CapabilityAccess::from_str))]
+    /// Who can call the functions: "unrestricted", "transferable" or "assigned"
+    pub access: Option<CapabilityAccess>,
+
+    #[structopt(long)]
+    /// Skips test generation for this capability
+    pub no_spec: bool,
+}
+
+impl Capability {
+    pub fn run(self, template_type: &TemplateType, hooks: &TemplateHooks) -> anyhow::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let file_tree = load_directory_into_memory(&current_dir)?;
+        if let Some(tag) = &self.tag {
+            check_case(tag, "capability tag", Case::Snake)?;
+        }
+
+        let dna_file_tree = DnaFileTree::get_or_choose(file_tree, self.dna.as_deref())?;
+        let zome_file_tree =
+            ZomeFileTree::get_or_choose_coordinator(dna_file_tree, self.zome.as_deref())?;
+
+        let dna_name = zome_file_tree.dna_file_tree.dna_manifest.name().to_string();
+        let zome_name = zome_file_tree.zome_manifest.name.to_string();
+
+        let ScaffoldedTemplate {
+            file_tree,
+            next_instructions,
+        } = scaffold_capability(
+            zome_file_tree,
+            &template_type.file_tree()?,
+            self.tag.as_deref(),
+            self.functions.as_ref(),
+            self.access,
+            self.no_spec,
+        )?;
+
+        build_file_tree(file_tree, ".")?;
+
+        if let Err(e) = run_cargo_fmt_if_available() {
+            println!(
+                "{}: {}",
+                "rustfmt exec failed: ".yellow(),
+                e.to_string().yellow()
+            );
+        }
+
+        hooks.run(
+            &current_dir,
+            &[("DNA_NAME", &dna_name), ("ZOME_NAME", &zome_name)],
+        );
+
+        println!("\nCapability scaffolded!\n");
+        if let Some(i) = next_instructions {
+            println!("{i}");
+        }
+
+        Ok(())
+    }
+}
@@ -10,7 +10,10 @@ use crate::{
         collection::{scaffold_collection, CollectionType},
         dna::DnaFileTree,
         entry_type::definitions::EntryTypeReference,
-        web_app::template_type::TemplateType,
+        web_app::{
+            template_hooks::TemplateHooks, template_manifest::TemplateCommand,
+            template_type::TemplateType,
+        },
         zome::ZomeFileTree,
     },
     templates::ScaffoldedTemplate,
@@ -48,7 +51,7 @@ pub struct Collection {
 }
 
 impl Collection {
-    pub fn run(self, template_type: &TemplateType) -> anyhow::Result<()> {
+    pub fn run(self, template_type: &TemplateType, hooks: &TemplateHooks) -> anyhow::Result<()> {
         let current_dir = std::env::current_dir()?;
         let file_tree = load_directory_into_memory(&current_dir)?;
 
@@ -68,12 +71,15 @@ impl Collection {
             )?,
         };
 
+        let dna_name = zome_file_tree.dna_file_tree.dna_manifest.name().to_string();
+        let zome_name = zome_file_tree.zome_manifest.name.to_string();
+
         let ScaffoldedTemplate {
             file_tree,
             next_instructions,
         } = scaffold_collection(
             zome_file_tree,
-            &template_type.file_tree()?,
+            &template_type.file_tree_for_command(TemplateCommand::Collection)?,
             &name,
             self.collection_type,
             self.entry_type,
@@ -91,6 +97,15 @@ impl Collection {
             );
         }
 
+        hooks.run(
+            &current_dir,
+            &[
+                ("DNA_NAME", &dna_name),
+                ("ZOME_NAME", &zome_name),
+                ("COLLECTION_NAME", &name),
+            ],
+        );
+
         println!("\nCollection {} scaffolded!\n", name.italic());
 
         if let Some(i) = next_instructions {
@@ -4,7 +4,11 @@ use structopt::StructOpt;
 
 use crate::{
     file_tree::{build_file_tree, load_directory_into_memory},
-    scaffold::{app::AppFileTree, dna::scaffold_dna, web_app::template_type::TemplateType},
+    scaffold::{
+        app::AppFileTree,
+        dna::scaffold_dna,
+        web_app::{template_hooks::TemplateHooks, template_type::TemplateType},
+    },
     templates::ScaffoldedTemplate,
     utils::{check_case, input_with_case},
 };
@@ -18,10 +22,18 @@ pub struct Dna {
 
     /// Name of the DNA being scaffolded
     pub name: Option<String>,
+
+    #[structopt(long, default_value = "0")]
+    /// Maximum number of clone cells that can be created for the DNA's role
+    pub clone_limit: u32,
+
+    #[structopt(long)]
+    /// Defers the creation of the DNA's cell until it's explicitly requested after installing the app
+    pub deferred: bool,
 }
 
 impl Dna {
-    pub fn run(self, template_type: &TemplateType) -> anyhow::Result<()> {
+    pub fn run(self, template_type: &TemplateType, hooks: &TemplateHooks) -> anyhow::Result<()> {
         let current_dir = std::env::current_dir()?;
         let file_tree = load_directory_into_memory(&current_dir)?;
 
@@ -34,14 +46,26 @@ impl Dna {
         };
 
         let app_file_tree = AppFileTree::get_or_choose(file_tree, self.app.as_deref())?;
+        let app_name = app_file_tree.app_manifest.app_name().to_string();
 
         let ScaffoldedTemplate {
             file_tree,
             next_instructions,
-        } = scaffold_dna(app_file_tree, &template_type.file_tree()?, &name)?;
+        } = scaffold_dna(
+            app_file_tree,
+            &template_type.file_tree()?,
+            &name,
+            self.clone_limit,
+            self.deferred,
+        )?;
 
         build_file_tree(file_tree, ".")?;
 
+        hooks.run(
+            &current_dir,
+            &[("APP_NAME", &app_name), ("DNA_NAME", &name)],
+        );
+
         println!("\nDNA {} scaffolded!", name.italic());
 
         if let Some(i) = next_instructions {
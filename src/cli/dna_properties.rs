@@ -0,0 +1,83 @@
+use std::str::FromStr;
+
+use colored::Colorize;
+use structopt::StructOpt;
+
+use crate::{
+    file_tree::{build_file_tree, load_directory_into_memory},
+    scaffold::{
+        dna::{properties::scaffold_dna_properties, DnaFileTree},
+        entry_type::definitions::FieldDefinition,
+        web_app::{template_hooks::TemplateHooks, template_type::TemplateType},
+        zome::ZomeFileTree,
+    },
+    templates::ScaffoldedTemplate,
+    utils::run_cargo_fmt_if_available,
+};
+
+#[derive(Debug, StructOpt)]
+/// Scaffold typed DNA properties, with a getter function and their default values in the manifests
+pub struct DnaProperties {
+    #[structopt(long)]
+    /// Name of the dna in which you want to scaffold the properties
+    pub dna: Option<String>,
+
+    #[structopt(long)]
+    /// Name of the integrity zome in which you want to scaffold the properties struct
+    pub zome: Option<String>,
+
+    #[structopt(long, required = true, value_delimiter = ",", parse(try_from_str = FieldDefinition::from_str))]
+    /// The fields of the DNA properties
+    /// Syntax: <FIELD_NAME>:<FIELD_TYPE> , Eg. "max_posts:u32" , "moderators:Vec\<AgentPubKey\>"
+    pub fields: Vec<FieldDefinition>,
+
+    #[structopt(long)]
+    /// Skips test generation for the DNA properties
+    pub no_spec: bool,
+}
+
+impl DnaProperties {
+    pub fn run(self, template_type: &TemplateType, hooks: &TemplateHooks) -> anyhow::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let file_tree = load_directory_into_memory(&current_dir)?;
+
+        let dna_file_tree = DnaFileTree::get_or_choose(file_tree, self.dna.as_deref())?;
+        let zome_file_tree =
+            ZomeFileTree::get_or_choose_integrity(dna_file_tree, self.zome.as_deref())?;
+
+        let dna_name = zome_file_tree.dna_file_tree.dna_manifest.name().to_string();
+        let zome_name = zome_file_tree.zome_manifest.name.to_string();
+
+        let ScaffoldedTemplate {
+            file_tree,
+            next_instructions,
+        } = scaffold_dna_properties(
+            zome_file_tree,
+            &template_type.file_tree()?,
+            &self.fields,
+            self.no_spec,
+        )?;
+
+        build_file_tree(file_tree, ".")?;
+
+        if let Err(e) = run_cargo_fmt_if_available() {
+            println!(
+                "{}: {}",
+                "rustfmt exec failed: ".yellow(),
+                e.to_string().yellow()
+            );
+        }
+
+        hooks.run(
+            &current_dir,
+            &[("DNA_NAME", &dna_name), ("ZOME_NAME", &zome_name)],
+        );
+
+        println!("\nDNA properties scaffolded!\n");
+        if let Some(i) = next_instructions {
+            println!("{i}");
+        }
+
+        Ok(())
+    }
+}
@@ -8,8 +8,15 @@ use crate::{
     file_tree::{build_file_tree, load_directory_into_memory},
     scaffold::{
         dna::DnaFileTree,
-        entry_type::{crud::Crud, definitions::FieldDefinition, scaffold_entry_type},
-        web_app::template_type::TemplateType,
+        entry_type::{
+            create_policy::CreatePolicy, crud::Crud, definitions::FieldDefinition,
+            scaffold_entry_type,
+        },
+        link_type::on_delete::FieldOnDelete,
+        web_app::{
+            template_hooks::TemplateHooks, template_manifest::TemplateCommand,
+            template_type::TemplateType,
+        },
         zome::ZomeFileTree,
     },
     templates::ScaffoldedTemplate,
@@ -51,6 +58,26 @@ pub struct EntryType {
     /// Eg. "title:String:TextField" , "posts_hashes:Vec\<ActionHash\>::Post"
     pub fields: Option<Vec<FieldDefinition>>,
 
+    #[structopt(long, value_delimiter = ",", parse(try_from_str = FieldOnDelete::from_str))]
+    /// What happens to the links from the linked_from entry types when they are deleted
+    /// Syntax: <FIELD_NAME>:<cascade|restrict|orphan> , (orphan is the default)
+    /// Eg. "post_hash:cascade"
+    pub on_delete: Option<Vec<FieldOnDelete>>,
+
+    #[structopt(long)]
+    /// Whether cascading deletes (see "--on-delete") also delete the entries of this entry type
+    pub delete_dependent_entries: bool,
+
+    #[structopt(long, parse(try_from_str = CreatePolicy::from_str))]
+    /// Who is allowed to create entries of this type: "anyone" (default) or "progenitor".
+    /// The progenitor is read from the "progenitor" DNA property, which is scaffolded if needed
+    pub create_policy: Option<CreatePolicy>,
+
+    #[structopt(long)]
+    /// Number of agents that countersign each entry of this type, in a countersigning session replacing its create function.
+    /// Countersigned entry types can't be updated, nor have linked_from fields
+    pub countersigned: Option<u8>,
+
     #[structopt(long)]
     /// Skips UI generation for this entry-type, overriding any specified widgets in the --fields option.
     pub no_ui: bool,
@@ -61,7 +88,7 @@ pub struct EntryType {
 }
 
 impl EntryType {
-    pub fn run(self, template_type: &TemplateType) -> anyhow::Result<()> {
+    pub fn run(self, template_type: &TemplateType, hooks: &TemplateHooks) -> anyhow::Result<()> {
         let current_dir = std::env::current_dir()?;
         let file_tree = load_directory_into_memory(&current_dir)?;
         let name = match self.name {
@@ -76,17 +103,24 @@ impl EntryType {
         let zome_file_tree =
             ZomeFileTree::get_or_choose_integrity(dna_file_tree, self.zome.as_deref())?;
 
+        let dna_name = zome_file_tree.dna_file_tree.dna_manifest.name().to_string();
+        let zome_name = zome_file_tree.zome_manifest.name.to_string();
+
         let ScaffoldedTemplate {
             file_tree,
             next_instructions,
         } = scaffold_entry_type(
             zome_file_tree,
-            &template_type.file_tree()?,
+            &template_type.file_tree_for_command(TemplateCommand::EntryType)?,
             &name,
             self.crud,
             self.reference_entry_hash,
             self.link_from_original_to_each_update,
             self.fields.as_ref(),
+            self.on_delete.as_deref().unwrap_or_default(),
+            self.delete_dependent_entries,
+            self.create_policy.unwrap_or_default(),
+            self.countersigned,
             self.no_ui,
             self.no_spec,
         )?;
@@ -101,6 +135,15 @@ impl EntryType {
             );
         }
 
+        hooks.run(
+            &current_dir,
+            &[
+                ("DNA_NAME", &dna_name),
+                ("ZOME_NAME", &zome_name),
+                ("ENTRY_TYPE_NAME", &name),
+            ],
+        );
+
         println!("\nEntry type {} scaffolded!", name.italic());
 
         if let Some(i) = next_instructions {
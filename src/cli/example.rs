@@ -14,13 +14,20 @@ use crate::{
         config::ScaffoldConfig,
         dna::{scaffold_dna, DnaFileTree},
         entry_type::{
+            create_policy::CreatePolicy,
             crud::Crud,
             definitions::{
                 Cardinality, EntryTypeReference, FieldDefinition, FieldType, Referenceable,
             },
             scaffold_entry_type,
         },
-        web_app::{scaffold_web_app, template_type::TemplateType},
+        web_app::{
+            scaffold_web_app,
+            template_hooks::TemplateHooks,
+            template_manifest::{TemplateCommand, TemplateVars},
+            template_type::TemplateType,
+            template_upgrade::{render_web_app, store_web_app_render},
+        },
         zome::{
             scaffold_coordinator_zome_in_path, scaffold_integrity_zome_with_path, ZomeFileTree,
         },
@@ -40,9 +47,14 @@ pub struct Example {
 }
 
 impl Example {
-    pub async fn run(self, template_type: &TemplateType) -> anyhow::Result<()> {
+    pub async fn run(
+        self,
+        template_type: &TemplateType,
+        template_vars: TemplateVars,
+        hooks: &TemplateHooks,
+    ) -> anyhow::Result<()> {
         let command_root_dir = std::env::current_dir()?;
-        let template_file_tree = template_type.file_tree()?;
+        let template_file_tree = template_type.file_tree_for_command(TemplateCommand::Example)?;
 
         let app_dir = command_root_dir.join(FORUM);
         if app_dir.as_path().exists() {
@@ -50,19 +62,34 @@ impl Example {
         }
 
         // scaffold web-app
-        let ScaffoldedTemplate { file_tree, .. } = scaffold_web_app(
+        let ScaffoldedTemplate { mut file_tree, .. } = scaffold_web_app(
             FORUM,
             Some("A simple 'forum' application."),
             false,
             &template_file_tree,
+            &template_vars,
+        )?;
+
+        store_web_app_render(
+            &mut file_tree,
+            &render_web_app(&template_file_tree, FORUM, &template_vars)?,
         )?;
 
+        // Written before scaffolding the rest of the example so that its templates get the variables
+        ScaffoldConfig {
+            template: Some(template_type.clone()),
+            template_commit: template_type.commit().map(String::from),
+            template_base_commits: template_type.base_commits(),
+            vars: template_vars,
+        }
+        .write_to_package_json(&mut file_tree)?;
+
         // scaffold dna forum
         let dna_name = FORUM;
 
         let app_file_tree = AppFileTree::get_or_choose(file_tree, Some(FORUM))?;
         let ScaffoldedTemplate { file_tree, .. } =
-            scaffold_dna(app_file_tree, &template_file_tree, dna_name)?;
+            scaffold_dna(app_file_tree, &template_file_tree, dna_name, 0, false)?;
 
         // scaffold integrity zome posts
         let dna_file_tree = DnaFileTree::get_or_choose(file_tree, Some(dna_name))?;
@@ -135,6 +162,10 @@ impl Example {
                     linked_from: None,
                 },
             ]),
+            &[],
+            false,
+            CreatePolicy::Anyone,
+            None,
             false,
             false,
         )?;
@@ -173,6 +204,10 @@ impl Example {
                     })),
                 },
             ]),
+            &[],
+            false,
+            CreatePolicy::Anyone,
+            None,
             false,
             false,
         )?;
@@ -196,12 +231,10 @@ impl Example {
         )?;
 
         let ScaffoldedTemplate {
-            mut file_tree,
+            file_tree,
             next_instructions,
         } = scaffold_example(file_tree, &template_file_tree, FORUM)?;
 
-        ScaffoldConfig::write_to_package_json(&mut file_tree, template_type)?;
-
         build_file_tree(file_tree, &app_dir)?;
 
         // cargo fmt needs to be run inside the Rust project folder
@@ -225,6 +258,8 @@ impl Example {
 
         setup_git_environment(&app_dir)?;
 
+        hooks.run(&app_dir, &[("APP_NAME", FORUM)]);
+
         println!("\nExample {} scaffolded!\n", FORUM.italic());
 
         if let Some(i) = next_instructions {
@@ -0,0 +1,84 @@
+use colored::Colorize;
+use convert_case::Case;
+use structopt::StructOpt;
+
+use crate::{
+    file_tree::{build_file_tree, load_directory_into_memory},
+    scaffold::{
+        dna::DnaFileTree,
+        file_storage::scaffold_file_storage,
+        web_app::{template_hooks::TemplateHooks, template_type::TemplateType},
+    },
+    templates::ScaffoldedTemplate,
+    utils::{check_case, run_cargo_fmt_if_available},
+};
+
+#[derive(Debug, StructOpt)]
+/// Scaffold a coordinator/integrity zome pair that stores files split in chunks, and the
+/// "File" field type that entry types can use to reference them
+pub struct FileStorage {
+    #[structopt(long)]
+    /// Name of the dna in which you want to scaffold the file storage zomes
+    pub dna: Option<String>,
+
+    #[structopt(long, default_value = "file_storage")]
+    /// Name of the coordinator zome, the integrity zome will be named "<ZOME>_integrity"
+    pub zome: String,
+
+    #[structopt(long, default_value = "10")]
+    /// Maximum size of the stored files, in megabytes
+    pub max_file_size: usize,
+
+    #[structopt(long)]
+    /// Skips UI generation for the file storage
+    pub no_ui: bool,
+
+    #[structopt(long)]
+    /// Skips test generation for the file storage
+    pub no_spec: bool,
+}
+
+impl FileStorage {
+    pub fn run(self, template_type: &TemplateType, hooks: &TemplateHooks) -> anyhow::Result<()> {
+        check_case(&self.zome, "zome name", Case::Snake)?;
+
+        let current_dir = std::env::current_dir()?;
+        let file_tree = load_directory_into_memory(&current_dir)?;
+        let dna_file_tree = DnaFileTree::get_or_choose(file_tree, self.dna.as_deref())?;
+        let dna_name = dna_file_tree.dna_manifest.name().to_string();
+
+        let ScaffoldedTemplate {
+            file_tree,
+            next_instructions,
+        } = scaffold_file_storage(
+            dna_file_tree,
+            &template_type.file_tree()?,
+            &self.zome,
+            self.max_file_size,
+            self.no_ui,
+            self.no_spec,
+        )?;
+
+        build_file_tree(file_tree, ".")?;
+
+        if let Err(e) = run_cargo_fmt_if_available() {
+            println!(
+                "{}: {}",
+                "rustfmt exec failed: ".yellow(),
+                e.to_string().yellow()
+            );
+        }
+
+        hooks.run(
+            &current_dir,
+            &[("DNA_NAME", &dna_name), ("ZOME_NAME", &self.zome)],
+        );
+
+        println!("\nFile storage zomes {} scaffolded!\n", self.zome.italic());
+        if let Some(i) = next_instructions {
+            println!("{i}");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+use std::str::FromStr;
+
+use colored::Colorize;
+use convert_case::Case;
+use structopt::StructOpt;
+
+use crate::{
+    file_tree::{build_file_tree, load_directory_into_memory},
+    scaffold::{
+        dna::DnaFileTree,
+        entry_type::definitions::FieldDefinition,
+        function::{scaffold_function, FunctionInput},
+        web_app::{template_hooks::TemplateHooks, template_type::TemplateType},
+        zome::ZomeFileTree,
+    },
+    templates::ScaffoldedTemplate,
+    utils::{check_case, input_with_case, run_cargo_fmt_if_available},
+};
+
+#[derive(Debug, StructOpt)]
+/// Scaffold a zome function into an existing coordinator zome
+pub struct Function {
+    #[structopt(long)]
+    /// Name of the dna in which you want to scaffold the function
+    pub dna: Option<String>,
+
+    #[structopt(long)]
+    /// Name of the coordinator zome in which you want to scaffold the function
+    pub zome: Option<String>,
+
+    /// Name of the function being scaffolded
+    pub name: Option<String>,
+
+    #[structopt(long)]
+    /// File of the coordinator zome's "src" folder in which to scaffold the function
+    /// It's created as a new module if it doesn't exist
+    pub file: Option<String>,
+
+    #[structopt(long)]
+    /// Rust type of the function's input, eg. "ActionHash"
+    /// If "--input-fields" is given, this is the name of the new input struct
+    pub input: Option<String>,
+
+    #[structopt(long, value_delimiter = ",", parse(try_from_str = FieldDefinition::from_str))]
+    /// Fields of a new input struct to scaffold for the function
+    /// Syntax: <FIELD_NAME>:<FIELD_TYPE> , Eg. "title:String" , "tags:Vec\<String\>"
+    pub input_fields: Option<Vec<FieldDefinition>>,
+
+    #[structopt(long)]
+    /// Rust type of the function's output, eg. "Vec\<Record\>" (defaults to "()")
+    pub output: Option<String>,
+
+    #[structopt(long)]
+    /// Skips UI generation for this function
+    pub no_ui: bool,
+
+    #[structopt(long)]
+    /// Skips test generation for this function
+    pub no_spec: bool,
+}
+
+impl Function {
+    pub fn run(self, template_type: &TemplateType, hooks: &TemplateHooks) -> anyhow::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let file_tree = load_directory_into_memory(&current_dir)?;
+        let name = match self.name {
+            Some(n) => {
+                check_case(&n, "function name", Case::Snake)?;
+                n
+            }
+            None => input_with_case("Function name (snake_case):", None, Case::Snake)?,
+        };
+        if let Some(file) = &self.file {
+            check_case(file.trim_end_matches(".rs"), "file name", Case::Snake)?;
+        }
+
+        let input =
+            FunctionInput::from_args(&name, self.input.as_deref(), self.input_fields.as_ref())?;
+
+        let dna_file_tree = DnaFileTree::get_or_choose(file_tree, self.dna.as_deref())?;
+        let zome_file_tree =
+            ZomeFileTree::get_or_choose_coordinator(dna_file_tree, self.zome.as_deref())?;
+
+        let dna_name = zome_file_tree.dna_file_tree.dna_manifest.name().to_string();
+        let zome_name = zome_file_tree.zome_manifest.name.to_string();
+
+        let ScaffoldedTemplate {
+            file_tree,
+            next_instructions,
+        } = scaffold_function(
+            zome_file_tree,
+            &template_type.file_tree()?,
+            &name,
+            self.file.as_deref(),
+            input,
+            self.output.as_deref(),
+            self.no_ui,
+            self.no_spec,
+        )?;
+
+        build_file_tree(file_tree, ".")?;
+
+        if let Err(e) = run_cargo_fmt_if_available() {
+            println!(
+                "{}: {}",
+                "rustfmt exec failed: ".yellow(),
+                e.to_string().yellow()
+            );
+        }
+
+        hooks.run(
+            &current_dir,
+            &[
+                ("DNA_NAME", &dna_name),
+                ("ZOME_NAME", &zome_name),
+                ("FUNCTION_NAME", &name),
+            ],
+        );
+
+        println!("\nFunction {} scaffolded!\n", name.italic());
+        if let Some(i) = next_instructions {
+            println!("{i}");
+        }
+
+        Ok(())
+    }
+}
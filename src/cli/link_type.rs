@@ -6,8 +6,14 @@ use structopt::StructOpt;
 use crate::{
     file_tree::{build_file_tree, load_directory_into_memory},
     scaffold::{
-        dna::DnaFileTree, entry_type::definitions::Referenceable, link_type::scaffold_link_type,
-        web_app::template_type::TemplateType, zome::ZomeFileTree,
+        dna::DnaFileTree,
+        entry_type::definitions::Referenceable,
+        link_type::{on_delete::OnDelete, scaffold_link_type},
+        web_app::{
+            template_hooks::TemplateHooks, template_manifest::TemplateCommand,
+            template_type::TemplateType,
+        },
+        zome::ZomeFileTree,
     },
     templates::ScaffoldedTemplate,
     utils::run_cargo_fmt_if_available,
@@ -32,14 +38,30 @@ pub struct LinkType {
     /// Entry type (or agent role) used as the target for the links
     pub to_referenceable: Option<Referenceable>,
 
+    #[structopt(long, conflicts_with = "to-referenceable")]
+    /// Name of the link type (PascalCase), to scaffold a link type without a target
+    pub name: Option<String>,
+
     #[structopt(long)]
     /// Whether to create the inverse link, from the "--to-referenceable" entry type to the "--from-referenceable" entry type
     pub bidirectional: Option<bool>,
 
+    #[structopt(long)]
+    /// Whether to scaffold functions to count the links and to check whether a base is linked to a given target
+    pub query_functions: Option<bool>,
+
     #[structopt(long)]
     /// Whether this link type can be deleted
     pub delete: Option<bool>,
 
+    #[structopt(long, parse(try_from_str = OnDelete::from_str))]
+    /// What happens to the links when their base entry is deleted: "cascade", "restrict" or "orphan" (default)
+    pub on_delete: Option<OnDelete>,
+
+    #[structopt(long)]
+    /// Whether cascading deletes (see "--on-delete") also delete the entries the links point to
+    pub delete_dependent_entries: bool,
+
     #[structopt(long)]
     /// Skips UI generation for this link-type.
     pub no_ui: bool,
@@ -50,7 +72,7 @@ pub struct LinkType {
 }
 
 impl LinkType {
-    pub fn run(self, template_type: &TemplateType) -> anyhow::Result<()> {
+    pub fn run(self, template_type: &TemplateType, hooks: &TemplateHooks) -> anyhow::Result<()> {
         let current_dir = std::env::current_dir()?;
         let file_tree = load_directory_into_memory(&current_dir)?;
 
@@ -58,16 +80,23 @@ impl LinkType {
         let zome_file_tree =
             ZomeFileTree::get_or_choose_integrity(dna_file_tree, self.zome.as_deref())?;
 
+        let dna_name = zome_file_tree.dna_file_tree.dna_manifest.name().to_string();
+        let zome_name = zome_file_tree.zome_manifest.name.to_string();
+
         let ScaffoldedTemplate {
             file_tree,
             next_instructions,
         } = scaffold_link_type(
             zome_file_tree,
-            &template_type.file_tree()?,
+            &template_type.file_tree_for_command(TemplateCommand::LinkType)?,
             self.from_referenceable.as_ref(),
             self.to_referenceable.as_ref(),
+            self.name.as_deref(),
             self.delete,
             self.bidirectional,
+            self.query_functions,
+            self.on_delete,
+            self.delete_dependent_entries,
             self.no_ui,
             self.no_spec,
         )?;
@@ -82,6 +111,11 @@ impl LinkType {
             );
         }
 
+        hooks.run(
+            &current_dir,
+            &[("DNA_NAME", &dna_name), ("ZOME_NAME", &zome_name)],
+        );
+
         println!("\nLink type scaffolded!\n");
         if let Some(i) = next_instructions {
             println!("{i}");
@@ -0,0 +1,87 @@
+use std::str::FromStr;
+
+use colored::Colorize;
+use structopt::StructOpt;
+
+use crate::{
+    file_tree::{build_file_tree, load_directory_into_memory},
+    scaffold::{
+        dna::DnaFileTree,
+        membrane::{scaffold_membrane, MembraneKind},
+        web_app::{template_hooks::TemplateHooks, template_type::TemplateType},
+        zome::ZomeFileTree,
+    },
+    templates::ScaffoldedTemplate,
+    utils::run_cargo_fmt_if_available,
+};
+
+#[derive(Debug, StructOpt)]
+/// Scaffold the membrane proof validation of the agents joining the network of a DNA
+pub struct Membrane {
+    #[structopt(long)]
+    /// Name of the dna in which you want to scaffold the membrane
+    pub dna: Option<String>,
+
+    #[structopt(long)]
+    /// Name of the integrity zome in which you want to scaffold the membrane proof validation
+    pub zome: Option<String>,
+
+    #[structopt(long, parse(try_from_str = MembraneKind::from_str))]
+    /// How agents prove that they can join: "invite-code", "signed-by-progenitor" or "allowlist"
+    pub kind: Option<MembraneKind>,
+
+    #[structopt(long)]
+    /// Skips UI generation for the membrane
+    pub no_ui: bool,
+
+    #[structopt(long)]
+    /// Skips test generation for the membrane
+    pub no_spec: bool,
+}
+
+impl Membrane {
+    pub fn run(self, template_type: &TemplateType, hooks: &TemplateHooks) -> anyhow::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let file_tree = load_directory_into_memory(&current_dir)?;
+
+        let dna_file_tree = DnaFileTree::get_or_choose(file_tree, self.dna.as_deref())?;
+        let zome_file_tree =
+            ZomeFileTree::get_or_choose_integrity(dna_file_tree, self.zome.as_deref())?;
+
+        let dna_name = zome_file_tree.dna_file_tree.dna_manifest.name().to_string();
+        let zome_name = zome_file_tree.zome_manifest.name.to_string();
+
+        let ScaffoldedTemplate {
+            file_tree,
+            next_instructions,
+        } = scaffold_membrane(
+            zome_file_tree,
+            &template_type.file_tree()?,
+            self.kind,
+            self.no_ui,
+            self.no_spec,
+        )?;
+
+        build_file_tree(file_tree, ".")?;
+
+        if let Err(e) = run_cargo_fmt_if_available() {
+            println!(
+                "{}: {}",
+                "rustfmt exec failed: ".yellow(),
+                e.to_string().yellow()
+            );
+        }
+
+        hooks.run(
+            &current_dir,
+            &[("DNA_NAME", &dna_name), ("ZOME_NAME", &zome_name)],
+        );
+
+        println!("\nMembrane scaffolded!\n");
+        if let Some(i) = next_instructions {
+            println!("{i}");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use std::str::FromStr;
+
+use colored::Colorize;
+use convert_case::Case;
+use structopt::StructOpt;
+
+use crate::{
+    file_tree::{build_file_tree, load_directory_into_memory},
+    scaffold::{
+        dna::DnaFileTree,
+        entry_type::definitions::{FieldDefinition, Referenceable},
+        relation::scaffold_relation,
+        web_app::{
+            template_hooks::TemplateHooks, template_manifest::TemplateCommand,
+            template_type::TemplateType,
+        },
+        zome::ZomeFileTree,
+    },
+    templates::ScaffoldedTemplate,
+    utils::{check_case, input_with_case, run_cargo_fmt_if_available},
+};
+
+#[derive(Debug, StructOpt)]
+/// Scaffold a many-to-many relation between two entry types (or agent roles) through a join entry type
+pub struct Relation {
+    #[structopt(long)]
+    /// Name of the dna in which you want to scaffold the relation
+    pub dna: Option<String>,
+
+    #[structopt(long)]
+    /// Name of the integrity zome in which you want to scaffold the relation
+    pub zome: Option<String>,
+
+    #[structopt(parse(try_from_str = Referenceable::from_str))]
+    /// Entry type (or agent role) on one end of the relation
+    pub from_referenceable: Option<Referenceable>,
+
+    #[structopt(parse(try_from_str = Referenceable::from_str))]
+    /// Entry type (or agent role) on the other end of the relation
+    pub to_referenceable: Option<Referenceable>,
+
+    #[structopt(long)]
+    /// Name of the join entry type holding the addresses of both ends of the relation
+    pub via_entry: Option<String>,
+
+    #[structopt(long, value_delimiter = ",", parse(try_from_str = FieldDefinition::from_str))]
+    /// Extra fields that the join entry type struct should contain
+    /// Syntax: <FIELD_NAME>:<FIELD_TYPE>:<WIDGET> , (widget is optional)
+    /// Eg. "role:String:TextField" , "joined_at:Timestamp"
+    pub fields: Option<Vec<FieldDefinition>>,
+
+    #[structopt(long)]
+    /// Skips UI generation for the join entry type
+    pub no_ui: bool,
+
+    #[structopt(long)]
+    /// Skips test generation for the join entry type
+    pub no_spec: bool,
+}
+
+impl Relation {
+    pub fn run(self, template_type: &TemplateType, hooks: &TemplateHooks) -> anyhow::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let file_tree = load_directory_into_memory(&current_dir)?;
+        let via_entry = match self.via_entry {
+            Some(n) => {
+                check_case(&n, "join entry type name", Case::Snake)?;
+                n
+            }
+            None => input_with_case("Join entry type name (snake_case):", None, Case::Snake)?,
+        };
+
+        let dna_file_tree = DnaFileTree::get_or_choose(file_tree, self.dna.as_deref())?;
+        let zome_file_tree =
+            ZomeFileTree::get_or_choose_integrity(dna_file_tree, self.zome.as_deref())?;
+
+        let dna_name = zome_file_tree.dna_file_tree.dna_manifest.name().to_string();
+        let zome_name = zome_file_tree.zome_manifest.name.to_string();
+
+        let ScaffoldedTemplate {
+            file_tree,
+            next_instructions,
+        } = scaffold_relation(
+            zome_file_tree,
+            &template_type.file_tree_for_command(TemplateCommand::EntryType)?,
+            self.from_referenceable.as_ref(),
+            self.to_referenceable.as_ref(),
+            &via_entry,
+            self.fields.as_ref(),
+            self.no_ui,
+            self.no_spec,
+        )?;
+
+        build_file_tree(file_tree, ".")?;
+
+        if let Err(e) = run_cargo_fmt_if_available() {
+            println!(
+                "{}: {}",
+                "rustfmt exec failed: ".yellow(),
+                e.to_string().yellow()
+            );
+        }
+
+        hooks.run(
+            &current_dir,
+            &[
+                ("DNA_NAME", &dna_name),
+                ("ZOME_NAME", &zome_name),
+                ("ENTRY_TYPE_NAME", &via_entry),
+            ],
+        );
+
+        println!("\nRelation via {} scaffolded!\n", via_entry.italic());
+        if let Some(i) = next_instructions {
+            println!("{i}");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use std::str::FromStr;
+
+use colored::Colorize;
+use convert_case::Case;
+use structopt::StructOpt;
+
+use crate::{
+    file_tree::{build_file_tree, load_directory_into_memory},
+    scaffold::{
+        dna::DnaFileTree,
+        entry_type::definitions::FieldDefinition,
+        remote_signal::scaffold_remote_signal,
+        web_app::{template_hooks::TemplateHooks, template_type::TemplateType},
+        zome::ZomeFileTree,
+    },
+    templates::ScaffoldedTemplate,
+    utils::{check_case, input_with_case, run_cargo_fmt_if_available},
+};
+
+#[derive(Debug, StructOpt)]
+/// Scaffold a remote signal that the agents of a coordinator zome can send to each other
+pub struct RemoteSignal {
+    #[structopt(long)]
+    /// Name of the dna in which you want to scaffold the remote signal
+    pub dna: Option<String>,
+
+    #[structopt(long)]
+    /// Name of the coordinator zome in which you want to scaffold the remote signal
+    pub zome: Option<String>,
+
+    /// Name of the remote signal being scaffolded, eg. "new_message"
+    pub name: Option<String>,
+
+    #[structopt(long, value_delimiter = ",", parse(try_from_str = FieldDefinition::from_str))]
+    /// Fields of the payload sent with the signal (empty if not given)
+    /// Syntax: <FIELD_NAME>:<FIELD_TYPE> , Eg. "content:String" , "mentions:Vec\<AgentPubKey\>"
+    pub payload: Option<Vec<FieldDefinition>>,
+
+    #[structopt(long)]
+    /// Skips UI generation for this remote signal
+    pub no_ui: bool,
+
+    #[structopt(long)]
+    /// Skips test generation for this remote signal
+    pub no_spec: bool,
+}
+
+impl RemoteSignal {
+    pub fn run(self, template_type: &TemplateType, hooks: &TemplateHooks) -> anyhow::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let file_tree = load_directory_into_memory(&current_dir)?;
+        let name = match self.name {
+            Some(n) => {
+                check_case(&n, "remote signal name", Case::Snake)?;
+                n
+            }
+            None => input_with_case("Remote signal name (snake_case):", None, Case::Snake)?,
+        };
+
+        let dna_file_tree = DnaFileTree::get_or_choose(file_tree, self.dna.as_deref())?;
+        let zome_file_tree =
+            ZomeFileTree::get_or_choose_coordinator(dna_file_tree, self.zome.as_deref())?;
+
+        let dna_name = zome_file_tree.dna_file_tree.dna_manifest.name().to_string();
+        let zome_name = zome_file_tree.zome_manifest.name.to_string();
+
+        let ScaffoldedTemplate {
+            file_tree,
+            next_instructions,
+        } = scaffold_remote_signal(
+            zome_file_tree,
+            &template_type.file_tree()?,
+            &name,
+            &self.payload.unwrap_or_default(),
+            self.no_ui,
+            self.no_spec,
+        )?;
+
+        build_file_tree(file_tree, ".")?;
+
+        if let Err(e) = run_cargo_fmt_if_available() {
+            println!(
+                "{}: {}",
+                "rustfmt exec failed: ".yellow(),
+                e.to_string().yellow()
+            );
+        }
+
+        hooks.run(
+            &current_dir,
+            &[
+                ("DNA_NAME", &dna_name),
+                ("ZOME_NAME", &zome_name),
+                ("REMOTE_SIGNAL_NAME", &name),
+            ],
+        );
+
+        println!("\nRemote signal {} scaffolded!\n", name.italic());
+        if let Some(i) = next_instructions {
+            println!("{i}");
+        }
+
+        Ok(())
+    }
+}
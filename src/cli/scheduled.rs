@@ -0,0 +1,103 @@
+use colored::Colorize;
+use convert_case::Case;
+use structopt::StructOpt;
+
+use crate::{
+    file_tree::{build_file_tree, load_directory_into_memory},
+    scaffold::{
+        dna::DnaFileTree,
+        scheduled::{scaffold_scheduled, ScheduleTrigger},
+        web_app::{template_hooks::TemplateHooks, template_type::TemplateType},
+        zome::ZomeFileTree,
+    },
+    templates::ScaffoldedTemplate,
+    utils::{check_case, input_with_case, run_cargo_fmt_if_available},
+};
+
+#[derive(Debug, StructOpt)]
+/// Scaffold a function that the conductor runs on a schedule into an existing coordinator zome
+pub struct Scheduled {
+    #[structopt(long)]
+    /// Name of the dna in which you want to scaffold the scheduled function
+    pub dna: Option<String>,
+
+    #[structopt(long)]
+    /// Name of the coordinator zome in which you want to scaffold the scheduled function
+    pub zome: Option<String>,
+
+    /// Name of the scheduled function being scaffolded
+    pub name: Option<String>,
+
+    #[structopt(long, conflicts_with = "cron")]
+    /// Run the function every given duration, eg. "30s", "5m", "1h" or "1d"
+    /// The schedule is ephemeral: it doesn't survive a restart of the conductor
+    pub every: Option<String>,
+
+    #[structopt(long)]
+    /// Run the function on the given cron schedule, with seconds, eg. "0 */5 * * * *"
+    /// The schedule is persisted: it survives restarts of the conductor
+    pub cron: Option<String>,
+
+    #[structopt(long)]
+    /// Skips test generation for this scheduled function
+    pub no_spec: bool,
+}
+
+impl Scheduled {
+    pub fn run(self, template_type: &TemplateType, hooks: &TemplateHooks) -> anyhow::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let file_tree = load_directory_into_memory(&current_dir)?;
+        let name = match self.name {
+            Some(n) => {
+                check_case(&n, "function name", Case::Snake)?;
+                n
+            }
+            None => input_with_case("Scheduled function name (snake_case):", None, Case::Snake)?,
+        };
+        let trigger = ScheduleTrigger::from_args(self.every.as_deref(), self.cron.as_deref())?;
+
+        let dna_file_tree = DnaFileTree::get_or_choose(file_tree, self.dna.as_deref())?;
+        let zome_file_tree =
+            ZomeFileTree::get_or_choose_coordinator(dna_file_tree, self.zome.as_deref())?;
+
+        let dna_name = zome_file_tree.dna_file_tree.dna_manifest.name().to_string();
+        let zome_name = zome_file_tree.zome_manifest.name.to_string();
+
+        let ScaffoldedTemplate {
+            file_tree,
+            next_instructions,
+        } = scaffold_scheduled(
+            zome_file_tree,
+            &template_type.file_tree()?,
+            &name,
+            &trigger,
+            self.no_spec,
+        )?;
+
+        build_file_tree(file_tree, ".")?;
+
+        if let Err(e) = run_cargo_fmt_if_available() {
+            println!(
+                "{}: {}",
+                "rustfmt exec failed: ".yellow(),
+                e.to_string().yellow()
+            );
+        }
+
+        hooks.run(
+            &current_dir,
+            &[
+                ("DNA_NAME", &dna_name),
+                ("ZOME_NAME", &zome_name),
+                ("FUNCTION_NAME", &name),
+            ],
+        );
+
+        println!("\nScheduled function {} scaffolded!\n", name.italic());
+        if let Some(i) = next_instructions {
+            println!("{i}");
+        }
+
+        Ok(())
+    }
+}
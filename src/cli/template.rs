@@ -1,9 +1,31 @@
-use std::{ffi::OsString, path::PathBuf};
+use std::{ffi::OsString, fs, path::PathBuf, str::FromStr};
 
 use build_fs_tree::{dir, file, Build, MergeableFileSystemTree};
+use colored::Colorize;
 use structopt::StructOpt;
 
-use crate::{scaffold::web_app::template_type::TemplateType, utils::input_with_case};
+use crate::{
+    error::ScaffoldError,
+    file_tree::{build_file_tree, load_directory_into_memory},
+    scaffold::{
+        app::AppFileTree,
+        config::ScaffoldConfig,
+        entry_type::definitions::FieldType,
+        reference_app::{
+            check_formatting, compare_with_snapshot, scaffold_reference_app, write_snapshot,
+        },
+        web_app::{
+            template_manifest::{TemplateManifest, TEMPLATE_MANIFEST_FILE},
+            template_source::GitTemplate,
+            template_type::TemplateType,
+            template_upgrade::{
+                render_web_app, stored_web_app_render, upgrade_web_app, UpgradedFile,
+            },
+        },
+    },
+    templates::{lint::lint_template, widget::widget_file_tree},
+    utils::{check_case, input_with_case},
+};
 
 #[derive(Debug, StructOpt)]
 #[structopt(setting = structopt::clap::AppSettings::InferSubcommands)]
@@ -16,6 +38,35 @@ pub enum Template {
         #[structopt(long)]
         /// The folder to initialize the template into, will end up at "<TO TEMPLATE>"
         to_template: Option<String>,
+
+        #[structopt(long)]
+        /// Only create a manifest extending the template in use, instead of copying all of its files
+        extend: bool,
+    },
+    /// Check the template in use for problems without scaffolding anything
+    Lint,
+    /// Scaffold a reference hApp in memory with the template in use, exercising every command,
+    /// field type and cardinality
+    Test {
+        #[structopt(long, parse(from_os_str))]
+        /// Directory with a previous output of this command to compare the reference hApp with
+        snapshot: Option<PathBuf>,
+
+        #[structopt(long, requires = "snapshot")]
+        /// Write the reference hApp to the snapshot directory instead of comparing with it
+        update_snapshot: bool,
+    },
+    /// Apply the changes of the template in use since the hApp was scaffolded to its web-app files,
+    /// marking the conflicts with the changes made to them
+    Upgrade,
+    /// Add a new widget for a field type to the custom template in use
+    Widget {
+        #[structopt(parse(try_from_str = FieldType::from_str))]
+        /// Field type the widget renders, e.g. "String" or "Timestamp"
+        field_type: FieldType,
+
+        /// Name of the widget, in PascalCase
+        widget: String,
     },
 }
 
@@ -23,7 +74,19 @@ impl Template {
     pub fn run(self, template_type: &TemplateType) -> anyhow::Result<()> {
         match self {
             Template::New => Template::new_template(template_type),
-            Template::Clone { to_template } => Template::clone_template(to_template, template_type),
+            Template::Clone {
+                to_template,
+                extend,
+            } => Template::clone_template(to_template, extend, template_type),
+            Template::Lint => Template::lint_template(template_type),
+            Template::Test {
+                snapshot,
+                update_snapshot,
+            } => Template::test_template(template_type, snapshot, update_snapshot),
+            Template::Upgrade => Template::upgrade_template(template_type),
+            Template::Widget { field_type, widget } => {
+                Template::add_widget(template_type, field_type, widget)
+            }
         }
     }
 
@@ -54,6 +117,7 @@ impl Template {
 
     fn clone_template(
         to_template: Option<String>,
+        extend: bool,
         template_type: &TemplateType,
     ) -> anyhow::Result<()> {
         let target_template = match to_template {
@@ -61,8 +125,26 @@ impl Template {
             None => input_with_case("Enter new template name:", None, convert_case::Case::Kebab)?,
         };
 
-        let template_file_tree = dir! {
-            target_template.clone() => template_type.file_tree()?
+        let template_file_tree = if extend {
+            let extends = match template_type {
+                TemplateType::Custom(path) | TemplateType::Tarball(path) => {
+                    path.canonicalize()?.to_string_lossy().to_string()
+                }
+                TemplateType::Git(git) => git.pinned_spec(),
+                _ => template_type.name(),
+            };
+            let manifest = toml::toml! {
+                extends = extends
+            };
+            dir! {
+                target_template.clone() => dir! {
+                    TEMPLATE_MANIFEST_FILE => file!(toml::to_string(&manifest)?)
+                }
+            }
+        } else {
+            dir! {
+                target_template.clone() => template_type.file_tree()?
+            }
         };
 
         let file_tree = MergeableFileSystemTree::<OsString, String>::from(template_file_tree);
@@ -73,4 +155,207 @@ impl Template {
 
         Ok(())
     }
+
+    fn lint_template(template_type: &TemplateType) -> anyhow::Result<()> {
+        let problems = lint_template(&template_type.file_tree()?);
+
+        if problems.is_empty() {
+            println!("No problems found in template {template_type}");
+            return Ok(());
+        }
+
+        for problem in &problems {
+            println!("{} {problem}", "error:".red());
+        }
+
+        Err(ScaffoldError::MalformedTemplate(format!(
+            "found {} problem(s) in template {}",
+            problems.len(),
+            template_type.name()
+        )))?
+    }
+
+    fn test_template(
+        template_type: &TemplateType,
+        snapshot: Option<PathBuf>,
+        update_snapshot: bool,
+    ) -> anyhow::Result<()> {
+        let file_tree = scaffold_reference_app(&template_type.file_tree()?)?;
+
+        let mut problems: Vec<String> = check_formatting(&file_tree)
+            .into_iter()
+            .map(|(path, error)| format!("{}: {error}", path.display()))
+            .collect();
+
+        match snapshot {
+            Some(snapshot_dir) if update_snapshot || !snapshot_dir.exists() => {
+                write_snapshot(file_tree, &snapshot_dir)?;
+                println!("Snapshot written to {}", snapshot_dir.display());
+            }
+            Some(snapshot_dir) => {
+                problems.extend(compare_with_snapshot(&file_tree, &snapshot_dir)?)
+            }
+            None => {}
+        }
+
+        if problems.is_empty() {
+            println!("Template {template_type} scaffolded the reference hApp successfully");
+            return Ok(());
+        }
+
+        for problem in &problems {
+            println!("{} {problem}", "error:".red());
+        }
+
+        Err(ScaffoldError::MalformedTemplate(format!(
+            "found {} problem(s) in template {}",
+            problems.len(),
+            template_type.name()
+        )))?
+    }
+
+    fn add_widget(
+        template_type: &TemplateType,
+        field_type: FieldType,
+        widget: String,
+    ) -> anyhow::Result<()> {
+        let TemplateType::Custom(template_path) = template_type else {
+            return Err(ScaffoldError::InvalidArguments(format!(
+                "Widgets can only be added to a custom template in a local folder, but the template in use is {}. Create one with `hc-scaffold template clone` first",
+                template_type.name()
+            )))?;
+        };
+        check_case(&widget, "widget name", convert_case::Case::Pascal)?;
+
+        let template_file_tree = template_type.file_tree()?;
+        let field_type_name = field_type.to_string();
+        let widget_file_tree = widget_file_tree(&template_file_tree, &field_type, &widget)?;
+        build_file_tree(
+            dir! {
+                "field-types" => dir! {
+                    field_type_name.clone() => dir! {
+                        widget.clone() => widget_file_tree
+                    }
+                }
+            },
+            template_path,
+        )?;
+
+        // Templates with a manifest only offer the widgets it declares
+        let manifest_path = template_path.join(TEMPLATE_MANIFEST_FILE);
+        if manifest_path.exists() {
+            let mut widgets = TemplateManifest::from_template_file_tree(&template_file_tree)?
+                .and_then(|mut manifest| manifest.widgets.remove(&field_type_name))
+                .unwrap_or_default();
+            widgets.push(widget.clone());
+            let manifest = TemplateManifest::declare_widgets(
+                &fs::read_to_string(&manifest_path)?,
+                &field_type_name,
+                &widgets,
+            )?;
+            fs::write(&manifest_path, manifest)?;
+        }
+
+        println!(
+            "Widget created in: {}",
+            template_path
+                .join("field-types")
+                .join(&field_type_name)
+                .join(&widget)
+                .display()
+        );
+
+        Ok(())
+    }
+
+    fn upgrade_template(template_type: &TemplateType) -> anyhow::Result<()> {
+        let app_dir = std::env::current_dir()?;
+        let Some(mut scaffold_config) = ScaffoldConfig::from_package_json_path(&app_dir)? else {
+            return Err(ScaffoldError::InvalidArguments(
+                "No hApp scaffolded with hc-scaffold was found in the current directory"
+                    .to_string(),
+            ))?;
+        };
+
+        let app_file_tree =
+            AppFileTree::get_or_choose(load_directory_into_memory(&app_dir)?, None)?;
+        let app_name = app_file_tree.app_manifest.app_name().to_string();
+        let mut app_file_tree = app_file_tree.file_tree();
+
+        let render = render_web_app(
+            &template_type.file_tree()?,
+            &app_name,
+            &scaffold_config.vars,
+        )?;
+        let base = match (stored_web_app_render(&app_file_tree)?, template_type) {
+            (Some(base), _) => Some(base),
+            // Git templates can render again the version the hApp was scaffolded with
+            (None, TemplateType::Git(git)) if scaffold_config.template_commit.is_some() => {
+                let scaffolded_template = TemplateType::Git(GitTemplate {
+                    commit: scaffold_config.template_commit.clone(),
+                    base_commits: scaffold_config.template_base_commits.clone(),
+                    ..git.clone()
+                });
+                Some(render_web_app(
+                    &scaffolded_template.file_tree()?,
+                    &app_name,
+                    &scaffold_config.vars,
+                )?)
+            }
+            (None, _) => None,
+        };
+
+        if scaffold_config.template.is_some()
+            && (scaffold_config.template_commit.as_deref() != template_type.commit()
+                || scaffold_config.template_base_commits != template_type.base_commits())
+        {
+            scaffold_config.template_commit = template_type.commit().map(String::from);
+            scaffold_config.template_base_commits = template_type.base_commits();
+            scaffold_config.write_to_package_json(&mut app_file_tree)?;
+        }
+
+        let upgraded_files = upgrade_web_app(&mut app_file_tree, base.as_ref(), &render)?;
+        build_file_tree(app_file_tree, &app_dir)?;
+
+        if upgraded_files.is_empty() {
+            println!("The web-app files are up to date with template {template_type}");
+            return Ok(());
+        }
+
+        let mut conflicted_files = 0;
+        for (path, upgraded_file) in &upgraded_files {
+            let status = match upgraded_file {
+                UpgradedFile::Added => "added".green(),
+                UpgradedFile::Updated => "updated".green(),
+                UpgradedFile::Conflicted(conflicts) => {
+                    conflicted_files += 1;
+                    format!("{conflicts} conflict(s)").red()
+                }
+                UpgradedFile::Removed => {
+                    fs::remove_file(app_dir.join(path))?;
+                    "removed".yellow()
+                }
+                UpgradedFile::Kept => "kept, removed from the template".yellow(),
+            };
+            println!("  {}: {status}", path.display());
+        }
+
+        println!(
+            "\nUpgraded {} file(s) to template {template_type}",
+            upgraded_files.len()
+        );
+        if base.is_none() {
+            println!(
+                "{}",
+                "No previous render of the template was found, so every difference with it was marked as a conflict".yellow()
+            );
+        }
+        if conflicted_files > 0 {
+            println!(
+                "Resolve the conflicts between the \"<<<<<<< current\" and \">>>>>>> template\" markers in {conflicted_files} file(s)"
+            );
+        }
+
+        Ok(())
+    }
 }
@@ -18,7 +18,10 @@ use crate::{
         web_app::{
             npm::{Npm, SubCommand},
             scaffold_web_app,
+            template_hooks::TemplateHooks,
+            template_manifest::TemplateVars,
             template_type::TemplateType,
+            template_upgrade::{render_web_app, store_web_app_render},
         },
         zome::scaffold_zome_pair,
     },
@@ -45,7 +48,12 @@ pub struct WebApp {
 }
 
 impl WebApp {
-    pub async fn run(self, template_type: &TemplateType) -> anyhow::Result<()> {
+    pub async fn run(
+        self,
+        template_type: &TemplateType,
+        template_vars: TemplateVars,
+        hooks: &TemplateHooks,
+    ) -> anyhow::Result<()> {
         let command_root_dir = std::env::current_dir()?;
         let name = match self.name {
             Some(n) => {
@@ -82,10 +90,22 @@ impl WebApp {
             self.description.as_deref(),
             !setup_nix,
             &template_file_tree,
+            &template_vars,
         )?;
 
-        if !template_type.is_nixified_custom_template() {
-            ScaffoldConfig::write_to_package_json(&mut file_tree, template_type)?;
+        store_web_app_render(
+            &mut file_tree,
+            &render_web_app(&template_file_tree, &name, &template_vars)?,
+        )?;
+
+        let scaffold_config = ScaffoldConfig {
+            template: (!template_type.is_nixified_custom_template()).then(|| template_type.clone()),
+            template_commit: template_type.commit().map(String::from),
+            template_base_commits: template_type.base_commits(),
+            vars: template_vars,
+        };
+        if scaffold_config.template.is_some() || !scaffold_config.vars.is_empty() {
+            scaffold_config.write_to_package_json(&mut file_tree)?;
         }
 
         build_file_tree(file_tree, &app_folder)?;
@@ -114,6 +134,8 @@ impl WebApp {
 
         setup_git_environment(&app_folder)?;
 
+        hooks.run(&app_folder, &[("APP_NAME", &name)]);
+
         if let Some(instructions) = next_instructions {
             println!("{instructions}");
         } else {
@@ -169,10 +191,11 @@ Here's how you can get started with developing your application:
         let app_file_tree = AppFileTree::get_or_choose(file_tree, Some(name))?;
 
         let ScaffoldedTemplate { file_tree, .. } =
-            scaffold_dna(app_file_tree, &template_file_tree, &dna_name)?;
+            scaffold_dna(app_file_tree, &template_file_tree, &dna_name, 0, false)?;
 
         if input_yes_or_no("Do you want to scaffold an initial coordinator/integrity zome pair for your DNA? (y/n)", None)? {
-            scaffold_zome_pair(file_tree, template_file_tree, &dna_name)?;
+            let file_tree = scaffold_zome_pair(file_tree, &template_file_tree, &dna_name, None)?;
+            build_file_tree(file_tree, ".")?;
             println!("Coordinator/integrity zome pair scaffolded.")
         } else {
             build_file_tree(file_tree, ".")?;
@@ -10,7 +10,7 @@ use crate::{
     scaffold::{
         app::cargo::exec_metadata,
         dna::DnaFileTree,
-        web_app::template_type::TemplateType,
+        web_app::{template_hooks::TemplateHooks, template_type::TemplateType},
         zome::{
             integrity_zome_name, scaffold_coordinator_zome, scaffold_integrity_zome,
             utils::{select_integrity_zomes, select_scaffold_zome_options},
@@ -40,7 +40,7 @@ pub struct Zome {
 }
 
 impl Zome {
-    pub fn run(self, template_type: &TemplateType) -> anyhow::Result<()> {
+    pub fn run(self, template_type: &TemplateType, hooks: &TemplateHooks) -> anyhow::Result<()> {
         let current_dir = std::env::current_dir()?;
         let file_tree = load_directory_into_memory(&current_dir)?;
         let template_file_tree = template_type.file_tree()?;
@@ -68,6 +68,7 @@ impl Zome {
 
         let mut dna_file_tree = DnaFileTree::get_or_choose(file_tree, self.dna.as_deref())?;
         let dna_manifest_path = dna_file_tree.dna_manifest_path.clone();
+        let mut hook_names = vec![("DNA_NAME", dna_file_tree.dna_manifest.name().to_string())];
 
         let mut zome_next_instructions: (Option<String>, Option<String>) = Default::default();
 
@@ -88,6 +89,7 @@ impl Zome {
             )?;
 
             zome_next_instructions.0 = next_instructions;
+            hook_names.push(("INTEGRITY_ZOME_NAME", integrity_zome_name.clone()));
 
             println!(
                 "Integrity zome {} scaffolded!\n",
@@ -119,6 +121,7 @@ impl Zome {
                 &self.coordinator,
             )?;
             zome_next_instructions.1 = next_instructions;
+            hook_names.push(("COORDINATOR_ZOME_NAME", name.clone()));
 
             println!("Coordinator zome {} scaffolded!\n", name.italic());
 
@@ -144,6 +147,12 @@ impl Zome {
         // Execute cargo metadata to set up the cargo workspace in case this zome is the first crate
         exec_metadata(&f)?;
 
+        let hook_names: Vec<(&str, &str)> = hook_names
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect();
+        hooks.run(&current_dir, &hook_names);
+
         match zome_next_instructions {
             (Some(integrity), Some(coordinator)) => {
                 println!("\n{integrity}");
@@ -0,0 +1,67 @@
+use colored::Colorize;
+use structopt::StructOpt;
+
+use crate::{
+    file_tree::{build_file_tree, load_directory_into_memory},
+    scaffold::{
+        dna::DnaFileTree, web_app::template_hooks::TemplateHooks, zome_call::scaffold_zome_call,
+    },
+    utils::run_cargo_fmt_if_available,
+};
+
+#[derive(Debug, StructOpt)]
+/// Scaffold a typed function calling a zome function of another coordinator zome of the same DNA
+pub struct ZomeCall {
+    #[structopt(long)]
+    /// Name of the dna in which you want to scaffold the zome call
+    pub dna: Option<String>,
+
+    #[structopt(long)]
+    /// Name of the coordinator zome in which the wrapper function is scaffolded
+    pub from: Option<String>,
+
+    #[structopt(long)]
+    /// Name of the coordinator zome whose function is called
+    pub to: Option<String>,
+
+    #[structopt(long)]
+    /// Name of the function to call
+    pub function: Option<String>,
+}
+
+impl ZomeCall {
+    pub fn run(self, hooks: &TemplateHooks) -> anyhow::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let file_tree = load_directory_into_memory(&current_dir)?;
+
+        let dna_file_tree = DnaFileTree::get_or_choose(file_tree, self.dna.as_deref())?;
+        let zome_file_tree = scaffold_zome_call(
+            dna_file_tree,
+            self.from.as_deref(),
+            self.to.as_deref(),
+            self.function.as_deref(),
+        )?;
+
+        let dna_name = zome_file_tree.dna_file_tree.dna_manifest.name().to_string();
+        let zome_name = zome_file_tree.zome_manifest.name.to_string();
+
+        build_file_tree(zome_file_tree.dna_file_tree.file_tree(), ".")?;
+
+        if let Err(e) = run_cargo_fmt_if_available() {
+            println!(
+                "{}: {}",
+                "rustfmt exec failed: ".yellow(),
+                e.to_string().yellow()
+            );
+        }
+
+        hooks.run(
+            &current_dir,
+            &[("DNA_NAME", &dna_name), ("ZOME_NAME", &zome_name)],
+        );
+
+        println!("\nZome call scaffolded!\n");
+
+        Ok(())
+    }
+}
@@ -35,6 +35,9 @@ pub enum ScaffoldError {
     #[error("Error setting up the git repository")]
     GitInitError,
 
+    #[error("Git error: {0}")]
+    GitError(#[from] git2::Error),
+
     #[error(transparent)]
     IgnoreError(#[from] ignore::Error),
 
@@ -80,6 +83,9 @@ pub enum ScaffoldError {
     #[error("Malformed template: {0}")]
     MalformedTemplate(String),
 
+    #[error("Template \"{0}\" can't be used: {1}")]
+    IncompatibleTemplate(String, String),
+
     #[error("DNA \"{0}\" already exists")]
     DnaAlreadyExists(String),
 
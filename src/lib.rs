@@ -55,6 +55,12 @@
 //!   - "headless" (no ui)
 //! - Or a path to a custom template.
 //!   - E.g `hc-scaffold --template ./path/to/custom/template/folder web-app`
+//! - Or a path to a `.tar.gz` tarball of a custom template.
+//!   - E.g `hc-scaffold --template ./template.tar.gz web-app`
+//! - Or a git repository containing a custom template, with an optional branch, tag or commit.
+//!   - E.g `hc-scaffold --template git+https://github.com/org/template.git#v0.1.0 web-app`
+//!
+//! Git repositories and tarballs are fetched into a cache in the user's data directory (e.g. `~/.local/share/hc-scaffold/templates`). Tarballs are only extracted again when they change.
 //!
 //! If you know of some already existing custom template, look first in the documentation of that template for instructions on how to use it, in case the template offers a nix wrapper command, which is much easier to use.
 //!
@@ -80,6 +86,14 @@
 //! pass the `--template` flag for every command. This also provides a guardrail to prevent mixing up templates
 //! for different `hc-scaffold` commands.
 //!
+//! For a git template, the config also records the commit its branch or tag pointed to in `"templateCommit"`, so that
+//! every command in the project uses the same version of the template even if the branch moves on. The commits of the git
+//! templates it extends are recorded as well, in `"templateBaseCommits"`. Pass `--template-update` to any command to fetch
+//! the templates again and record their latest commits.
+//!
+//! When a newer version of the template is available, `hc-scaffold template upgrade` applies its changes to the `web-app`
+//! files of the project with a three-way merge, marking the places where they overlap with changes made to the files.
+//!
 //! ## How to create a custom template
 //!
 //! Creating and maintaining your own template can be challenging at first, so look for existing templates that you can reuse before diving in to create your own.
@@ -208,6 +222,76 @@
 //!
 //! This will get replaced by the contents of the file `field-types/String/TextArea/detail/render.hbs`.
 //!
+//! To add a new widget to a custom template, run `hc-scaffold template widget <FIELD_TYPE> <WIDGET_NAME>`: it creates its partials with a comment documenting the variables each of them gets, and declares the widget in the template manifest.
+//!
+//! ### Template manifest
+//!
+//! A template can describe itself with a `template.toml` file at its root:
+//!
+//! ```toml
+//! name = "my-template"
+//! hc-scaffold-version = "0.700"
+//! commands = ["entry-type", "link-type", "collection", "example"]
+//!
+//! [widgets]
+//! String = ["TextArea", "TextField"]
+//! bool = ["Checkbox"]
+//! ```
+//!
+//! `hc-scaffold-version` is the range of versions of the scaffolding tool the template is compatible with, and using the template with any other version fails right away. The `entry-type`, `link-type`, `collection` and `example` commands will also refuse to run if they are not listed in `commands`. Only the widgets listed in `widgets` are offered to the user when choosing how to render a field, even if the `field-types` folder contains others.
+//!
+//! The manifest is optional: templates without one are used as is.
+//!
+//! ### Template variables
+//!
+//! If your template needs inputs that are not part of the data of a command, like a theme or a license header, it can declare them in the `variables` section of its manifest, keyed by the name of the command that asks for them:
+//!
+//! ```toml
+//! [[variables.web-app]]
+//! name = "license"
+//! prompt = "Which license header should the generated files have?"
+//! default = "MIT"
+//!
+//! [[variables.entry-type]]
+//! name = "page_size"
+//! type = "number" # "string" (the default), "bool" or "number"
+//! ```
+//!
+//! The scaffolding tool prompts the user for the variables of the command being run, unless their values were given with `--var key=value` or were saved when running a previous command. The values are saved in the `hcScaffold` section of the root `package.json` file of the hApp, and are available in all the templates under `vars`, e.g. `{{vars.license}}`.
+//!
+//! ### Extending templates
+//!
+//! Instead of copying a whole template, a custom template can extend another one by adding an `extends` key to its manifest, with either the name of a built-in template (`svelte` or `headless`), a path relative to the custom template's folder, a `.tar.gz` tarball or a git repository given as `git+<url>#<ref>`:
+//!
+//! ```toml
+//! extends = "svelte"
+//! ```
+//!
+//! The custom template then only needs to contain the files it overrides or adds: they are layered on top of the files of the extended template, which can itself extend another one. Every other key of the manifest is inherited from the extended template unless overridden, with `widgets` being overridden per field type. This way a template extending `svelte` picks up the fixes to the built-in template when the scaffolding tool is upgraded.
+//!
+//! Run `hc scaffold template clone --extend` to create a new template that extends the one in use.
+//!
+//! ### Hooks
+//!
+//! Your template can run its own commands after the scaffolded files are written, like installing dependencies or regenerating an index file, by declaring them in the `hooks` section of its manifest, keyed by the name of the command that runs them:
+//!
+//! ```toml
+//! [[hooks.web-app]]
+//! command = "npm install"
+//!
+//! [[hooks.entry-type]]
+//! command = "npm run generate-icons"
+//! working-dir = "ui"
+//! ```
+//!
+//! The hooks run in order, from the root of the hApp unless a `working-dir` is given, and their output is shown to the user. If a hook fails, the remaining hooks are skipped. Each hook gets these environment variables:
+//!
+//! - `HC_SCAFFOLD_COMMAND`: the name of the command that was run.
+//! - The names of what was scaffolded, which depend on the command: `HC_SCAFFOLD_APP_NAME`, `HC_SCAFFOLD_DNA_NAME`, `HC_SCAFFOLD_ZOME_NAME`, `HC_SCAFFOLD_INTEGRITY_ZOME_NAME`, `HC_SCAFFOLD_COORDINATOR_ZOME_NAME`, `HC_SCAFFOLD_ENTRY_TYPE_NAME`, `HC_SCAFFOLD_COLLECTION_NAME`, `HC_SCAFFOLD_FUNCTION_NAME` and `HC_SCAFFOLD_REMOTE_SIGNAL_NAME`.
+//! - `HC_SCAFFOLD_VAR_<NAME>` for each [template variable](#template-variables).
+//!
+//! Users can skip the hooks by passing `--no-hooks`.
+//!
 //! ### Instructions
 //!
 //! Additionally to the folders, you can override the built-in instructions that get shown to the user after each command. The scaffolding tool will look for a file named `<COMMAND>.instructions.hbs` in the folder for the custom template, and if it exists, render its contents and display them to the user. The name of the `COMMAND` for the file matches the names for the folders where the templates for each command exist.
@@ -228,7 +312,40 @@
 //!   - `lower_case`: converts the string to lower case.
 //!   - `snake_case`: converts the string to snake case.
 //!   - `camel_case`: converts the string to camel case.
-//! - `plural`: converts the given string to its plural.
+//! - `plural` and `singular`: convert the given string to its plural or singular. `plural` takes an optional count, and renders the singular if it's 1.
+//!   - Example usage:
+//! ```hbs
+//! {{plural entry_type.name}} {{plural "comment" comments_count}}
+//! ```
+//! - Collection helpers:
+//!   - `join`: joins the elements of a list with the given separator, e.g. `{{join enum_variants ", "}}`.
+//!   - `split`: splits a string by the given separator into a list.
+//!   - `length`: gets the number of elements of a list or an object, or the number of characters of a string.
+//!   - `first` and `last`: get the first and last elements of a list.
+//!   - `sort_by`: sorts a list of objects by the value at the given dot separated path, with an optional `reverse=true`.
+//!   - `group_by`: groups a list of objects by the value at the given dot separated path, into an object of lists.
+//!   - Example usage:
+//! ```hbs
+//! {{#each (group_by (sort_by entry_type.fields "field_name") "field_type.type")}}
+//! // {{@key}}: {{length this}} fields
+//! {{/each}}
+//! ```
+//! - Formatting helpers:
+//!   - `json`: serializes the given value to JSON, with an optional `pretty=true`.
+//!   - `indent`: indents every non-blank line of the string by the given number of spaces.
+//!   - `default`: renders the first argument, or the second one if the first is missing or empty.
+//!   - Example usage:
+//! ```hbs
+//! const config = {{json (default vars.config "{}")}};
+//! {{indent previous_scope_content 2}}
+//! ```
+//! - `lookup_field_type`: gets the TypeScript (`"ts"`) or Rust (`"rust"`) type of a field type, given either as a field type name or as the `field_type` of a field.
+//!   - Example usage:
+//! ```hbs
+//! {{#each entry_type.fields}}
+//!   {{camel_case field_name}}: {{lookup_field_type field_type "ts"}};
+//! {{/each}}
+//! ```
 //! - `concat`: concatenize strings.
 //! - `contains`: check whether list contains an element.
 //!   - Example usage:
@@ -1,8 +1,18 @@
 pub mod app;
+pub mod bridge;
+pub mod capability;
 pub mod collection;
 pub mod config;
 pub mod dna;
 pub mod entry_type;
+pub mod file_storage;
+pub mod function;
 pub mod link_type;
+pub mod membrane;
+pub mod reference_app;
+pub mod relation;
+pub mod remote_signal;
+pub mod scheduled;
 pub mod web_app;
 pub mod zome;
+pub mod zome_call;
@@ -159,6 +159,24 @@ pub fn workspace_package_path(
     Ok(package_path)
 }
 
+/// Path from the workspace root of a crate added with [`add_workspace_path_dependency`],
+/// which is known even before the crate has been written to disk
+pub fn workspace_path_dependency(
+    app_file_tree: &FileTree,
+    crate_name: &str,
+) -> ScaffoldResult<Option<PathBuf>> {
+    let cargo_toml = get_workspace_cargo_toml(app_file_tree)?;
+
+    let path = cargo_toml
+        .get("workspace")
+        .and_then(|workspace| workspace.get("dependencies"))
+        .and_then(|dependencies| dependencies.get(crate_name))
+        .and_then(|dependency| dependency.get("path"))
+        .and_then(|path| path.as_str())
+        .map(PathBuf::from);
+    Ok(path)
+}
+
 pub fn get_workspace_members(app_file_tree: &FileTree) -> ScaffoldResult<Vec<String>> {
     let cargo_toml = get_workspace_cargo_toml(app_file_tree)?;
 
@@ -0,0 +1,389 @@
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use convert_case::{Case, Casing};
+use dialoguer::{theme::ColorfulTheme, Select};
+use holochain_types::prelude::{DnaManifest, ZomeManifest};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::{file_exists, insert_file, map_file, map_rust_files, FileTree},
+    templates::{bridge::scaffold_bridge_templates, ScaffoldedTemplate},
+};
+
+use super::{
+    app::AppFileTree,
+    dna::DnaFileTree,
+    zome::{
+        coordinator::{
+            find_all_extern_functions, find_extern_function_in_zomes,
+            find_referenced_type_definitions, item_ident, ExternFunctionSignature, SweettestInput,
+        },
+        ZomeFileTree,
+    },
+};
+
+/// A coordinator zome of a DNA, given as `<dna>/<coordinator>` or just `<dna>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZomeTarget {
+    pub dna: String,
+    pub zome: Option<String>,
+}
+
+impl FromStr for ZomeTarget {
+    type Err = ScaffoldError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (dna, zome) = match s.split_once('/') {
+            Some((dna, zome)) => (dna, Some(zome)),
+            None => (s, None),
+        };
+        if dna.is_empty() || zome.is_some_and(|z| z.is_empty() || z.contains('/')) {
+            return Err(ScaffoldError::InvalidArguments(format!(
+                "Invalid zome \"{s}\": use the format \"<dna>/<coordinator zome>\""
+            )));
+        }
+        Ok(ZomeTarget {
+            dna: dna.to_string(),
+            zome: zome.map(|z| z.to_string()),
+        })
+    }
+}
+
+/// Scaffolds a typed function in the `from` coordinator zome that calls the given function of
+/// the `to` coordinator zome, in the cell of another role of the same hApp, and a sweettest
+/// installing the app with both roles
+pub fn scaffold_bridge(
+    file_tree: FileTree,
+    template_file_tree: &FileTree,
+    from: Option<&ZomeTarget>,
+    to: Option<&ZomeTarget>,
+    maybe_function: Option<&str>,
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    let dna_file_tree = DnaFileTree::get_or_choose(file_tree, from.map(|f| f.dna.as_str()))?;
+    let from_zome_file_tree = ZomeFileTree::get_or_choose_coordinator(
+        dna_file_tree,
+        from.and_then(|f| f.zome.as_deref()),
+    )?;
+    let from_dna_manifest_path = from_zome_file_tree.dna_file_tree.dna_manifest_path.clone();
+    let from_dna_name = from_zome_file_tree.dna_file_tree.dna_manifest.name();
+    let from_zome_manifest = from_zome_file_tree.zome_manifest.clone();
+    let from_crate_path = from_zome_file_tree.zome_crate_path.clone();
+
+    let to_dna_file_tree = DnaFileTree::get_or_choose(
+        from_zome_file_tree.dna_file_tree.file_tree_ref().clone(),
+        to.map(|t| t.dna.as_str()),
+    )?;
+    let to_dna_name = to_dna_file_tree.dna_manifest.name();
+    if to_dna_name == from_dna_name {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "a bridge calls a function of another DNA, but both zomes are in the {from_dna_name} DNA"
+        )));
+    }
+
+    let to_coordinator_zomes = match to.and_then(|t| t.zome.as_deref()) {
+        Some(zome_name) => vec![coordinator_zome(&to_dna_file_tree.dna_manifest, zome_name)?],
+        None => match &to_dna_file_tree.dna_manifest {
+            DnaManifest::V0(v0) => v0.coordinator.zomes.clone(),
+        },
+    };
+    if to_coordinator_zomes.is_empty() {
+        return Err(ScaffoldError::NoCoordinatorZomesFound(to_dna_name));
+    }
+
+    let function_name = match maybe_function {
+        Some(f) => f.to_string(),
+        None => choose_function(&to_dna_file_tree, &to_coordinator_zomes)?,
+    };
+    let Some((to_zome_manifest, item_fn)) =
+        find_extern_function_in_zomes(&to_dna_file_tree, &to_coordinator_zomes, &function_name)?
+    else {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "function {function_name} was not found in the coordinator zomes of the {to_dna_name} DNA"
+        )));
+    };
+    let bridged_function = ExternFunctionSignature::from_item_fn(&item_fn)?;
+
+    let to_zome_file_tree = ZomeFileTree::from_zome_manifest(to_dna_file_tree, to_zome_manifest)?;
+    let mirrored_types = find_referenced_type_definitions(&to_zome_file_tree, &bridged_function)?;
+    // The sample functions of the tests of the other zome are out of reach of this zome's tests
+    let sweettest_input = match &bridged_function.input_type {
+        Some(input_type) => match SweettestInput::for_type(&to_zome_file_tree, input_type)? {
+            SweettestInput::Sample { .. } => Some(SweettestInput::Placeholder),
+            sweettest_input => Some(sweettest_input),
+        },
+        None => None,
+    };
+    let to_zome_manifest = to_zome_file_tree.zome_manifest.clone();
+    let to_dna_bundle_path = to_zome_file_tree
+        .dna_file_tree
+        .dna_manifest_path
+        .with_file_name(format!("{to_dna_name}.dna"));
+
+    let bridge_fn_name = bridge_fn_name(&to_dna_name, &function_name);
+    let already_exists = find_all_extern_functions(&from_zome_file_tree)?
+        .iter()
+        .any(|f| f.sig.ident == bridge_fn_name);
+    if already_exists {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "function {bridge_fn_name} already exists in zome {}",
+            from_zome_manifest.name
+        )));
+    }
+
+    let bridge_items: syn::File = syn::parse2(bridge_fn(
+        &to_dna_name,
+        &to_zome_manifest.name.to_string(),
+        &bridged_function,
+    ))?;
+
+    let mut file_tree = from_zome_file_tree.dna_file_tree.file_tree();
+
+    // 1. Add the bridge function to the "<dna>_bridge" module of the caller zome
+    let crate_src_path = from_crate_path.join("src");
+    let bridge_module = bridge_module_name(&to_dna_name);
+    let bridge_file_name = format!("{bridge_module}.rs");
+    let bridge_file_path = crate_src_path.join(&bridge_file_name);
+    if !file_exists(&file_tree, &bridge_file_path) {
+        insert_file(&mut file_tree, &bridge_file_path, "use hdk::prelude::*;")?;
+        map_file(&mut file_tree, &crate_src_path.join("lib.rs"), |contents| {
+            Ok(format!(
+                r#"pub mod {bridge_module};
+{contents}"#
+            ))
+        })?;
+    }
+
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+    map_rust_files(
+        file_tree
+            .path_mut(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        |file_path, mut file| {
+            if file_path == Path::new(&bridge_file_name) {
+                // 2. Mirror the types of the input and output of the function, that live in the other DNA
+                for mirrored_type in &mirrored_types {
+                    let ident = item_ident(&mirrored_type.item);
+                    if !file.items.iter().any(|i| item_ident(i) == ident) {
+                        file.items.push(mirrored_type.item.clone());
+                    }
+                }
+                file.items.extend(bridge_items.items.clone());
+            }
+            Ok(file)
+        },
+    )
+    .map_err(|e| match e {
+        ScaffoldError::MalformedFile(path, error) => {
+            ScaffoldError::MalformedFile(crate_src_path.join(path), error)
+        }
+        _ => e,
+    })?;
+
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &from_dna_manifest_path)?;
+
+    let app_file_tree = AppFileTree::get_or_choose(dna_file_tree.file_tree(), None)?;
+    let role_names: Vec<String> = app_file_tree
+        .app_manifest
+        .app_roles()
+        .into_iter()
+        .map(|r| r.name)
+        .collect();
+    if let Some(missing_role) = [&from_dna_name, &to_dna_name]
+        .into_iter()
+        .find(|dna| !role_names.contains(dna))
+    {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "the {} app doesn't have a {missing_role} role, add the DNA to the app before bridging to it",
+            app_file_tree.app_manifest.app_name()
+        )));
+    }
+    let app_name = app_file_tree.app_manifest.app_name().to_string();
+
+    scaffold_bridge_templates(
+        app_file_tree.file_tree(),
+        template_file_tree,
+        &app_name,
+        &from_dna_name,
+        &from_zome_manifest,
+        &to_dna_name,
+        &to_zome_manifest,
+        &relative_path(&from_crate_path, &to_dna_bundle_path),
+        &bridged_function,
+        sweettest_input.as_ref(),
+        no_spec,
+    )
+}
+
+pub fn bridge_module_name(to_dna_name: &str) -> String {
+    format!("{}_bridge", to_dna_name.to_case(Case::Snake))
+}
+
+pub fn bridge_fn_name(to_dna_name: &str, function_name: &str) -> String {
+    format!("call_{}_{function_name}", to_dna_name.to_case(Case::Snake))
+}
+
+fn coordinator_zome(dna_manifest: &DnaManifest, zome_name: &str) -> ScaffoldResult<ZomeManifest> {
+    let coordinator_zomes = match dna_manifest {
+        DnaManifest::V0(v0) => &v0.coordinator.zomes,
+    };
+    coordinator_zomes
+        .iter()
+        .find(|z| z.name.0.eq(zome_name))
+        .cloned()
+        .ok_or(ScaffoldError::CoordinatorZomeNotFound(
+            zome_name.to_string(),
+            dna_manifest.name(),
+        ))
+}
+
+fn choose_function(
+    dna_file_tree: &DnaFileTree,
+    coordinator_zomes: &[ZomeManifest],
+) -> ScaffoldResult<String> {
+    let mut function_names: Vec<String> = vec![];
+    for zome in coordinator_zomes {
+        let zome_file_tree = ZomeFileTree::from_zome_manifest(dna_file_tree.clone(), zome.clone())?;
+        function_names.extend(
+            find_all_extern_functions(&zome_file_tree)?
+                .into_iter()
+                .map(|f| f.sig.ident.to_string())
+                .filter(|name| name != "init" && name != "post_commit"),
+        );
+    }
+    if function_names.is_empty() {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "the {} DNA has no functions to call",
+            dna_file_tree.dna_manifest.name()
+        )));
+    }
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Which function should be called from the other DNA?")
+        .default(0)
+        .items(&function_names[..])
+        .interact()?;
+
+    Ok(function_names[selection].clone())
+}
+
+/// Path of `to` relative to the directory `from`, both relative to the root of the app
+fn relative_path(from: &Path, to: &Path) -> String {
+    let mut path = PathBuf::new();
+    for _ in from.components() {
+        path = path.join("..");
+    }
+    path.join(to).to_string_lossy().to_string()
+}
+
+fn bridge_fn(
+    to_dna_name: &str,
+    to_zome_name: &str,
+    bridged_function: &ExternFunctionSignature,
+) -> TokenStream {
+    let function_name = &bridged_function.name;
+    let bridge_fn_name = format_ident!("{}", bridge_fn_name(to_dna_name, function_name));
+    let output_type: syn::Type =
+        syn::parse_str(&bridged_function.output_type).expect("output type was parsed before");
+    let failed_call_message =
+        format!("Call to {function_name} in the {to_dna_name} role failed: {{response:?}}");
+
+    let (params, payload) = match &bridged_function.input_type {
+        Some(input_type) => {
+            let input_type: syn::Type =
+                syn::parse_str(input_type).expect("input type was parsed before");
+            (quote! { input: #input_type }, quote! { input })
+        }
+        None => (quote! {}, quote! { () }),
+    };
+
+    quote! {
+        #[hdk_extern]
+        pub fn #bridge_fn_name(#params) -> ExternResult<#output_type> {
+            let response = call(
+                CallTargetCell::OtherRole(#to_dna_name.into()),
+                ZomeName::from(#to_zome_name),
+                FunctionName::from(#function_name),
+                None,
+                #payload,
+            )?;
+            match response {
+                ZomeCallResponse::Ok(result) => result
+                    .decode()
+                    .map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string()))),
+                _ => Err(wasm_error!(WasmErrorInner::Guest(format!(#failed_call_message)))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::unparse_pretty;
+    use syn::parse_quote;
+
+    #[test]
+    fn parse_zome_targets() {
+        assert_eq!(
+            ZomeTarget::from_str("forum/posts").unwrap(),
+            ZomeTarget {
+                dna: "forum".to_string(),
+                zome: Some("posts".to_string()),
+            }
+        );
+        assert_eq!(ZomeTarget::from_str("forum").unwrap().zome, None);
+        assert!(ZomeTarget::from_str("forum/").is_err());
+        assert!(ZomeTarget::from_str("/posts").is_err());
+        assert!(ZomeTarget::from_str("forum/posts/extra").is_err());
+    }
+
+    #[test]
+    fn bridge_fn_calls_the_other_role() {
+        let bridged_function = ExternFunctionSignature::from_item_fn(&parse_quote! {
+            #[hdk_extern]
+            pub fn get_post(post_hash: ActionHash) -> ExternResult<Option<Record>> {
+                get(post_hash, GetOptions::default())
+            }
+        })
+        .unwrap();
+        let generated =
+            unparse_pretty(&syn::parse2(bridge_fn("forum", "posts", &bridged_function)).unwrap());
+
+        assert!(generated.contains(
+            "pub fn call_forum_get_post(input: ActionHash) -> ExternResult<Option<Record>>"
+        ));
+        assert!(generated.contains(r#"CallTargetCell::OtherRole("forum".into()),"#));
+        assert!(generated.contains(r#"ZomeName::from("posts"),"#));
+        assert!(generated.contains(r#"FunctionName::from("get_post"),"#));
+    }
+
+    #[test]
+    fn bridge_fn_without_input() {
+        let bridged_function = ExternFunctionSignature::from_item_fn(&parse_quote! {
+            #[hdk_extern]
+            pub fn get_all_posts() -> ExternResult<Vec<Link>> {
+                todo!()
+            }
+        })
+        .unwrap();
+        let generated =
+            unparse_pretty(&syn::parse2(bridge_fn("forum", "posts", &bridged_function)).unwrap());
+
+        assert!(generated.contains("pub fn call_forum_get_all_posts() -> ExternResult<Vec<Link>>"));
+        assert!(generated.contains("        (),\n"));
+        assert_eq!(
+            relative_path(
+                Path::new("dnas/teams/zomes/coordinator/teams"),
+                Path::new("dnas/forum/workdir/forum.dna")
+            ),
+            "../../../../../dnas/forum/workdir/forum.dna"
+        );
+    }
+}
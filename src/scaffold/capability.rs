@@ -0,0 +1,609 @@
+use std::{collections::BTreeMap, ffi::OsString, path::Path, str::FromStr};
+
+use convert_case::{Case, Casing};
+use dialoguer::{theme::ColorfulTheme, MultiSelect, Select};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use serde::Serialize;
+use syn::{parse_quote, ItemFn};
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::{
+        file_exists, find_map_rust_files, insert_file, map_file, map_rust_files, FileTree,
+    },
+    templates::{capability::scaffold_capability_templates, ScaffoldedTemplate},
+};
+
+use super::{
+    app::AppFileTree,
+    dna::DnaFileTree,
+    zome::{
+        coordinator::{add_stmt_to_init, module_path, ExternFunctionSignature, SweettestInput},
+        ZomeFileTree,
+    },
+};
+
+/// Who can call the functions of a capability grant
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CapabilityAccess {
+    /// Anyone can call the functions, the grant is created in `init`
+    Unrestricted,
+    /// Anyone holding the secret of the grant can call the functions
+    Transferable,
+    /// Only the assigned agents holding the secret of the grant can call the functions
+    Assigned,
+}
+
+impl CapabilityAccess {
+    pub fn choose() -> ScaffoldResult<CapabilityAccess> {
+        let options = [
+            CapabilityAccess::Unrestricted,
+            CapabilityAccess::Transferable,
+            CapabilityAccess::Assigned,
+        ];
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Who should be able to call the functions?")
+            .default(0)
+            .item("Anyone (unrestricted)")
+            .item("Anyone holding the secret of the grant (transferable)")
+            .item("Only the assigned agents, holding the secret of the grant (assigned)")
+            .interact()?;
+        Ok(options[selection])
+    }
+}
+
+impl FromStr for CapabilityAccess {
+    type Err = ScaffoldError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unrestricted" => Ok(CapabilityAccess::Unrestricted),
+            "transferable" => Ok(CapabilityAccess::Transferable),
+            "assigned" => Ok(CapabilityAccess::Assigned),
+            _ => Err(ScaffoldError::InvalidArguments(format!(
+                "Invalid access \"{s}\": only \"unrestricted\", \"transferable\" and \"assigned\" are allowed"
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for CapabilityAccess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            CapabilityAccess::Unrestricted => "unrestricted",
+            CapabilityAccess::Transferable => "transferable",
+            CapabilityAccess::Assigned => "assigned",
+        };
+        write!(f, "{str}")
+    }
+}
+
+/// An extern function covered by a capability grant, which other agents call remotely
+#[derive(Serialize, Debug, Clone)]
+pub struct GrantedFunction {
+    /// Path of the module the function is defined in, eg. `crate::post`
+    pub module_path: String,
+    #[serde(flatten)]
+    pub signature: ExternFunctionSignature,
+    /// How the sweettest builds the input of the function, if it has one
+    pub sweettest_input: Option<SweettestInput>,
+}
+
+/// Scaffolds a capability grant for the given functions of a coordinator zome, the helpers to
+/// claim it and to call the functions remotely, and sweettests calling them from a second agent
+pub fn scaffold_capability(
+    zome_file_tree: ZomeFileTree,
+    template_file_tree: &FileTree,
+    maybe_tag: Option<&str>,
+    maybe_functions: Option<&Vec<String>>,
+    maybe_access: Option<CapabilityAccess>,
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    let dna_manifest_path = zome_file_tree.dna_file_tree.dna_manifest_path.clone();
+    let zome_manifest = zome_file_tree.zome_manifest.clone();
+    let crate_src_path = zome_file_tree.zome_crate_path.join("src");
+
+    let zome_modules = zome_modules(&zome_file_tree)?;
+    let extern_functions: Vec<(String, ItemFn)> = zome_modules
+        .iter()
+        .flat_map(|(module_path, module)| {
+            module
+                .extern_functions
+                .iter()
+                .map(move |f| (module_path.clone(), f.clone()))
+        })
+        .collect();
+
+    let function_names = match maybe_functions {
+        Some(f) => f.clone(),
+        None => choose_functions(&extern_functions)?,
+    };
+    if function_names.is_empty() {
+        return Err(ScaffoldError::InvalidArguments(
+            "a capability grant needs at least one function".to_string(),
+        ));
+    }
+
+    let access = match maybe_access {
+        Some(a) => a,
+        None => CapabilityAccess::choose()?,
+    };
+    let tag = maybe_tag
+        .map(|t| t.to_string())
+        .unwrap_or_else(|| access.to_string());
+
+    let granted_functions = function_names
+        .iter()
+        .map(|name| {
+            let (module_path, item_fn) = extern_functions
+                .iter()
+                .find(|(_, f)| f.sig.ident == name)
+                .ok_or(ScaffoldError::InvalidArguments(format!(
+                    "function {name} was not found in zome {}",
+                    zome_manifest.name
+                )))?;
+            let signature = ExternFunctionSignature::from_item_fn(item_fn)?;
+            let sweettest_input = match &signature.input_type {
+                Some(input_type) => Some(SweettestInput::for_type(&zome_file_tree, input_type)?),
+                None => None,
+            };
+            Ok(GrantedFunction {
+                module_path: module_path.clone(),
+                signature,
+                sweettest_input,
+            })
+        })
+        .collect::<ScaffoldResult<Vec<GrantedFunction>>>()?;
+
+    let capability_items: syn::File =
+        syn::parse2(capability_items(&tag, access, &granted_functions))?;
+
+    if let Some(existing) = capability_items.items.iter().find_map(|item| match item {
+        syn::Item::Fn(item_fn) => extern_functions
+            .iter()
+            .any(|(_, f)| f.sig.ident == item_fn.sig.ident)
+            .then_some(item_fn.sig.ident.to_string()),
+        _ => None,
+    }) {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "function {existing} already exists in zome {}, choose another tag for the capability",
+            zome_manifest.name
+        )));
+    }
+
+    let mut file_tree = zome_file_tree.dna_file_tree.file_tree();
+
+    // 1. Add the capability functions to the "capabilities" module of the zome
+    let capabilities_file_path = crate_src_path.join("capabilities.rs");
+    if !file_exists(&file_tree, &capabilities_file_path) {
+        insert_file(
+            &mut file_tree,
+            &capabilities_file_path,
+            "use hdk::prelude::*;",
+        )?;
+        map_file(&mut file_tree, &crate_src_path.join("lib.rs"), |contents| {
+            Ok(format!(
+                r#"pub mod capabilities;
+{contents}"#
+            ))
+        })?;
+    }
+
+    let create_grant_fn_name = format_ident!("create_{}_cap_grant", tag.to_case(Case::Snake));
+    let create_grant_stmt: syn::Stmt =
+        parse_quote! { crate::capabilities::#create_grant_fn_name()?; };
+
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+    map_rust_files(
+        file_tree
+            .path_mut(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        |file_path, mut file| {
+            if file_path == Path::new("capabilities.rs") {
+                for use_item in capability_imports(&zome_modules, &granted_functions)? {
+                    if !file.items.contains(&use_item) {
+                        file.items.insert(0, use_item);
+                    }
+                }
+                file.items.extend(capability_items.items.clone());
+            }
+            Ok(file)
+        },
+    )
+    .map_err(|e| match e {
+        ScaffoldError::MalformedFile(path, error) => {
+            ScaffoldError::MalformedFile(crate_src_path.join(path), error)
+        }
+        _ => e,
+    })?;
+
+    // 2. Unrestricted grants are created as soon as the cell is initialized
+    if access == CapabilityAccess::Unrestricted {
+        add_stmt_to_init(&mut file_tree, &crate_src_path, &create_grant_stmt)?;
+    }
+
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    let dna_role_name = dna_file_tree.dna_manifest.name();
+
+    let app_file_tree = AppFileTree::get_or_choose(dna_file_tree.file_tree(), None)?;
+    let app_name = app_file_tree.app_manifest.app_name().to_string();
+
+    scaffold_capability_templates(
+        app_file_tree.file_tree(),
+        template_file_tree,
+        &app_name,
+        &dna_role_name,
+        &zome_manifest,
+        &tag,
+        access,
+        &granted_functions,
+        no_spec,
+    )
+}
+
+/// A rust module of the coordinator zome
+struct ZomeModule {
+    uses: Vec<syn::Item>,
+    /// Names of the structs and enums defined in the module
+    types: Vec<String>,
+    extern_functions: Vec<ItemFn>,
+}
+
+/// Returns all the modules of the zome, indexed by their path
+fn zome_modules(zome_file_tree: &ZomeFileTree) -> ScaffoldResult<BTreeMap<String, ZomeModule>> {
+    let crate_src_path = zome_file_tree.zome_crate_path.join("src");
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+
+    let modules = find_map_rust_files(
+        zome_file_tree
+            .dna_file_tree
+            .file_tree_ref()
+            .path(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        &|_file_path, rust_file| {
+            let mut module = ZomeModule {
+                uses: vec![],
+                types: vec![],
+                extern_functions: vec![],
+            };
+            for item in &rust_file.items {
+                match item {
+                    syn::Item::Use(item_use) => {
+                        // Relative imports would point somewhere else from the capabilities module
+                        let relative = matches!(
+                            &item_use.tree,
+                            syn::UseTree::Path(p) if p.ident == "super" || p.ident == "self"
+                        );
+                        if !relative {
+                            module.uses.push(item.clone());
+                        }
+                    }
+                    syn::Item::Struct(item_struct) => {
+                        module.types.push(item_struct.ident.to_string())
+                    }
+                    syn::Item::Enum(item_enum) => module.types.push(item_enum.ident.to_string()),
+                    syn::Item::Fn(item_fn)
+                        if item_fn
+                            .attrs
+                            .iter()
+                            .any(|a| a.path().segments.iter().any(|s| s.ident == "hdk_extern")) =>
+                    {
+                        module.extern_functions.push(item_fn.clone())
+                    }
+                    _ => {}
+                }
+            }
+            Some(module)
+        },
+    );
+
+    Ok(modules
+        .into_iter()
+        .map(|(file_path, module)| (module_path(&file_path), module))
+        .collect())
+}
+
+/// Imports needed by the capabilities module to refer to the input and output types of the
+/// granted functions: the imports of the modules they are defined in, and those modules
+/// themselves if the types are defined there
+fn capability_imports(
+    zome_modules: &BTreeMap<String, ZomeModule>,
+    granted_functions: &[GrantedFunction],
+) -> ScaffoldResult<Vec<syn::Item>> {
+    let mut imports: Vec<syn::Item> = vec![];
+    for granted_function in granted_functions {
+        let Some(module) = zome_modules.get(&granted_function.module_path) else {
+            continue;
+        };
+        let type_names: Vec<&str> = granted_function
+            .signature
+            .input_type
+            .iter()
+            .chain(std::iter::once(&granted_function.signature.output_type))
+            .flat_map(|t| t.split(|c: char| !c.is_alphanumeric() && c != '_'))
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        if type_names
+            .iter()
+            .any(|t| module.types.iter().any(|m| m == t))
+        {
+            let module_path: syn::Path = syn::parse_str(&granted_function.module_path)?;
+            imports.push(parse_quote! { use #module_path::*; });
+        }
+        imports.extend(module.uses.iter().cloned());
+    }
+    imports.dedup();
+    Ok(imports)
+}
+
+fn choose_functions(extern_functions: &[(String, ItemFn)]) -> ScaffoldResult<Vec<String>> {
+    let function_names: Vec<String> = extern_functions
+        .iter()
+        .map(|(_, f)| f.sig.ident.to_string())
+        .filter(|name| name != "init" && name != "post_commit")
+        .collect();
+
+    let selection = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Which functions should be callable by other agents? (Use space to select, enter to submit)")
+        .items(&function_names[..])
+        .interact()?;
+
+    Ok(selection
+        .into_iter()
+        .map(|i| function_names[i].clone())
+        .collect())
+}
+
+fn capability_items(
+    tag: &str,
+    access: CapabilityAccess,
+    granted_functions: &[GrantedFunction],
+) -> TokenStream {
+    let snake_tag = tag.to_case(Case::Snake);
+    let pascal_tag = tag.to_case(Case::Pascal);
+    let granted_function_names: Vec<&str> = granted_functions
+        .iter()
+        .map(|f| f.signature.name.as_str())
+        .collect();
+
+    let capability_fn_name = format_ident!("{snake_tag}_capability");
+    let capability_fn = quote! {
+        fn #capability_fn_name() -> ExternResult<GrantedFunctions> {
+            let zome_name = zome_info()?.name;
+            let mut functions = HashSet::new();
+            #(functions.insert((zome_name.clone(), FunctionName::from(#granted_function_names)));)*
+            Ok(GrantedFunctions::Listed(functions))
+        }
+    };
+
+    let grant_fns = match access {
+        CapabilityAccess::Unrestricted => {
+            let create_grant_fn_name = format_ident!("create_{snake_tag}_cap_grant");
+            quote! {
+                /// Called from `init` so that anyone can call the granted functions
+                pub fn #create_grant_fn_name() -> ExternResult<ActionHash> {
+                    create_cap_grant(CapGrantEntry {
+                        tag: #tag.to_string(),
+                        access: CapAccess::Unrestricted,
+                        functions: #capability_fn_name()?,
+                    })
+                }
+            }
+        }
+        CapabilityAccess::Transferable | CapabilityAccess::Assigned => {
+            let grant_fn_name = format_ident!("grant_{snake_tag}_access");
+            let claim_fn_name = format_ident!("claim_{snake_tag}_access");
+            let claim_input = format_ident!("Claim{pascal_tag}AccessInput");
+            let get_secret_fn_name = format_ident!("get_{snake_tag}_cap_secret");
+
+            let (grant_params, cap_access) = if access == CapabilityAccess::Assigned {
+                (
+                    quote! { assignees: Vec<AgentPubKey> },
+                    quote! {
+                        CapAccess::Assigned {
+                            secret,
+                            assignees: assignees.into_iter().collect(),
+                        }
+                    },
+                )
+            } else {
+                (quote! {}, quote! { CapAccess::Transferable { secret } })
+            };
+
+            quote! {
+                /// Creates a new grant, whose secret must be sent to the agents that will call the granted functions
+                #[hdk_extern]
+                pub fn #grant_fn_name(#grant_params) -> ExternResult<CapSecret> {
+                    let secret = generate_cap_secret()?;
+                    create_cap_grant(CapGrantEntry {
+                        tag: #tag.to_string(),
+                        access: #cap_access,
+                        functions: #capability_fn_name()?,
+                    })?;
+                    Ok(secret)
+                }
+
+                #[derive(Serialize, Deserialize, Debug)]
+                pub struct #claim_input {
+                    pub grantor: AgentPubKey,
+                    pub secret: CapSecret,
+                }
+
+                /// Stores the secret received from the grantor, to be used in the remote calls to it
+                #[hdk_extern]
+                pub fn #claim_fn_name(input: #claim_input) -> ExternResult<ActionHash> {
+                    create_cap_claim(CapClaimEntry {
+                        tag: #tag.to_string(),
+                        grantor: input.grantor,
+                        secret: input.secret,
+                    })
+                }
+
+                fn #get_secret_fn_name(grantor: &AgentPubKey) -> ExternResult<Option<CapSecret>> {
+                    let records = query(
+                        ChainQueryFilter::new()
+                            .entry_type(EntryType::CapClaim)
+                            .include_entries(true),
+                    )?;
+                    Ok(records.into_iter().rev().find_map(|record| match record.entry().as_option() {
+                        Some(Entry::CapClaim(claim))
+                            if claim.tag == #tag && claim.grantor == *grantor =>
+                        {
+                            Some(claim.secret)
+                        }
+                        _ => None,
+                    }))
+                }
+            }
+        }
+    };
+
+    let remote_call_fns: Vec<TokenStream> = granted_functions
+        .iter()
+        .map(|f| remote_call_fn(&snake_tag, access, f))
+        .collect();
+
+    quote! {
+        #capability_fn
+
+        #grant_fns
+
+        #(#remote_call_fns)*
+    }
+}
+
+fn remote_call_fn(
+    snake_tag: &str,
+    access: CapabilityAccess,
+    granted_function: &GrantedFunction,
+) -> TokenStream {
+    let function_name = &granted_function.signature.name;
+    let remote_fn_name = format_ident!("remote_{function_name}");
+    let remote_input = format_ident!("Remote{}Input", function_name.to_case(Case::Pascal));
+    let output_type: syn::Type = syn::parse_str(&granted_function.signature.output_type)
+        .expect("output type was parsed before");
+    let failed_call_message = format!("Remote call to {function_name} failed: {{response:?}}");
+
+    let (input_struct, payload) = match &granted_function.signature.input_type {
+        Some(input_type) => {
+            let input_type: syn::Type =
+                syn::parse_str(input_type).expect("input type was parsed before");
+            (
+                quote! {
+                    #[derive(Serialize, Deserialize, Debug)]
+                    pub struct #remote_input {
+                        pub agent: AgentPubKey,
+                        pub input: #input_type,
+                    }
+                },
+                quote! { input.input },
+            )
+        }
+        None => (
+            quote! {
+                #[derive(Serialize, Deserialize, Debug)]
+                pub struct #remote_input {
+                    pub agent: AgentPubKey,
+                }
+            },
+            quote! { () },
+        ),
+    };
+
+    let cap_secret = match access {
+        CapabilityAccess::Unrestricted => quote! { None },
+        CapabilityAccess::Transferable | CapabilityAccess::Assigned => {
+            let get_secret_fn_name = format_ident!("get_{snake_tag}_cap_secret");
+            quote! { #get_secret_fn_name(&input.agent)? }
+        }
+    };
+
+    quote! {
+        #input_struct
+
+        #[hdk_extern]
+        pub fn #remote_fn_name(input: #remote_input) -> ExternResult<#output_type> {
+            let response = call_remote(
+                input.agent.clone(),
+                zome_info()?.name,
+                FunctionName::from(#function_name),
+                #cap_secret,
+                #payload,
+            )?;
+            match response {
+                ZomeCallResponse::Ok(result) => result
+                    .decode()
+                    .map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string()))),
+                _ => Err(wasm_error!(WasmErrorInner::Guest(format!(#failed_call_message)))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::unparse_pretty;
+    use std::path::PathBuf;
+
+    fn get_post() -> GrantedFunction {
+        GrantedFunction {
+            module_path: "crate::post".to_string(),
+            signature: ExternFunctionSignature::from_item_fn(&parse_quote! {
+                #[hdk_extern]
+                pub fn get_post(post_hash: ActionHash) -> ExternResult<Option<Record>> {
+                    get(post_hash, GetOptions::default())
+                }
+            })
+            .unwrap(),
+            sweettest_input: Some(SweettestInput::Placeholder),
+        }
+    }
+
+    #[test]
+    fn unrestricted_grant_is_created_without_secret() {
+        let items = capability_items(
+            "unrestricted",
+            CapabilityAccess::Unrestricted,
+            &[get_post()],
+        );
+        let generated = unparse_pretty(&syn::parse2(items).unwrap());
+
+        assert!(generated
+            .contains("pub fn create_unrestricted_cap_grant() -> ExternResult<ActionHash>"));
+        assert!(generated.contains("access: CapAccess::Unrestricted,"));
+        assert!(generated.contains("Ok(GrantedFunctions::Listed(functions))"));
+        assert!(generated.contains(r#"FunctionName::from("get_post")"#));
+        assert!(generated.contains("pub input: ActionHash,"));
+        assert!(generated.contains(
+            "pub fn remote_get_post(input: RemoteGetPostInput) -> ExternResult<Option<Record>>"
+        ));
+        assert!(!generated.contains("claim_unrestricted_access"));
+    }
+
+    #[test]
+    fn assigned_grant_is_claimed_and_its_secret_used_in_remote_calls() {
+        let items = capability_items("readers", CapabilityAccess::Assigned, &[get_post()]);
+        let generated = unparse_pretty(&syn::parse2(items).unwrap());
+
+        assert!(generated.contains(
+            "pub fn grant_readers_access(assignees: Vec<AgentPubKey>) -> ExternResult<CapSecret>"
+        ));
+        assert!(generated.contains("pub fn claim_readers_access(input: ClaimReadersAccessInput)"));
+        assert!(generated.contains("get_readers_cap_secret(&input.agent)?,"));
+    }
+
+    #[test]
+    fn functions_must_return_an_extern_result() {
+        let result = ExternFunctionSignature::from_item_fn(&parse_quote! {
+            #[hdk_extern(infallible)]
+            pub fn post_commit(committed_actions: Vec<SignedActionHashed>) {}
+        });
+        assert!(result.is_err());
+        assert_eq!(module_path(&PathBuf::from("lib.rs")), "crate");
+        assert_eq!(module_path(&PathBuf::from("posts/mod.rs")), "crate::posts");
+    }
+}
@@ -5,14 +5,35 @@ use serde_json::Value;
 
 use crate::{
     error::ScaffoldResult,
-    file_tree::{map_file, FileTree},
+    file_tree::{file_content, map_file, FileTree},
 };
 
-use super::web_app::template_type::TemplateType;
+use super::web_app::{
+    template_manifest::TemplateVars, template_source::TemplateCommits, template_type::TemplateType,
+};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct ScaffoldConfig {
-    pub template: TemplateType,
+    /// Not written for templates in the nix store, as their path changes with every version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<TemplateType>,
+    /// The commit a git template was resolved to, so that later commands use the same template
+    #[serde(
+        default,
+        rename = "templateCommit",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub template_commit: Option<String>,
+    /// The commits the git templates extended by a git template were resolved to
+    #[serde(
+        default,
+        rename = "templateBaseCommits",
+        skip_serializing_if = "TemplateCommits::is_empty"
+    )]
+    pub template_base_commits: TemplateCommits,
+    /// Values of the variables declared by the template
+    #[serde(default, skip_serializing_if = "TemplateVars::is_empty")]
+    pub vars: TemplateVars,
 }
 
 impl ScaffoldConfig {
@@ -23,7 +44,19 @@ impl ScaffoldConfig {
         let Ok(file) = fs::read_to_string(package_json_path) else {
             return Ok(None);
         };
-        let file = serde_json::from_str::<Value>(&file)?;
+        Self::from_package_json(&file)
+    }
+
+    /// Gets the template config in the root `package.json` file of the given app file tree
+    pub fn from_app_file_tree(app_file_tree: &FileTree) -> ScaffoldResult<Option<Self>> {
+        let Ok(file) = file_content(app_file_tree, &PathBuf::from("package.json")) else {
+            return Ok(None);
+        };
+        Self::from_package_json(&file)
+    }
+
+    fn from_package_json(content: &str) -> ScaffoldResult<Option<Self>> {
+        let file = serde_json::from_str::<Value>(content)?;
         if let Some(config) = file.get("hcScaffold") {
             let config = serde_json::from_value(config.to_owned())?;
             Ok(Some(config))
@@ -32,33 +65,31 @@ impl ScaffoldConfig {
         }
     }
 
-    pub fn write_to_package_json(
-        web_app_file_tree: &mut FileTree,
-        template_type: &TemplateType,
-    ) -> ScaffoldResult<()> {
-        let config = ScaffoldConfig {
-            template: template_type.clone(),
-        };
-        let package_json_path = PathBuf::from("package.json");
-
-        map_file(web_app_file_tree, &package_json_path, |c| {
-            let original_content = c.clone();
-            let json = serde_json::from_str::<Value>(&c)?;
-            let json = match json {
-                Value::Object(mut o) => {
-                    o.insert(
-                        "hcScaffold".to_owned(),
-                        serde_json::to_value(&config).unwrap(),
-                    );
-                    o
-                }
-                _ => return Ok(original_content),
-            };
-            let json = serde_json::to_value(json)?;
-            let json = serde_json::to_string_pretty(&json)?;
-            Ok(json)
-        })?;
+    pub fn write_to_package_json(&self, web_app_file_tree: &mut FileTree) -> ScaffoldResult<()> {
+        map_file(web_app_file_tree, &PathBuf::from("package.json"), |c| {
+            self.insert_into_package_json(c)
+        })
+    }
 
+    /// Overwrites the config in the `package.json` file found in the given path
+    pub fn write_to_package_json_path<P: Into<PathBuf>>(&self, path: P) -> ScaffoldResult<()> {
+        let package_json_path = path.into().join("package.json");
+        let content = fs::read_to_string(&package_json_path)?;
+        fs::write(package_json_path, self.insert_into_package_json(content)?)?;
         Ok(())
     }
+
+    fn insert_into_package_json(&self, content: String) -> ScaffoldResult<String> {
+        let json = serde_json::from_str::<Value>(&content)?;
+        let json = match json {
+            Value::Object(mut o) => {
+                o.insert("hcScaffold".to_owned(), serde_json::to_value(self)?);
+                o
+            }
+            _ => return Ok(content),
+        };
+        let json = serde_json::to_value(json)?;
+        let json = serde_json::to_string_pretty(&json)?;
+        Ok(json)
+    }
 }
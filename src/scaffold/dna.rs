@@ -21,6 +21,7 @@ use mr_bundle::Manifest;
 pub mod coordinator;
 pub mod integrity;
 pub mod manifest;
+pub mod properties;
 
 use crate::error::{ScaffoldError, ScaffoldResult};
 
@@ -167,6 +168,8 @@ pub fn scaffold_dna(
     app_file_tree: AppFileTree,
     template_file_tree: &FileTree,
     dna_name: &str,
+    clone_limit: u32,
+    deferred: bool,
 ) -> ScaffoldResult<ScaffoldedTemplate> {
     check_for_reserved_keywords(dna_name)?;
 
@@ -216,9 +219,9 @@ pub fn scaffold_dna(
                 properties: None,
             },
             installed_hash: None,
-            clone_limit: 0,
+            clone_limit,
         },
-        provisioning: Some(CellProvisioning::Create { deferred: false }),
+        provisioning: Some(CellProvisioning::Create { deferred }),
     });
 
     let new_manifest: AppManifest = AppManifestCurrentBuilder::default()
@@ -252,5 +255,7 @@ pub fn scaffold_dna(
         template_file_tree,
         &app_name.to_string(),
         dna_name,
+        clone_limit,
+        deferred,
     )
 }
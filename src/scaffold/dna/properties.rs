@@ -0,0 +1,462 @@
+use std::{collections::BTreeMap, ffi::OsString, path::PathBuf};
+
+use colored::Colorize;
+use dialoguer::{theme::ColorfulTheme, Select};
+use holochain_types::prelude::{AppManifest, DnaManifest, YamlProperties};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use serde::Serialize;
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::{file_exists, find_map_rust_files, insert_file, map_file, FileTree},
+    scaffold::{
+        app::{find_app_manifests, AppFileTree},
+        entry_type::definitions::{Cardinality, FieldDefinition, FieldType},
+        zome::{utils::get_coordinator_zomes_for_integrity, ZomeFileTree},
+    },
+    templates::{dna_properties::scaffold_dna_properties_templates, ScaffoldedTemplate},
+    utils::unparse_pretty,
+};
+
+use super::DnaFileTree;
+
+/// Scaffolds a `DnaProperties` struct with the given fields in the integrity zome, writes their
+/// default values to the DNA manifest and to the app roles using the DNA, and adds a sweettest
+/// installing the DNA with custom properties
+pub fn scaffold_dna_properties(
+    integrity_zome_file_tree: ZomeFileTree,
+    template_file_tree: &FileTree,
+    fields: &[FieldDefinition],
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    let dna_manifest_path = integrity_zome_file_tree
+        .dna_file_tree
+        .dna_manifest_path
+        .clone();
+    let dna_name = integrity_zome_file_tree.dna_file_tree.dna_manifest.name();
+    let integrity_zome_manifest = integrity_zome_file_tree.zome_manifest.clone();
+    let integrity_zome_name = integrity_zome_manifest.name.0.to_string();
+    let crate_src_path = integrity_zome_file_tree.zome_crate_path.join("src");
+    let properties_file_path = crate_src_path.join("properties.rs");
+
+    if file_exists(
+        integrity_zome_file_tree.dna_file_tree.file_tree_ref(),
+        &properties_file_path,
+    ) {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "DNA properties were already scaffolded in zome {integrity_zome_name}"
+        )));
+    }
+    if let Some(f) = fields.iter().find(|f| f.linked_from.is_some()) {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "field {} of the DNA properties can't be linked from another entry type or agent",
+            f.field_name
+        )));
+    }
+
+    let coordinator_zomes_for_integrity = get_coordinator_zomes_for_integrity(
+        &integrity_zome_file_tree.dna_file_tree.dna_manifest,
+        &integrity_zome_name,
+    );
+    let coordinator_zome = match coordinator_zomes_for_integrity.len() {
+        0 => None,
+        1 => Some(coordinator_zomes_for_integrity[0].clone()),
+        _ => {
+            let names: Vec<String> = coordinator_zomes_for_integrity
+                .iter()
+                .map(|z| z.name.0.to_string())
+                .collect();
+            let selection = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt(
+                    "Which coordinator zome should the DNA properties getter function be scaffolded in?",
+                )
+                .default(0)
+                .items(&names[..])
+                .interact()?;
+
+            Some(coordinator_zomes_for_integrity[selection].clone())
+        }
+    };
+
+    // 1. Add the properties struct and its getter to the integrity zome
+    let mut file_tree = integrity_zome_file_tree.dna_file_tree.file_tree();
+
+    let properties_module: syn::File = syn::parse2(dna_properties_module(fields))?;
+    insert_file(
+        &mut file_tree,
+        &properties_file_path,
+        &unparse_pretty(&properties_module),
+    )?;
+    map_file(&mut file_tree, &crate_src_path.join("lib.rs"), |contents| {
+        Ok(format!(
+            r#"pub mod properties;
+pub use properties::*;
+{contents}"#
+        ))
+    })?;
+
+    // 2. Expose the properties in the coordinator zome, so that they can be read by the UI and the tests
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    let mut file_tree = match &coordinator_zome {
+        Some(coordinator_zome) => {
+            let coordinator_zome_file_tree =
+                ZomeFileTree::from_zome_manifest(dna_file_tree, coordinator_zome.clone())?;
+            let lib_rs_path = coordinator_zome_file_tree
+                .zome_crate_path
+                .join("src")
+                .join("lib.rs");
+            let mut file_tree = coordinator_zome_file_tree.dna_file_tree.file_tree();
+
+            let integrity_crate = format_ident!("{integrity_zome_name}");
+            let getter: syn::File = syn::parse_quote! {
+                #[hdk_extern]
+                pub fn get_dna_properties() -> ExternResult<#integrity_crate::DnaProperties> {
+                    #integrity_crate::get_dna_properties()
+                }
+            };
+            map_file(&mut file_tree, &lib_rs_path, |contents| {
+                Ok(format!("{contents}\n{}", unparse_pretty(&getter)))
+            })?;
+            file_tree
+        }
+        None => {
+            println!(
+                "{}",
+                format!(
+                    "WARNING: no coordinator zome depends on {integrity_zome_name}, skipping the DNA properties getter function and its tests"
+                )
+                .yellow()
+            );
+            dna_file_tree.file_tree()
+        }
+    };
+
+    // 3. Write the default values of the properties to the DNA manifest and the app manifests
+    let default_properties: YamlProperties = serde_yaml::from_value(serde_yaml::Value::Mapping(
+        fields
+            .iter()
+            .map(|f| (f.field_name.clone().into(), default_value(f)))
+            .collect(),
+    ))?;
+
+    let dna_manifest =
+        match DnaFileTree::from_dna_manifest_path(file_tree.clone(), &dna_manifest_path)?
+            .dna_manifest
+        {
+            DnaManifest::V0(mut m) => {
+                m.integrity.properties = Some(default_properties.clone());
+                DnaManifest::V0(m)
+            }
+        };
+    insert_file(
+        &mut file_tree,
+        &dna_manifest_path,
+        &serde_yaml::to_string(&dna_manifest)?,
+    )?;
+
+    let dna_bundle_file_name = format!("{dna_name}.dna");
+    for (app_manifest_path, app_manifest) in find_app_manifests(&file_tree)? {
+        let AppManifest::V0(mut m) = app_manifest;
+        let mut uses_dna = false;
+        for role in &mut m.roles {
+            let is_dna_role = role.dna.path.as_ref().is_some_and(|path| {
+                PathBuf::from(path)
+                    .file_name()
+                    .is_some_and(|f| f.to_string_lossy() == dna_bundle_file_name)
+            });
+            if is_dna_role {
+                role.dna.modifiers.properties = Some(default_properties.clone());
+                uses_dna = true;
+            }
+        }
+        if uses_dna {
+            insert_file(
+                &mut file_tree,
+                &app_manifest_path,
+                &serde_yaml::to_string(&AppManifest::V0(m))?,
+            )?;
+        }
+    }
+
+    if fields.iter().any(|f| {
+        matches!(f.cardinality, Cardinality::Single) && default_value(f) == serde_yaml::Value::Null
+    }) {
+        println!(
+            "{}",
+            format!(
+                "WARNING: the hash fields of the DNA properties have no default value, set them in {} before installing the DNA",
+                dna_manifest_path.to_string_lossy()
+            )
+            .yellow()
+        );
+    }
+
+    let app_file_tree = AppFileTree::get_or_choose(file_tree, None)?;
+    let app_name = app_file_tree.app_manifest.app_name().to_string();
+
+    let Some(coordinator_zome) = coordinator_zome else {
+        return Ok(ScaffoldedTemplate {
+            file_tree: app_file_tree.file_tree(),
+            next_instructions: None,
+        });
+    };
+
+    scaffold_dna_properties_templates(
+        app_file_tree.file_tree(),
+        template_file_tree,
+        &app_name,
+        &dna_name,
+        &integrity_zome_manifest,
+        &coordinator_zome,
+        fields,
+        no_spec,
+    )
+}
+
+/// A DNA property, with the value the sweettests installing the DNA give it
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DnaPropertyValue {
+    pub field_name: String,
+    /// Rust expression for the value, the same default that is written to the manifests
+    pub value: String,
+}
+
+/// Makes sure the integrity zome has the given DNA property, scaffolding the DNA properties if
+/// there are none yet, and returns the other DNA properties
+///
+/// `feature` names what needs the property, for the error shown when the existing properties
+/// don't have it
+pub fn ensure_dna_property(
+    integrity_zome_file_tree: ZomeFileTree,
+    template_file_tree: &FileTree,
+    field: &FieldDefinition,
+    feature: &str,
+    no_spec: bool,
+) -> ScaffoldResult<(ZomeFileTree, Vec<DnaPropertyValue>)> {
+    let zome_name = integrity_zome_file_tree.zome_manifest.name.0.to_string();
+    let crate_src_path = integrity_zome_file_tree.zome_crate_path.join("src");
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+
+    let dna_properties_fields = find_map_rust_files(
+        integrity_zome_file_tree
+            .dna_file_tree
+            .file_tree_ref()
+            .path(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        &|_, file| {
+            // First variant of each enum, the default of the enum properties
+            let enum_defaults: BTreeMap<String, String> = file
+                .items
+                .iter()
+                .filter_map(|item| match item {
+                    syn::Item::Enum(item_enum) => item_enum
+                        .variants
+                        .first()
+                        .map(|v| (item_enum.ident.to_string(), v.ident.to_string())),
+                    _ => None,
+                })
+                .collect();
+            file.items.iter().find_map(|item| match item {
+                syn::Item::Struct(item_struct) if item_struct.ident == "DnaProperties" => Some(
+                    item_struct
+                        .fields
+                        .iter()
+                        .filter_map(|f| {
+                            f.ident.as_ref().map(|i| DnaPropertyValue {
+                                field_name: i.to_string(),
+                                value: default_rust_value(&f.ty, &enum_defaults),
+                            })
+                        })
+                        .collect::<Vec<DnaPropertyValue>>(),
+                ),
+                _ => None,
+            })
+        },
+    )
+    .into_values()
+    .next();
+
+    match dna_properties_fields {
+        None => {
+            let dna_manifest_path = integrity_zome_file_tree
+                .dna_file_tree
+                .dna_manifest_path
+                .clone();
+            let zome_manifest = integrity_zome_file_tree.zome_manifest.clone();
+            let file_tree = scaffold_dna_properties(
+                integrity_zome_file_tree,
+                template_file_tree,
+                std::slice::from_ref(field),
+                no_spec,
+            )?
+            .file_tree;
+
+            let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+            Ok((
+                ZomeFileTree::from_zome_manifest(dna_file_tree, zome_manifest)?,
+                vec![],
+            ))
+        }
+        Some(fields) if fields.iter().any(|f| f.field_name == field.field_name) => {
+            let other_dna_properties = fields
+                .into_iter()
+                .filter(|f| f.field_name != field.field_name)
+                .collect();
+            Ok((integrity_zome_file_tree, other_dna_properties))
+        }
+        Some(_) => Err(ScaffoldError::InvalidArguments(format!(
+            "{feature} needs a \"{}: {}\" field in the DnaProperties of zome {zome_name}, add it first",
+            field.field_name,
+            field.rust_type().to_string().replace(' ', "")
+        ))),
+    }
+}
+
+fn dna_properties_module(fields: &[FieldDefinition]) -> TokenStream {
+    let type_definitions: Vec<TokenStream> = fields
+        .iter()
+        .filter_map(|f| f.field_type.rust_type_definition())
+        .collect();
+    let fields: Vec<TokenStream> = fields
+        .iter()
+        .map(|f| {
+            let field_name = format_ident!("{}", f.field_name);
+            let rust_type = f.rust_type();
+            quote! { pub #field_name: #rust_type }
+        })
+        .collect();
+
+    quote! {
+        use hdi::prelude::*;
+
+        #(#type_definitions)*
+
+        /// Properties of the DNA, set in its manifest or when installing it
+        #[derive(Serialize, Deserialize, SerializedBytes, Debug, Clone, PartialEq)]
+        pub struct DnaProperties {
+            #(#fields),*
+        }
+
+        pub fn get_dna_properties() -> ExternResult<DnaProperties> {
+            DnaProperties::try_from(dna_info()?.modifiers.properties)
+                .map_err(|e| wasm_error!(WasmErrorInner::Guest(e.into())))
+        }
+    }
+}
+
+/// Value written to the manifests for the given field, hashes have no sensible default
+fn default_value(field: &FieldDefinition) -> serde_yaml::Value {
+    use serde_yaml::Value;
+
+    match field.cardinality {
+        Cardinality::Vector => Value::Sequence(vec![]),
+        Cardinality::Option => Value::Null,
+        Cardinality::Single => match &field.field_type {
+            FieldType::Bool => Value::Bool(false),
+            FieldType::String => Value::String(String::new()),
+            FieldType::U8 | FieldType::U32 | FieldType::I32 | FieldType::Timestamp => 0.into(),
+            FieldType::F32 => 0.0.into(),
+            FieldType::Enum { variants, .. } => Value::Mapping(
+                [(
+                    "type".into(),
+                    variants.first().cloned().unwrap_or_default().into(),
+                )]
+                .into_iter()
+                .collect(),
+            ),
+            FieldType::AgentPubKey
+            | FieldType::ActionHash
+            | FieldType::EntryHash
+            | FieldType::DnaHash
+            | FieldType::ExternalHash
+            | FieldType::File => Value::Null,
+        },
+    }
+}
+
+/// Rust expression of the value written to the manifests for a field of the given type, hashes
+/// have no default there so they get a placeholder hash
+fn default_rust_value(ty: &syn::Type, enum_defaults: &BTreeMap<String, String>) -> String {
+    let syn::Type::Path(type_path) = ty else {
+        return "Default::default()".to_string();
+    };
+    let Some(segment) = type_path.path.segments.last() else {
+        return "Default::default()".to_string();
+    };
+    let type_name = segment.ident.to_string();
+    match type_name.as_str() {
+        "Vec" => "vec![]".to_string(),
+        "Option" => "None".to_string(),
+        "bool" => "false".to_string(),
+        "String" => "String::new()".to_string(),
+        "u8" | "u32" | "i32" => "0".to_string(),
+        "f32" => "0.0".to_string(),
+        "Timestamp" => "Timestamp::from_micros(0)".to_string(),
+        "AgentPubKey" | "ActionHash" | "EntryHash" | "DnaHash" | "ExternalHash" => {
+            format!("{type_name}::from_raw_36(vec![0; 36])")
+        }
+        _ => match enum_defaults.get(&type_name) {
+            Some(variant) => format!("{type_name}::{variant}"),
+            None => "Default::default()".to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn properties_struct_and_getter() {
+        let fields = vec![
+            FieldDefinition::from_str("max_posts:u32").unwrap(),
+            FieldDefinition::from_str("moderators:Vec<AgentPubKey>").unwrap(),
+        ];
+        let generated = unparse_pretty(&syn::parse2(dna_properties_module(&fields)).unwrap());
+
+        assert!(generated.contains("pub struct DnaProperties {"));
+        assert!(generated.contains("pub max_posts: u32,"));
+        assert!(generated.contains("pub moderators: Vec<AgentPubKey>,"));
+        assert!(generated.contains("DnaProperties::try_from(dna_info()?.modifiers.properties)"));
+    }
+
+    #[test]
+    fn rust_default_values_match_the_manifest_defaults() {
+        let enum_defaults = BTreeMap::from([("Mode".to_string(), "Open".to_string())]);
+        let default_of =
+            |ty: &str| default_rust_value(&syn::parse_str(ty).unwrap(), &enum_defaults);
+
+        assert_eq!(default_of("String"), "String::new()");
+        assert_eq!(default_of("u32"), "0");
+        assert_eq!(default_of("Vec<AgentPubKey>"), "vec![]");
+        assert_eq!(default_of("Option<ActionHash>"), "None");
+        assert_eq!(default_of("Mode"), "Mode::Open");
+        assert_eq!(
+            default_of("AgentPubKey"),
+            "AgentPubKey::from_raw_36(vec![0; 36])"
+        );
+    }
+
+    #[test]
+    fn default_values_by_field_type() {
+        let default_of = |f: &str| default_value(&FieldDefinition::from_str(f).unwrap());
+
+        assert_eq!(default_of("name:String"), serde_yaml::Value::from(""));
+        assert_eq!(default_of("max_posts:u32"), serde_yaml::Value::from(0));
+        assert_eq!(
+            default_of("progenitor:AgentPubKey"),
+            serde_yaml::Value::Null
+        );
+        assert_eq!(
+            default_of("tags:Vec<String>"),
+            serde_yaml::Value::Sequence(vec![])
+        );
+        assert_eq!(
+            serde_yaml::to_string(&default_of("mode:Enum:Mode:open.closed")).unwrap(),
+            "type: Open\n"
+        );
+    }
+}
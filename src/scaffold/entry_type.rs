@@ -1,4 +1,4 @@
-use std::{ffi::OsString, path::PathBuf};
+use std::path::PathBuf;
 
 use crate::{
     file_tree::FileTree,
@@ -6,28 +6,48 @@ use crate::{
     templates::{entry_type::scaffold_entry_type_templates, ScaffoldedTemplate},
 };
 
-use build_fs_tree::dir;
 use colored::Colorize;
 use convert_case::{Case, Casing};
 use dialoguer::{theme::ColorfulTheme, Select};
+use holochain_types::prelude::DnaManifest;
 
 use crate::error::{ScaffoldError, ScaffoldResult};
 
 use self::{
     coordinator::{add_crud_functions_to_coordinator, updates_link_name},
+    countersigning::{
+        add_countersigning_functions_to_coordinator,
+        add_countersigning_validation_to_integrity_zome, check_countersigned_entry_type,
+    },
+    create_policy::{
+        add_is_progenitor_to_coordinator, add_progenitor_validation_to_integrity_zome,
+        ensure_progenitor_dna_property, CreatePolicy,
+    },
     crud::Crud,
-    definitions::{EntryDefinition, EntryTypeReference, FieldDefinition, Referenceable},
-    fields::choose_fields,
+    definitions::{EntryDefinition, EntryTypeReference, FieldDefinition, FieldType, Referenceable},
+    fields::{choosable_field_types_templates, choose_fields},
     integrity::{add_entry_type_to_integrity_zome, get_all_entry_types},
 };
 
 use super::{
     app::AppFileTree,
-    link_type::{integrity::add_link_type_to_integrity_zome, link_type_name},
-    zome::{utils::get_coordinator_zomes_for_integrity, ZomeFileTree},
+    link_type::{
+        integrity::add_link_type_to_integrity_zome,
+        link_type_name,
+        on_delete::{
+            add_on_delete_to_coordinator, add_restrict_validation_to_integrity_zome,
+            deletable_base, DependentLinks, FieldOnDelete, OnDelete,
+        },
+    },
+    zome::{
+        coordinator::find_extern_function_in_zomes, utils::get_coordinator_zomes_for_integrity,
+        ZomeFileTree,
+    },
 };
 
 pub mod coordinator;
+pub mod countersigning;
+pub mod create_policy;
 pub mod crud;
 pub mod definitions;
 pub mod fields;
@@ -44,6 +64,10 @@ pub fn scaffold_entry_type(
     maybe_reference_entry_hash: Option<bool>,
     maybe_link_from_original_to_each_update: Option<bool>,
     maybe_fields: Option<&Vec<FieldDefinition>>,
+    on_delete: &[FieldOnDelete],
+    delete_dependent_entries: bool,
+    create_policy: CreatePolicy,
+    countersigned: Option<u8>,
     no_ui: bool,
     no_spec: bool,
 ) -> ScaffoldResult<ScaffoldedTemplate> {
@@ -63,18 +87,12 @@ inadvertently reference or expect elements from the skipped entry type."#
             check_field_definitions(name, &zome_file_tree, f)?;
             f.clone()
         }
-        None => {
-            let v: Vec<OsString> = PathBuf::from("field-types")
-                .iter()
-                .map(|s| s.to_os_string())
-                .collect();
-            choose_fields(
-                name,
-                &zome_file_tree,
-                template_file_tree.path(&mut v.iter()).unwrap_or(&dir! {}),
-                no_ui,
-            )?
-        }
+        None => choose_fields(
+            name,
+            &zome_file_tree,
+            &choosable_field_types_templates(template_file_tree)?,
+            no_ui,
+        )?,
     };
 
     let reference_entry_hash = maybe_reference_entry_hash.unwrap_or(false);
@@ -110,10 +128,49 @@ inadvertently reference or expect elements from the skipped entry type."#
     };
     let entry_def_ts_types = entry_def.ts_type_codegen();
 
+    let dependent_links = dependent_links_on_delete(&entry_def, &crud, on_delete)?;
+
+    let file_storage_zome = file_storage_zome(&zome_file_tree, &entry_def)?;
+
+    if let Some(countersigning_agents) = countersigned {
+        if let CreatePolicy::Progenitor { .. } = create_policy {
+            return Err(ScaffoldError::InvalidArguments(String::from(
+                "countersigned entries are created by every agent of their session, so they can't have the progenitor create policy",
+            )));
+        }
+        check_countersigned_entry_type(&entry_def, &crud, countersigning_agents)?;
+    }
+
     let integrity_zome_name = zome_file_tree.zome_manifest.name.0.to_string();
 
+    let (zome_file_tree, create_policy) = match create_policy {
+        CreatePolicy::Anyone => (zome_file_tree, CreatePolicy::Anyone),
+        CreatePolicy::Progenitor { .. } => {
+            let (zome_file_tree, other_dna_properties) =
+                ensure_progenitor_dna_property(zome_file_tree, template_file_tree, no_spec)?;
+            (
+                zome_file_tree,
+                CreatePolicy::Progenitor {
+                    other_dna_properties,
+                },
+            )
+        }
+    };
+
     let mut zome_file_tree = add_entry_type_to_integrity_zome(zome_file_tree, &entry_def, &crud)?;
 
+    if let CreatePolicy::Progenitor { .. } = create_policy {
+        zome_file_tree = add_progenitor_validation_to_integrity_zome(zome_file_tree, &entry_def)?;
+    }
+
+    if let Some(countersigning_agents) = countersigned {
+        zome_file_tree = add_countersigning_validation_to_integrity_zome(
+            zome_file_tree,
+            &entry_def,
+            countersigning_agents,
+        )?;
+    }
+
     let linked_from: Vec<Referenceable> = entry_def
         .fields
         .iter()
@@ -131,6 +188,16 @@ inadvertently reference or expect elements from the skipped entry type."#
         )?;
     }
 
+    for (base_entry_type, link_type, on_delete) in &dependent_links {
+        if *on_delete == OnDelete::Restrict {
+            zome_file_tree = add_restrict_validation_to_integrity_zome(
+                zome_file_tree,
+                base_entry_type,
+                link_type,
+            )?;
+        }
+    }
+
     let coordinator_zomes_for_integrity = get_coordinator_zomes_for_integrity(
         &zome_file_tree.dna_file_tree.dna_manifest,
         zome_file_tree.zome_manifest.name.0.as_ref(),
@@ -182,11 +249,47 @@ inadvertently reference or expect elements from the skipped entry type."#
         link_from_original_to_each_update,
     )?;
 
+    if let CreatePolicy::Progenitor { .. } = create_policy {
+        zome_file_tree = add_is_progenitor_to_coordinator(zome_file_tree, &integrity_zome_name)?;
+    }
+
+    if let Some(countersigning_agents) = countersigned {
+        zome_file_tree = add_countersigning_functions_to_coordinator(
+            zome_file_tree,
+            &entry_def,
+            countersigning_agents,
+        )?;
+    }
+
+    let entry_referenceable = entry_def.referenceable();
+    for (base_entry_type, link_type, on_delete) in &dependent_links {
+        let dna_file_tree = add_on_delete_to_coordinator(
+            zome_file_tree.dna_file_tree,
+            &coordinator_zomes_for_integrity,
+            base_entry_type,
+            &DependentLinks {
+                link_type_name: link_type,
+                target_referenceable: &entry_referenceable,
+                inverse_link_type_name: None,
+                on_delete: *on_delete,
+                delete_dependent_entries,
+            },
+        )?;
+        zome_file_tree = ZomeFileTree::from_zome_manifest(dna_file_tree, coordinator_zome.clone())?;
+    }
+
     let dna_manifest = zome_file_tree.dna_file_tree.dna_manifest.clone();
 
     let app_file_tree = AppFileTree::get_or_choose(zome_file_tree.dna_file_tree.file_tree(), None)?;
 
     let app_name = app_file_tree.app_manifest.app_name().to_string();
+    let clone_limit = app_file_tree
+        .app_manifest
+        .app_roles()
+        .into_iter()
+        .find(|r| r.name.eq(&dna_manifest.name()))
+        .map(|r| r.dna.clone_limit)
+        .unwrap_or(0);
 
     scaffold_entry_type_templates(
         app_file_tree.file_tree(),
@@ -199,11 +302,89 @@ inadvertently reference or expect elements from the skipped entry type."#
         &entry_def_ts_types,
         &crud,
         link_from_original_to_each_update,
+        &create_policy,
+        countersigned,
+        clone_limit,
+        file_storage_zome.as_deref(),
         no_ui,
         no_spec,
     )
 }
 
+/// The coordinator zome of the DNA storing the files of the `File` fields of the entry type,
+/// which has to be scaffolded with the file-storage command first
+fn file_storage_zome(
+    zome_file_tree: &ZomeFileTree,
+    entry_def: &EntryDefinition,
+) -> ScaffoldResult<Option<String>> {
+    let Some(file_field) = entry_def
+        .fields
+        .iter()
+        .find(|f| f.field_type == FieldType::File)
+    else {
+        return Ok(None);
+    };
+
+    let coordinator_zomes = match &zome_file_tree.dna_file_tree.dna_manifest {
+        DnaManifest::V0(m) => m.coordinator.zomes.clone(),
+    };
+    match find_extern_function_in_zomes(
+        &zome_file_tree.dna_file_tree,
+        &coordinator_zomes,
+        "upload_file",
+    )? {
+        Some((zome, _)) => Ok(Some(zome.name.0.to_string())),
+        None => Err(ScaffoldError::InvalidArguments(format!(
+            "field {} is a File, but no coordinator zome of the {} DNA stores files: scaffold one with the file-storage command first",
+            file_field.field_name,
+            zome_file_tree.dna_file_tree.dna_manifest.name()
+        ))),
+    }
+}
+
+/// Resolves the on-delete policies of the `linked_from` fields into the base entry types and
+/// link types they apply to, skipping the orphaned ones
+fn dependent_links_on_delete(
+    entry_def: &EntryDefinition,
+    crud: &Crud,
+    on_delete: &[FieldOnDelete],
+) -> ScaffoldResult<Vec<(EntryTypeReference, String, OnDelete)>> {
+    let mut dependent_links = Vec::new();
+
+    for field_on_delete in on_delete {
+        let Some(linked_from) = entry_def
+            .fields
+            .iter()
+            .find(|f| f.field_name == field_on_delete.field_name)
+            .and_then(|f| f.linked_from.clone())
+        else {
+            return Err(ScaffoldError::InvalidArguments(format!(
+                "on-delete policies can only be set for linked_from fields, and {} is not one",
+                field_on_delete.field_name
+            )));
+        };
+
+        if field_on_delete.on_delete == OnDelete::Cascade && !crud.delete {
+            return Err(ScaffoldError::InvalidArguments(format!(
+                "the cascade on-delete policy for {} deletes the links to {}, so {} must be deletable",
+                field_on_delete.field_name,
+                entry_def.pascal_case_name(),
+                entry_def.pascal_case_name()
+            )));
+        }
+
+        if let Some(base_entry_type) = deletable_base(&linked_from, field_on_delete.on_delete)? {
+            dependent_links.push((
+                base_entry_type,
+                link_type_name(&linked_from, &entry_def.referenceable()),
+                field_on_delete.on_delete,
+            ));
+        }
+    }
+
+    Ok(dependent_links)
+}
+
 fn check_field_definitions(
     entry_type_name: &str,
     zome_file_tree: &ZomeFileTree,
@@ -0,0 +1,450 @@
+use std::{ffi::OsString, path::Path};
+
+use convert_case::{Case, Casing};
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+use syn::parse_quote;
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::{map_file, map_rust_files},
+    scaffold::{dna::DnaFileTree, zome::ZomeFileTree},
+};
+
+use super::{crud::Crud, definitions::EntryDefinition};
+
+/// Feature of the hdk and holochain crates that exposes the countersigning functions
+const COUNTERSIGNING_FEATURE: &str = "unstable-countersigning";
+
+/// Checks that an entry type can be created in countersigning sessions between the given
+/// number of agents
+pub fn check_countersigned_entry_type(
+    entry_def: &EntryDefinition,
+    crud: &Crud,
+    countersigning_agents: u8,
+) -> ScaffoldResult<()> {
+    if countersigning_agents < 2 {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "countersigned entries need at least 2 agents, but {countersigning_agents} were given"
+        )));
+    }
+    if crud.update {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "countersigned entry types can't be updated by a single agent, remove update from the CRUD functions of {}",
+            entry_def.pascal_case_name()
+        )));
+    }
+    if let Some(f) = entry_def.fields.iter().find(|f| f.linked_from.is_some()) {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "the source chain is locked during a countersigning session, so no links can be created from the linked_from field {} of a countersigned entry type",
+            f.field_name
+        )));
+    }
+    Ok(())
+}
+
+/// Rejects the entries of the given type that are not created in a countersigning session
+/// between the given number of agents
+pub fn add_countersigning_validation_to_integrity_zome(
+    zome_file_tree: ZomeFileTree,
+    entry_def: &EntryDefinition,
+    countersigning_agents: u8,
+) -> ScaffoldResult<ZomeFileTree> {
+    let dna_manifest_path = zome_file_tree.dna_file_tree.dna_manifest_path.clone();
+    let zome_manifest = zome_file_tree.zome_manifest.clone();
+    let crate_src_path = zome_file_tree.zome_crate_path.join("src");
+
+    let entry_def_file_name = format!("{}.rs", entry_def.snake_case_name());
+    let validation_items: syn::File =
+        syn::parse2(countersigning_validation(entry_def, countersigning_agents))?;
+
+    let validate_countersigning_fn =
+        format_ident!("validate_{}_countersigning", entry_def.snake_case_name());
+    let countersigning_stmts: Vec<syn::Stmt> = parse_quote! {
+        let countersigning_result = #validate_countersigning_fn(&op)?;
+        if countersigning_result != ValidateCallbackResult::Valid {
+            return Ok(countersigning_result);
+        }
+    };
+
+    let mut file_tree = zome_file_tree.dna_file_tree.file_tree();
+
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+    map_rust_files(
+        file_tree
+            .path_mut(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        |file_path, mut file| {
+            if file_path == Path::new(&entry_def_file_name) {
+                file.items.extend(validation_items.items.clone());
+            }
+
+            // The session is checked before the op reaches the validation of the entry type
+            for item in &mut file.items {
+                if let syn::Item::Fn(item_fn) = item {
+                    if item_fn.sig.ident == "validate" {
+                        item_fn
+                            .block
+                            .stmts
+                            .splice(0..0, countersigning_stmts.clone());
+                    }
+                }
+            }
+            Ok(file)
+        },
+    )
+    .map_err(|e| match e {
+        ScaffoldError::MalformedFile(path, error) => {
+            ScaffoldError::MalformedFile(crate_src_path.join(path), error)
+        }
+        _ => e,
+    })?;
+
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    ZomeFileTree::from_zome_manifest(dna_file_tree, zome_manifest)
+}
+
+/// Replaces the `create_<entry>` function of the coordinator zome with the functions that drive a
+/// countersigning session creating the entry, and enables countersigning in the zome's crate
+pub fn add_countersigning_functions_to_coordinator(
+    zome_file_tree: ZomeFileTree,
+    entry_def: &EntryDefinition,
+    countersigning_agents: u8,
+) -> ScaffoldResult<ZomeFileTree> {
+    let dna_manifest_path = zome_file_tree.dna_file_tree.dna_manifest_path.clone();
+    let zome_manifest = zome_file_tree.zome_manifest.clone();
+    let crate_src_path = zome_file_tree.zome_crate_path.join("src");
+
+    let entry_def_file_name = format!("{}.rs", entry_def.snake_case_name());
+    let create_fn_name = format!("create_{}", entry_def.snake_case_name());
+    let countersigning_items: syn::File =
+        syn::parse2(countersigning_functions(entry_def, countersigning_agents))?;
+
+    let mut file_tree = zome_file_tree.dna_file_tree.file_tree();
+
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+    map_rust_files(
+        file_tree
+            .path_mut(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        |file_path, mut file| {
+            if file_path == Path::new(&entry_def_file_name) {
+                let index = file
+                    .items
+                    .iter()
+                    .position(|i| matches!(i, syn::Item::Fn(f) if f.sig.ident == create_fn_name))
+                    .ok_or(ScaffoldError::MalformedFile(
+                        file_path.to_path_buf(),
+                        format!("the {create_fn_name} function was not found"),
+                    ))?;
+                file.items
+                    .splice(index..=index, countersigning_items.items.clone());
+            }
+            Ok(file)
+        },
+    )
+    .map_err(|e| match e {
+        ScaffoldError::MalformedFile(path, error) => {
+            ScaffoldError::MalformedFile(crate_src_path.join(path), error)
+        }
+        _ => e,
+    })?;
+
+    let cargo_toml_path = zome_file_tree.zome_crate_path.join("Cargo.toml");
+    map_file(&mut file_tree, &cargo_toml_path, |contents| {
+        enable_countersigning_feature(&contents).map_err(|e| match e {
+            ScaffoldError::MalformedFile(_, error) => {
+                ScaffoldError::MalformedFile(cargo_toml_path.clone(), error)
+            }
+            _ => e,
+        })
+    })?;
+
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    ZomeFileTree::from_zome_manifest(dna_file_tree, zome_manifest)
+}
+
+/// Adds the countersigning feature to the hdk dependency and the holochain dev-dependency of a
+/// coordinator zome's Cargo.toml, keeping the rest of the file as it is
+fn enable_countersigning_feature(cargo_toml: &str) -> ScaffoldResult<String> {
+    let mut section = "";
+    let mut lines = Vec::new();
+
+    for line in cargo_toml.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            section = trimmed;
+        }
+        let crate_name = match section {
+            "[dependencies]" => "hdk",
+            "[dev-dependencies]" => "holochain",
+            _ => "",
+        };
+        let is_dependency = !crate_name.is_empty()
+            && trimmed
+                .strip_prefix(crate_name)
+                .is_some_and(|rest| rest.trim_start().starts_with('='));
+        if !is_dependency {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let mut dependency = toml::from_str::<toml::Table>(trimmed)?
+            .remove(crate_name)
+            .ok_or(ScaffoldError::MalformedFile(
+                Path::new("Cargo.toml").to_path_buf(),
+                format!("malformed {crate_name} dependency"),
+            ))?;
+        if let toml::Value::String(version) = dependency {
+            let mut table = toml::Table::new();
+            table.insert("version".to_string(), toml::Value::String(version));
+            dependency = toml::Value::Table(table);
+        }
+        if let toml::Value::Table(table) = &mut dependency {
+            if let toml::Value::Array(features) = table
+                .entry("features")
+                .or_insert_with(|| toml::Value::Array(vec![]))
+            {
+                let feature = toml::Value::String(COUNTERSIGNING_FEATURE.to_string());
+                if !features.contains(&feature) {
+                    features.push(feature);
+                }
+            }
+        }
+        lines.push(format!("{crate_name} = {dependency}"));
+    }
+
+    Ok(format!("{}\n", lines.join("\n")))
+}
+
+fn countersigning_agents_const(entry_def: &EntryDefinition) -> syn::Ident {
+    format_ident!(
+        "{}_COUNTERSIGNING_AGENTS",
+        entry_def.name.to_case(Case::UpperSnake)
+    )
+}
+
+fn countersigning_validation(
+    entry_def: &EntryDefinition,
+    countersigning_agents: u8,
+) -> TokenStream {
+    let pascal_entry_def_name = format_ident!("{}", entry_def.pascal_case_name());
+    let validate_countersigning_fn =
+        format_ident!("validate_{}_countersigning", entry_def.snake_case_name());
+    let agents_const = countersigning_agents_const(entry_def);
+    let agents = Literal::usize_unsuffixed(countersigning_agents as usize);
+    let plural_name = pluralizer::pluralize(&entry_def.pascal_case_name(), 2, false);
+
+    let agents_doc = format!(
+        " The number of agents that countersign each {}",
+        entry_def.pascal_case_name()
+    );
+    let validate_doc = format!(
+        " {plural_name} can only be created in a countersigning session between {countersigning_agents} agents"
+    );
+    let no_session_reason =
+        format!("{plural_name} can only be created in a countersigning session");
+    let wrong_agents_reason =
+        format!("{plural_name} must be countersigned by {{{agents_const}}} agents");
+    let wrong_action_reason = format!(
+        "The countersigning session of a {} must create it",
+        entry_def.pascal_case_name()
+    );
+
+    quote! {
+        #[doc = #agents_doc]
+        pub const #agents_const: usize = #agents;
+
+        #[doc = #validate_doc]
+        pub fn #validate_countersigning_fn(op: &Op) -> ExternResult<ValidateCallbackResult> {
+            let Op::CreateEntry(create_entry) = op else {
+                return Ok(ValidateCallbackResult::Valid);
+            };
+            let app_entry_def: AppEntryDef = crate::UnitEntryTypes::#pascal_entry_def_name.try_into()?;
+            let entry_type = EntryType::App(app_entry_def);
+            if create_entry.action.hashed.content.entry_type() != Some(&entry_type) {
+                return Ok(ValidateCallbackResult::Valid);
+            }
+            let Entry::CounterSign(session_data, _) = &create_entry.entry else {
+                return Ok(ValidateCallbackResult::Invalid(String::from(#no_session_reason)));
+            };
+            if session_data.preflight_request.signing_agents.len() != #agents_const {
+                return Ok(ValidateCallbackResult::Invalid(format!(#wrong_agents_reason)));
+            }
+            if session_data.preflight_request.action_base != ActionBase::Create(CreateBase::new(entry_type)) {
+                return Ok(ValidateCallbackResult::Invalid(String::from(#wrong_action_reason)));
+            }
+            Ok(ValidateCallbackResult::Valid)
+        }
+    }
+}
+
+fn countersigning_functions(entry_def: &EntryDefinition, countersigning_agents: u8) -> TokenStream {
+    let pascal_name = entry_def.pascal_case_name();
+    let snake_name = entry_def.snake_case_name();
+    let pascal_entry_def_name = format_ident!("{pascal_name}");
+    let snake_entry_def_name = format_ident!("{snake_name}");
+    let agents_const = countersigning_agents_const(entry_def);
+
+    let preflight_input = format_ident!("{pascal_name}PreflightRequestInput");
+    let commit_input = format_ident!("CommitCountersigned{pascal_name}Input");
+    let generate_fn = format_ident!("generate_{snake_name}_preflight_request");
+    let accept_fn = format_ident!("accept_{snake_name}_preflight_request");
+    let commit_fn = format_ident!("commit_countersigned_{snake_name}");
+
+    let agents_doc = format!(
+        " The {countersigning_agents} agents countersigning the {pascal_name}, including the one starting the session"
+    );
+    let generate_doc = format!(
+        " Starts a countersigning session creating the {pascal_name}: all the agents have to accept the returned request and commit the {pascal_name} within 10 seconds"
+    );
+    let accept_doc = format!(
+        " Locks the source chain of the agent until the {pascal_name} is committed or the session times out"
+    );
+    let commit_doc = format!(
+        " Commits the {pascal_name} with the responses of all the agents, the session completes once all of them have committed it"
+    );
+    let wrong_agents_message =
+        format!("A {pascal_name} must be countersigned by {{{agents_const}}} agents");
+    let wrong_request_message =
+        format!("The preflight request is not for the creation of a {pascal_name}");
+
+    quote! {
+        #[derive(Serialize, Deserialize, Debug)]
+        pub struct #preflight_input {
+            pub #snake_entry_def_name: #pascal_entry_def_name,
+            #[doc = #agents_doc]
+            pub agents: Vec<AgentPubKey>,
+        }
+
+        #[doc = #generate_doc]
+        #[hdk_extern]
+        pub fn #generate_fn(input: #preflight_input) -> ExternResult<PreflightRequest> {
+            if input.agents.len() != #agents_const {
+                return Err(wasm_error!(WasmErrorInner::Guest(format!(#wrong_agents_message))));
+            }
+            let app_entry_def: AppEntryDef = UnitEntryTypes::#pascal_entry_def_name.try_into()?;
+            PreflightRequest::try_new(
+                hash_entry(&input.#snake_entry_def_name)?,
+                input.agents.into_iter().map(|agent| (agent, vec![])).collect(),
+                vec![],
+                0,
+                false,
+                session_times_from_millis(10_000)?,
+                ActionBase::Create(CreateBase::new(EntryType::App(app_entry_def))),
+                PreflightBytes(vec![]),
+            )
+            .map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))
+        }
+
+        #[doc = #accept_doc]
+        #[hdk_extern]
+        pub fn #accept_fn(preflight_request: PreflightRequest) -> ExternResult<PreflightRequestAcceptance> {
+            let app_entry_def: AppEntryDef = UnitEntryTypes::#pascal_entry_def_name.try_into()?;
+            if preflight_request.action_base != ActionBase::Create(CreateBase::new(EntryType::App(app_entry_def))) {
+                return Err(wasm_error!(WasmErrorInner::Guest(String::from(#wrong_request_message))));
+            }
+            accept_countersigning_preflight_request(preflight_request)
+        }
+
+        #[derive(Serialize, Deserialize, Debug)]
+        pub struct #commit_input {
+            pub #snake_entry_def_name: #pascal_entry_def_name,
+            pub responses: Vec<PreflightResponse>,
+        }
+
+        #[doc = #commit_doc]
+        #[hdk_extern]
+        pub fn #commit_fn(input: #commit_input) -> ExternResult<ActionHash> {
+            let session_data = CounterSigningSessionData::try_from_responses(input.responses, vec![])
+                .map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
+            let entry = Entry::CounterSign(
+                Box::new(session_data),
+                AppEntryBytes::try_from(input.#snake_entry_def_name)?,
+            );
+            let scoped_entry_def: ScopedEntryDefIndex = UnitEntryTypes::#pascal_entry_def_name.try_into()?;
+            create(CreateInput::new(
+                scoped_entry_def,
+                EntryVisibility::from(UnitEntryTypes::#pascal_entry_def_name),
+                entry,
+                ChainTopOrdering::Strict,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{scaffold::entry_type::definitions::FieldDefinition, utils::unparse_pretty};
+
+    fn trade() -> EntryDefinition {
+        EntryDefinition {
+            name: "trade".to_string(),
+            fields: vec![FieldDefinition::from_str("amount:u32").unwrap()],
+            reference_entry_hash: false,
+        }
+    }
+
+    #[test]
+    fn countersigning_session_is_driven_by_the_coordinator() {
+        let generated =
+            unparse_pretty(&syn::parse2(countersigning_functions(&trade(), 2)).unwrap());
+
+        assert!(generated.contains("pub fn generate_trade_preflight_request("));
+        assert!(generated.contains("input: TradePreflightRequestInput,"));
+        assert!(generated.contains("if input.agents.len() != TRADE_COUNTERSIGNING_AGENTS {"));
+        assert!(generated.contains("accept_countersigning_preflight_request(preflight_request)"));
+        assert!(generated.contains("pub fn commit_countersigned_trade("));
+        assert!(generated.contains("ChainTopOrdering::Strict,"));
+    }
+
+    #[test]
+    fn countersigning_session_is_validated() {
+        let generated =
+            unparse_pretty(&syn::parse2(countersigning_validation(&trade(), 3)).unwrap());
+
+        assert!(generated.contains("pub const TRADE_COUNTERSIGNING_AGENTS: usize = 3;"));
+        assert!(generated.contains(
+            "pub fn validate_trade_countersigning(op: &Op) -> ExternResult<ValidateCallbackResult>"
+        ));
+        assert!(generated
+            .contains("let Entry::CounterSign(session_data, _) = &create_entry.entry else {"));
+    }
+
+    #[test]
+    fn countersigning_feature_is_enabled() {
+        let cargo_toml = r#"[package]
+name = "trades"
+
+[dependencies]
+hdk = { workspace = true }
+serde = { workspace = true }
+
+[dev-dependencies]
+holochain = { workspace = true, features = ["wasmer-sys-cranelift", "test_utils"] }
+"#;
+        let cargo_toml = enable_countersigning_feature(cargo_toml).unwrap();
+
+        assert!(cargo_toml.starts_with("[package]\nname = \"trades\"\n\n[dependencies]\n"));
+        assert!(cargo_toml
+            .contains("hdk = { features = [\"unstable-countersigning\"], workspace = true }\n"));
+        assert!(cargo_toml.contains("serde = { workspace = true }\n"));
+        assert!(cargo_toml.contains(
+            "holochain = { features = [\"wasmer-sys-cranelift\", \"test_utils\", \"unstable-countersigning\"], workspace = true }\n"
+        ));
+    }
+
+    #[test]
+    fn countersigned_entry_types_cant_be_updated() {
+        let crud = Crud {
+            update: true,
+            delete: false,
+        };
+        assert!(check_countersigned_entry_type(&trade(), &crud, 2).is_err());
+        assert!(check_countersigned_entry_type(&trade(), &Crud::default(), 1).is_err());
+        assert!(check_countersigned_entry_type(&trade(), &Crud::default(), 2).is_ok());
+    }
+}
@@ -0,0 +1,354 @@
+use std::{ffi::OsString, str::FromStr};
+
+use convert_case::{Case, Casing};
+use quote::format_ident;
+use serde::Serialize;
+use syn::parse_quote;
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::{map_file, map_rust_files, FileTree},
+    scaffold::{
+        dna::{
+            properties::{ensure_dna_property, DnaPropertyValue},
+            DnaFileTree,
+        },
+        zome::{coordinator::find_extern_function_in_zome, ZomeFileTree},
+    },
+    utils::unparse_pretty,
+};
+
+use super::definitions::{EntryDefinition, FieldDefinition};
+
+/// Who is allowed to create entries of an entry type
+#[derive(Debug, Default, Serialize, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CreatePolicy {
+    /// Any agent of the DNA
+    #[default]
+    Anyone,
+    /// Only the progenitor agent, set in the `progenitor` DNA property
+    Progenitor {
+        /// The other DNA properties, which the sweettests installing the DNA need to set
+        other_dna_properties: Vec<DnaPropertyValue>,
+    },
+}
+
+impl FromStr for CreatePolicy {
+    type Err = ScaffoldError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "anyone" => Ok(CreatePolicy::Anyone),
+            "progenitor" => Ok(CreatePolicy::Progenitor {
+                other_dna_properties: vec![],
+            }),
+            _ => Err(ScaffoldError::InvalidArguments(format!(
+                "Invalid create policy \"{s}\": only \"anyone\" and \"progenitor\" are allowed"
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for CreatePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            CreatePolicy::Anyone => "anyone",
+            CreatePolicy::Progenitor { .. } => "progenitor",
+        };
+        write!(f, "{str}")
+    }
+}
+
+/// Makes sure the integrity zome has a `progenitor: AgentPubKey` DNA property, scaffolding the
+/// DNA properties if there are none yet, and returns the other DNA properties
+pub fn ensure_progenitor_dna_property(
+    zome_file_tree: ZomeFileTree,
+    template_file_tree: &FileTree,
+    no_spec: bool,
+) -> ScaffoldResult<(ZomeFileTree, Vec<DnaPropertyValue>)> {
+    ensure_dna_property(
+        zome_file_tree,
+        template_file_tree,
+        &FieldDefinition::from_str("progenitor:AgentPubKey")?,
+        "the progenitor create policy",
+        no_spec,
+    )
+}
+
+/// Rejects the creation of entries of the given type by any agent other than the progenitor
+pub fn add_progenitor_validation_to_integrity_zome(
+    zome_file_tree: ZomeFileTree,
+    entry_def: &EntryDefinition,
+) -> ScaffoldResult<ZomeFileTree> {
+    let dna_manifest_path = zome_file_tree.dna_file_tree.dna_manifest_path.clone();
+    let zome_manifest = zome_file_tree.zome_manifest.clone();
+    let crate_src_path = zome_file_tree.zome_crate_path.join("src");
+
+    let validate_create_fn = format!("validate_create_{}", entry_def.snake_case_name());
+    let invalid_reason = format!(
+        "Only the progenitor can create {}",
+        pluralizer::pluralize(&entry_def.name.to_case(Case::Lower), 2, false)
+    );
+    let progenitor_stmt: syn::Stmt = parse_quote! {
+        if action.author() != &crate::get_dna_properties()?.progenitor {
+            return Ok(ValidateCallbackResult::Invalid(String::from(#invalid_reason)));
+        }
+    };
+
+    let mut file_tree = zome_file_tree.dna_file_tree.file_tree();
+
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+    map_rust_files(
+        file_tree
+            .path_mut(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        |_, mut file| {
+            for item in &mut file.items {
+                if let syn::Item::Fn(item_fn) = item {
+                    if item_fn.sig.ident != validate_create_fn {
+                        continue;
+                    }
+                    // The create action is now read by the progenitor check
+                    if let Some(syn::FnArg::Typed(pat_type)) = item_fn.sig.inputs.first_mut() {
+                        if let syn::Pat::Ident(pat_ident) = &mut *pat_type.pat {
+                            if pat_ident.ident == "_action" {
+                                pat_ident.ident = format_ident!("action");
+                            }
+                        }
+                    }
+                    item_fn.block.stmts.insert(0, progenitor_stmt.clone());
+                }
+            }
+            Ok(file)
+        },
+    )
+    .map_err(|e| match e {
+        ScaffoldError::MalformedFile(path, error) => {
+            ScaffoldError::MalformedFile(crate_src_path.join(path), error)
+        }
+        _ => e,
+    })?;
+
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    ZomeFileTree::from_zome_manifest(dna_file_tree, zome_manifest)
+}
+
+/// Adds an `is_progenitor` function to the coordinator zome, so that the UI can tell whether
+/// the current agent can create the progenitor-only entry types
+pub fn add_is_progenitor_to_coordinator(
+    zome_file_tree: ZomeFileTree,
+    integrity_zome_name: &str,
+) -> ScaffoldResult<ZomeFileTree> {
+    if find_extern_function_in_zome(&zome_file_tree, "is_progenitor")?.is_some() {
+        return Ok(zome_file_tree);
+    }
+
+    let dna_manifest_path = zome_file_tree.dna_file_tree.dna_manifest_path.clone();
+    let zome_manifest = zome_file_tree.zome_manifest.clone();
+    let lib_rs_path = zome_file_tree.zome_crate_path.join("src").join("lib.rs");
+
+    let integrity_crate = format_ident!("{}", integrity_zome_name.to_case(Case::Snake));
+    let is_progenitor: syn::File = parse_quote! {
+        #[hdk_extern]
+        pub fn is_progenitor() -> ExternResult<bool> {
+            let progenitor = #integrity_crate::get_dna_properties()?.progenitor;
+            Ok(agent_info()?.agent_initial_pubkey == progenitor)
+        }
+    };
+
+    let mut file_tree = zome_file_tree.dna_file_tree.file_tree();
+    map_file(&mut file_tree, &lib_rs_path, |contents| {
+        Ok(format!("{contents}\n{}", unparse_pretty(&is_progenitor)))
+    })?;
+
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    ZomeFileTree::from_zome_manifest(dna_file_tree, zome_manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use build_fs_tree::{dir, file};
+
+    use super::*;
+    use crate::{
+        file_tree::{file_content, insert_file, map_file},
+        scaffold::{
+            app::{cargo::workspace_cargo_toml, manifests::empty_happ_manifest},
+            dna::{manifest::empty_dna_manifest, properties::scaffold_dna_properties},
+            web_app::template_type::TemplateType,
+            zome::{scaffold_coordinator_zome_in_path, scaffold_integrity_zome_with_path},
+        },
+    };
+
+    const DNA_MANIFEST_PATH: &str = "workdir/dna.yaml";
+
+    /// Scaffolds the "forum_integrity" and "forum" zomes in the empty DNA of an app
+    fn forum_dna_file_tree() -> DnaFileTree {
+        let app_file_tree: FileTree = dir! {
+            "Cargo.toml" => file!(workspace_cargo_toml()),
+            "workdir" => dir! {
+                "happ.yaml" => file!(empty_happ_manifest("forum_app", None).unwrap()),
+                "dna.yaml" => file!(empty_dna_manifest("forum").unwrap())
+            },
+            "zomes" => dir! {
+                "integrity" => dir! {},
+                "coordinator" => dir! {}
+            }
+        };
+        let template_file_tree = TemplateType::Svelte.file_tree().unwrap();
+        let dna_manifest_path = PathBuf::from(DNA_MANIFEST_PATH);
+
+        let dna_file_tree =
+            DnaFileTree::from_dna_manifest_path(app_file_tree, &dna_manifest_path).unwrap();
+        let file_tree = scaffold_integrity_zome_with_path(
+            dna_file_tree,
+            &template_file_tree,
+            "forum_integrity",
+            Path::new("zomes/integrity"),
+        )
+        .unwrap()
+        .file_tree;
+        let dna_file_tree =
+            DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path).unwrap();
+        let file_tree = scaffold_coordinator_zome_in_path(
+            dna_file_tree,
+            &template_file_tree,
+            "forum",
+            Some(&vec!["forum_integrity".to_string()]),
+            Path::new("zomes/coordinator"),
+        )
+        .unwrap()
+        .file_tree;
+
+        DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path).unwrap()
+    }
+
+    fn zome_file(zome_file_tree: &ZomeFileTree, file_name: &str) -> String {
+        file_content(
+            zome_file_tree.dna_file_tree.file_tree_ref(),
+            &zome_file_tree.zome_crate_path.join("src").join(file_name),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn progenitor_check_runs_before_the_entry_validation() {
+        let integrity_zome =
+            ZomeFileTree::get_or_choose_integrity(forum_dna_file_tree(), None).unwrap();
+        let dna_manifest_path = integrity_zome.dna_file_tree.dna_manifest_path.clone();
+        let zome_manifest = integrity_zome.zome_manifest.clone();
+        let src_path = integrity_zome.zome_crate_path.join("src");
+
+        let mut file_tree = integrity_zome.dna_file_tree.file_tree();
+        insert_file(
+            &mut file_tree,
+            &src_path.join("post.rs"),
+            r#"use hdi::prelude::*;
+
+pub fn validate_create_post(
+    _action: EntryCreationAction,
+    post: Post,
+) -> ExternResult<ValidateCallbackResult> {
+    if post.title.is_empty() {
+        return Ok(ValidateCallbackResult::Invalid(String::from("Posts need a title")));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+"#,
+        )
+        .unwrap();
+        map_file(&mut file_tree, &src_path.join("lib.rs"), |contents| {
+            Ok(format!("pub mod post;\n{contents}"))
+        })
+        .unwrap();
+        let integrity_zome = ZomeFileTree::from_zome_manifest(
+            DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path).unwrap(),
+            zome_manifest,
+        )
+        .unwrap();
+
+        let integrity_zome = add_progenitor_validation_to_integrity_zome(
+            integrity_zome,
+            &EntryDefinition {
+                name: "post".to_string(),
+                fields: vec![FieldDefinition::from_str("title:String").unwrap()],
+                reference_entry_hash: false,
+            },
+        )
+        .unwrap();
+
+        let post_rs = zome_file(&integrity_zome, "post.rs");
+        assert!(post_rs.contains("action: EntryCreationAction,"));
+        assert!(
+            post_rs.contains("if action.author() != &crate::get_dna_properties()?.progenitor {")
+        );
+        assert!(post_rs.contains(r#"String::from("Only the progenitor can create posts")"#));
+        assert!(post_rs.find("Only the progenitor") < post_rs.find("Posts need a title"));
+    }
+
+    #[test]
+    fn is_progenitor_compares_the_agent_with_the_dna_property() {
+        let coordinator_zome =
+            ZomeFileTree::get_or_choose_coordinator(forum_dna_file_tree(), None).unwrap();
+
+        let coordinator_zome =
+            add_is_progenitor_to_coordinator(coordinator_zome, "forum_integrity").unwrap();
+        let coordinator_zome =
+            add_is_progenitor_to_coordinator(coordinator_zome, "forum_integrity").unwrap();
+
+        let lib_rs = zome_file(&coordinator_zome, "lib.rs");
+        assert_eq!(lib_rs.matches("pub fn is_progenitor()").count(), 1);
+        assert!(lib_rs.contains("pub fn is_progenitor() -> ExternResult<bool> {"));
+        assert!(
+            lib_rs.contains("let progenitor = forum_integrity::get_dna_properties()?.progenitor;")
+        );
+        assert!(lib_rs.contains("Ok(agent_info()?.agent_initial_pubkey == progenitor)"));
+    }
+
+    #[test]
+    fn other_dna_properties_get_their_manifest_defaults_in_the_sweettests() {
+        let integrity_zome =
+            ZomeFileTree::get_or_choose_integrity(forum_dna_file_tree(), None).unwrap();
+        let dna_manifest_path = integrity_zome.dna_file_tree.dna_manifest_path.clone();
+        let zome_manifest = integrity_zome.zome_manifest.clone();
+        let template_file_tree = TemplateType::Svelte.file_tree().unwrap();
+
+        let file_tree = scaffold_dna_properties(
+            integrity_zome,
+            &template_file_tree,
+            &[
+                FieldDefinition::from_str("max_posts:u32").unwrap(),
+                FieldDefinition::from_str("progenitor:AgentPubKey").unwrap(),
+                FieldDefinition::from_str("moderators:Vec<AgentPubKey>").unwrap(),
+            ],
+            true,
+        )
+        .unwrap()
+        .file_tree;
+        let integrity_zome = ZomeFileTree::from_zome_manifest(
+            DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path).unwrap(),
+            zome_manifest,
+        )
+        .unwrap();
+
+        let (_, other_dna_properties) =
+            ensure_progenitor_dna_property(integrity_zome, &template_file_tree, true).unwrap();
+
+        assert_eq!(
+            other_dna_properties,
+            vec![
+                DnaPropertyValue {
+                    field_name: "max_posts".to_string(),
+                    value: "0".to_string(),
+                },
+                DnaPropertyValue {
+                    field_name: "moderators".to_string(),
+                    value: "vec![]".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -34,6 +34,8 @@ pub enum FieldType {
     EntryHash,
     DnaHash,
     ExternalHash,
+    /// A file stored with the `file-storage` zomes, referenced by the `EntryHash` of its metadata
+    File,
     Enum {
         label: String,
         variants: Vec<String>,
@@ -72,6 +74,7 @@ impl std::fmt::Display for FieldType {
             FieldType::EntryHash => "EntryHash",
             FieldType::DnaHash => "DnaHash",
             FieldType::ExternalHash => "ExternalHash",
+            FieldType::File => "File",
             FieldType::AgentPubKey => "AgentPubKey",
             FieldType::Enum { .. } => "Enum",
         };
@@ -94,6 +97,7 @@ impl FieldType {
             FieldType::DnaHash,
             FieldType::ExternalHash,
             FieldType::AgentPubKey,
+            FieldType::File,
             FieldType::Enum {
                 label: String::new(),
                 variants: Vec::new(),
@@ -134,6 +138,7 @@ impl FieldType {
             DnaHash => quote!(DnaHash),
             EntryHash => quote!(EntryHash),
             ExternalHash => quote!(ExternalHash),
+            File => quote!(EntryHash),
             AgentPubKey => quote!(AgentPubKey),
             Enum { label, .. } => {
                 let ident = format_ident!("{}", label);
@@ -158,6 +163,7 @@ impl FieldType {
             EntryHash => "EntryHash",
             DnaHash => "DnaHash",
             ExternalHash => "ExternalHash",
+            File => "EntryHash",
             Enum { label, .. } => label,
         }
     }
@@ -1,4 +1,6 @@
-use std::path::PathBuf;
+use std::{ffi::OsString, path::PathBuf};
+
+use build_fs_tree::dir;
 
 use colored::Colorize;
 use convert_case::{Case, Casing};
@@ -8,7 +10,7 @@ use crate::{
     error::{ScaffoldError, ScaffoldResult},
     file_tree::{dir_content, FileTree},
     reserved_words::check_for_reserved_keywords,
-    scaffold::zome::ZomeFileTree,
+    scaffold::{web_app::template_manifest::TemplateManifest, zome::ZomeFileTree},
     utils::{check_case, input_with_case, input_with_custom_validation},
 };
 
@@ -17,6 +19,25 @@ use super::{
     integrity::get_all_entry_types,
 };
 
+/// Gets the `field-types` templates to choose widgets from, keeping only the widgets declared
+/// in the template manifest if the template has one
+pub fn choosable_field_types_templates(template_file_tree: &FileTree) -> ScaffoldResult<FileTree> {
+    let v: Vec<OsString> = PathBuf::from("field-types")
+        .iter()
+        .map(|s| s.to_os_string())
+        .collect();
+    let mut field_types_templates = template_file_tree
+        .path(&mut v.iter())
+        .cloned()
+        .unwrap_or(dir! {});
+
+    if let Some(manifest) = TemplateManifest::from_template_file_tree(template_file_tree)? {
+        manifest.retain_declared_widgets(&mut field_types_templates);
+    }
+
+    Ok(field_types_templates)
+}
+
 pub fn choose_fields(
     entry_type_name: &str,
     zome_file_tree: &ZomeFileTree,
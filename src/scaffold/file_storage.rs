@@ -0,0 +1,390 @@
+use holochain_types::prelude::DnaManifest;
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::{insert_file, map_file, FileTree},
+    reserved_words::check_for_reserved_keywords,
+    templates::{file_storage::scaffold_file_storage_templates, ScaffoldedTemplate},
+    utils::unparse_pretty,
+};
+
+use super::{
+    app::{cargo::workspace_path_dependency, AppFileTree},
+    dna::DnaFileTree,
+    zome::{integrity_zome_name, scaffold_zome_pair},
+};
+
+/// Scaffolds a coordinator/integrity zome pair that stores files split in chunks:
+/// the integrity zome defines the `FileChunk` and `FileMetadata` entry types and validates their
+/// size, and the coordinator zome exposes the `upload_file` and `download_file` functions
+pub fn scaffold_file_storage(
+    dna_file_tree: DnaFileTree,
+    template_file_tree: &FileTree,
+    zome_name: &str,
+    max_file_size_mb: usize,
+    no_ui: bool,
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    check_for_reserved_keywords(zome_name)?;
+    check_max_file_size(max_file_size_mb)?;
+
+    let dna_manifest_path = dna_file_tree.dna_manifest_path.clone();
+    let dna_role_name = dna_file_tree.dna_manifest.name();
+
+    let file_tree = scaffold_zome_pair(
+        dna_file_tree.file_tree(),
+        template_file_tree,
+        &dna_role_name,
+        Some(zome_name),
+    )?;
+
+    add_file_storage_to_zome_pair(
+        DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?,
+        template_file_tree,
+        zome_name,
+        max_file_size_mb,
+        no_ui,
+        no_spec,
+    )
+}
+
+/// Turns the freshly scaffolded `zome_name` coordinator zome and its integrity zome into the
+/// file storage zomes, scaffolding the templates for them
+pub fn add_file_storage_to_zome_pair(
+    dna_file_tree: DnaFileTree,
+    template_file_tree: &FileTree,
+    zome_name: &str,
+    max_file_size_mb: usize,
+    no_ui: bool,
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    check_max_file_size(max_file_size_mb)?;
+
+    // The new zomes are not on disk yet, so their crates are located through the workspace
+    let dna_role_name = dna_file_tree.dna_manifest.name();
+    let integrity_zome_name = integrity_zome_name(zome_name);
+    let (integrity_zomes, coordinator_zomes) = match dna_file_tree.dna_manifest.clone() {
+        DnaManifest::V0(m) => (m.integrity.zomes, m.coordinator.zomes),
+    };
+    let mut file_tree = dna_file_tree.file_tree();
+
+    let integrity_zome_not_found =
+        || ScaffoldError::IntegrityZomeNotFound(integrity_zome_name.clone(), dna_role_name.clone());
+    let integrity_zome_manifest = integrity_zomes
+        .into_iter()
+        .find(|z| z.name.0 == integrity_zome_name)
+        .ok_or_else(integrity_zome_not_found)?;
+    let integrity_src_path = workspace_path_dependency(&file_tree, &integrity_zome_name)?
+        .ok_or_else(integrity_zome_not_found)?
+        .join("src");
+
+    let coordinator_zome_not_found =
+        || ScaffoldError::CoordinatorZomeNotFound(zome_name.to_string(), dna_role_name.clone());
+    let coordinator_zome_manifest = coordinator_zomes
+        .into_iter()
+        .find(|z| z.name.0 == zome_name)
+        .ok_or_else(coordinator_zome_not_found)?;
+    let coordinator_src_path = workspace_path_dependency(&file_tree, zome_name)?
+        .ok_or_else(coordinator_zome_not_found)?
+        .join("src");
+
+    // 1. Replace the empty integrity zome with the file storage entry types and their validation
+    map_file(&mut file_tree, &integrity_src_path.join("lib.rs"), |_| {
+        rust_file(integrity_lib_rs())
+    })?;
+    insert_file(
+        &mut file_tree,
+        &integrity_src_path.join("file_chunk.rs"),
+        &rust_file(file_chunk_rs())?,
+    )?;
+    insert_file(
+        &mut file_tree,
+        &integrity_src_path.join("file_metadata.rs"),
+        &rust_file(file_metadata_rs(max_file_size_mb))?,
+    )?;
+
+    // 2. Add the functions to upload and download files to the coordinator zome
+    insert_file(
+        &mut file_tree,
+        &coordinator_src_path.join("file_storage.rs"),
+        &rust_file(coordinator_file_storage_rs(&integrity_zome_manifest.name.0))?,
+    )?;
+    map_file(
+        &mut file_tree,
+        &coordinator_src_path.join("lib.rs"),
+        |contents| {
+            Ok(format!(
+                r#"pub mod file_storage;
+{contents}"#
+            ))
+        },
+    )?;
+
+    let app_file_tree = AppFileTree::get_or_choose(file_tree, None)?;
+    let app_name = app_file_tree.app_manifest.app_name().to_string();
+
+    scaffold_file_storage_templates(
+        app_file_tree.file_tree(),
+        template_file_tree,
+        &app_name,
+        &dna_role_name,
+        &integrity_zome_manifest,
+        &coordinator_zome_manifest,
+        no_ui,
+        no_spec,
+    )
+}
+
+fn check_max_file_size(max_file_size_mb: usize) -> ScaffoldResult<()> {
+    if max_file_size_mb == 0 {
+        return Err(ScaffoldError::InvalidArguments(
+            "the maximum file size must be at least 1 megabyte".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn rust_file(tokens: TokenStream) -> ScaffoldResult<String> {
+    let file: syn::File = syn::parse2(tokens)?;
+    Ok(unparse_pretty(&file))
+}
+
+fn integrity_lib_rs() -> TokenStream {
+    quote! {
+        pub mod file_chunk;
+        pub use file_chunk::*;
+        pub mod file_metadata;
+        pub use file_metadata::*;
+        use hdi::prelude::*;
+
+        #[derive(Serialize, Deserialize)]
+        #[serde(tag = "type")]
+        #[hdk_entry_types]
+        #[unit_enum(UnitEntryTypes)]
+        pub enum EntryTypes {
+            FileChunk(FileChunk),
+            FileMetadata(FileMetadata),
+        }
+
+        /// Validates the size of the stored files and their chunks
+        /// Files can't be updated, to change a file upload a new one instead
+        #[hdk_extern]
+        pub fn validate(op: Op) -> ExternResult<ValidateCallbackResult> {
+            match op.flattened::<EntryTypes, ()>()? {
+                FlatOp::CreateEntry(OpEntry::CreateEntry { app_entry, .. })
+                | FlatOp::CreateRecord(OpRecord::CreateEntry { app_entry, .. }) => match app_entry {
+                    EntryTypes::FileChunk(file_chunk) => validate_create_file_chunk(file_chunk),
+                    EntryTypes::FileMetadata(file_metadata) => validate_create_file_metadata(file_metadata),
+                },
+                FlatOp::CreateEntry(OpEntry::UpdateEntry { .. })
+                | FlatOp::Update(OpUpdate::Entry { .. })
+                | FlatOp::CreateRecord(OpRecord::UpdateEntry { .. }) => Ok(ValidateCallbackResult::Invalid(
+                    "Files can't be updated".to_string(),
+                )),
+                _ => Ok(ValidateCallbackResult::Valid),
+            }
+        }
+    }
+}
+
+fn file_chunk_rs() -> TokenStream {
+    quote! {
+        use hdi::prelude::*;
+
+        /// Maximum size in bytes of each of the chunks that files are split into (256 KB)
+        pub const MAX_CHUNK_SIZE: usize = 256 * 1024;
+
+        /// A piece of a file, at most MAX_CHUNK_SIZE bytes long
+        #[hdk_entry_helper]
+        #[derive(Clone, PartialEq)]
+        pub struct FileChunk(pub SerializedBytes);
+
+        pub fn validate_create_file_chunk(file_chunk: FileChunk) -> ExternResult<ValidateCallbackResult> {
+            if file_chunk.0.bytes().len() > MAX_CHUNK_SIZE {
+                return Ok(ValidateCallbackResult::Invalid(format!(
+                    "File chunks can't be bigger than {MAX_CHUNK_SIZE} bytes"
+                )));
+            }
+            Ok(ValidateCallbackResult::Valid)
+        }
+    }
+}
+
+fn file_metadata_rs(max_file_size_mb: usize) -> TokenStream {
+    let max_file_size_doc =
+        format!(" Maximum size in bytes of the stored files ({max_file_size_mb} MB)");
+    let max_file_size_mb = Literal::usize_unsuffixed(max_file_size_mb);
+
+    quote! {
+        use hdi::prelude::*;
+
+        use crate::{FileChunk, MAX_CHUNK_SIZE};
+
+        #[doc = #max_file_size_doc]
+        pub const MAX_FILE_SIZE: usize = #max_file_size_mb * 1024 * 1024;
+
+        /// Describes a stored file and references the chunks that hold its contents, in order
+        #[hdk_entry_helper]
+        #[derive(Clone, PartialEq)]
+        pub struct FileMetadata {
+            pub name: String,
+            pub last_modified: Timestamp,
+            pub size: usize,
+            pub file_type: String,
+            pub chunks_hashes: Vec<EntryHash>,
+        }
+
+        pub fn validate_create_file_metadata(file_metadata: FileMetadata) -> ExternResult<ValidateCallbackResult> {
+            if file_metadata.size > MAX_FILE_SIZE {
+                return Ok(ValidateCallbackResult::Invalid(format!(
+                    "Files can't be bigger than {MAX_FILE_SIZE} bytes"
+                )));
+            }
+            if file_metadata.chunks_hashes.len() != file_metadata.size.div_ceil(MAX_CHUNK_SIZE) {
+                return Ok(ValidateCallbackResult::Invalid(
+                    "The number of chunks doesn't match the size of the file".to_string(),
+                ));
+            }
+            let mut size = 0;
+            for chunk_hash in file_metadata.chunks_hashes {
+                let entry_hashed = must_get_entry(chunk_hash)?;
+                let file_chunk = FileChunk::try_from(entry_hashed.content)?;
+                size += file_chunk.0.bytes().len();
+            }
+            if size != file_metadata.size {
+                return Ok(ValidateCallbackResult::Invalid(
+                    "The size of the file doesn't match the size of its chunks".to_string(),
+                ));
+            }
+            Ok(ValidateCallbackResult::Valid)
+        }
+    }
+}
+
+fn coordinator_file_storage_rs(integrity_zome_name: &str) -> TokenStream {
+    let integrity_zome_name = format_ident!("{}", integrity_zome_name);
+
+    quote! {
+        use hdk::prelude::*;
+        use #integrity_zome_name::*;
+
+        #[derive(Serialize, Deserialize, Debug)]
+        pub struct UploadFileInput {
+            pub name: String,
+            pub last_modified: Timestamp,
+            pub file_type: String,
+            pub bytes: SerializedBytes,
+        }
+
+        /// Splits the file in chunks and stores them along with its metadata
+        /// Returns the hash of the metadata entry, which is the hash that identifies the file
+        #[hdk_extern]
+        pub fn upload_file(input: UploadFileInput) -> ExternResult<EntryHash> {
+            let bytes = input.bytes.bytes();
+            if bytes.len() > MAX_FILE_SIZE {
+                return Err(wasm_error!(WasmErrorInner::Guest(format!(
+                    "Files can't be bigger than {MAX_FILE_SIZE} bytes"
+                ))));
+            }
+
+            let mut chunks_hashes = Vec::new();
+            for chunk in bytes.chunks(MAX_CHUNK_SIZE) {
+                let file_chunk = FileChunk(SerializedBytes::from(UnsafeBytes::from(chunk.to_vec())));
+                chunks_hashes.push(hash_entry(&file_chunk)?);
+                create_entry(&EntryTypes::FileChunk(file_chunk))?;
+            }
+
+            let file_metadata = FileMetadata {
+                name: input.name,
+                last_modified: input.last_modified,
+                size: bytes.len(),
+                file_type: input.file_type,
+                chunks_hashes,
+            };
+            let file_metadata_hash = hash_entry(&file_metadata)?;
+            create_entry(&EntryTypes::FileMetadata(file_metadata))?;
+
+            Ok(file_metadata_hash)
+        }
+
+        #[hdk_extern]
+        pub fn get_file_metadata(file_metadata_hash: EntryHash) -> ExternResult<FileMetadata> {
+            get_entry_content(file_metadata_hash)
+        }
+
+        #[derive(Serialize, Deserialize, Debug)]
+        pub struct DownloadedFile {
+            pub metadata: FileMetadata,
+            pub bytes: SerializedBytes,
+        }
+
+        /// Gets the metadata of the file and all its chunks, joining them back into its contents
+        #[hdk_extern]
+        pub fn download_file(file_metadata_hash: EntryHash) -> ExternResult<DownloadedFile> {
+            let metadata = get_file_metadata(file_metadata_hash)?;
+
+            let mut bytes = Vec::with_capacity(metadata.size);
+            for chunk_hash in &metadata.chunks_hashes {
+                let file_chunk: FileChunk = get_entry_content(chunk_hash.clone())?;
+                bytes.extend_from_slice(file_chunk.0.bytes());
+            }
+
+            Ok(DownloadedFile {
+                metadata,
+                bytes: SerializedBytes::from(UnsafeBytes::from(bytes)),
+            })
+        }
+
+        fn get_entry_content<T>(entry_hash: EntryHash) -> ExternResult<T>
+        where
+            T: TryFrom<Entry, Error = WasmError>,
+        {
+            let record = get(entry_hash, GetOptions::default())?
+                .ok_or(wasm_error!(WasmErrorInner::Guest("Could not find the file".to_string())))?;
+            let entry = record
+                .entry()
+                .as_option()
+                .ok_or(wasm_error!(WasmErrorInner::Guest("The record doesn't contain an entry".to_string())))?;
+            T::try_from(entry.clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_size_limits_are_enforced_in_validation() {
+        let chunk = rust_file(file_chunk_rs()).unwrap();
+        assert!(chunk.contains("pub const MAX_CHUNK_SIZE: usize = 256 * 1024;"));
+        assert!(chunk.contains("file_chunk.0.bytes().len() > MAX_CHUNK_SIZE"));
+
+        let metadata = rust_file(file_metadata_rs(5)).unwrap();
+        assert!(metadata.contains("pub const MAX_FILE_SIZE: usize = 5 * 1024 * 1024;"));
+        assert!(metadata.contains("file_metadata.size > MAX_FILE_SIZE"));
+        assert!(metadata.contains("let entry_hashed = must_get_entry(chunk_hash)?;"));
+    }
+
+    #[test]
+    fn files_are_uploaded_and_downloaded_by_the_coordinator() {
+        let generated = rust_file(coordinator_file_storage_rs("file_storage_integrity")).unwrap();
+
+        assert!(generated.contains("use file_storage_integrity::*;"));
+        assert!(generated
+            .contains("pub fn upload_file(input: UploadFileInput) -> ExternResult<EntryHash>"));
+        assert!(generated.contains("for chunk in bytes.chunks(MAX_CHUNK_SIZE)"));
+        assert!(generated.contains("pub fn download_file("));
+    }
+
+    #[test]
+    fn files_cant_be_updated() {
+        let generated = rust_file(integrity_lib_rs()).unwrap();
+
+        assert!(generated.contains("FileChunk(FileChunk),"));
+        assert!(generated.contains("FileMetadata(FileMetadata),"));
+        assert!(generated.contains("\"Files can't be updated\""));
+    }
+}
@@ -0,0 +1,409 @@
+use std::{ffi::OsString, path::Path};
+
+use convert_case::{Case, Casing};
+use dialoguer::{theme::ColorfulTheme, Select};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::{file_exists, insert_file, map_file, map_rust_files, FileTree},
+    reserved_words::check_for_reserved_keywords,
+    templates::{function::scaffold_function_templates, ScaffoldedTemplate},
+};
+
+use super::{
+    app::AppFileTree,
+    dna::DnaFileTree,
+    entry_type::definitions::{EntryDefinition, FieldDefinition},
+    zome::{
+        coordinator::{find_extern_function_in_zome, SweettestInput},
+        ZomeFileTree,
+    },
+};
+
+/// Input of a scaffolded zome function
+pub enum FunctionInput {
+    /// An existing type, eg. `ActionHash` or `Vec<Record>`
+    Type(String),
+    /// A new struct with the given fields, defined next to the function
+    Struct {
+        name: String,
+        fields: Vec<FieldDefinition>,
+    },
+}
+
+impl FunctionInput {
+    pub fn from_args(
+        function_name: &str,
+        input_type: Option<&str>,
+        input_fields: Option<&Vec<FieldDefinition>>,
+    ) -> ScaffoldResult<Option<FunctionInput>> {
+        match (input_type, input_fields) {
+            (None, None) => Ok(None),
+            (Some(t), None) => {
+                parse_type(t)?;
+                Ok(Some(FunctionInput::Type(t.to_string())))
+            }
+            (maybe_name, Some(fields)) => {
+                let name = match maybe_name {
+                    Some(name) => {
+                        if syn::parse_str::<syn::Ident>(name).is_err() {
+                            return Err(ScaffoldError::InvalidArguments(format!(
+                                "{name} is not a valid name for the input struct of {function_name}"
+                            )));
+                        }
+                        name.to_string()
+                    }
+                    None => format!("{}Input", function_name.to_case(Case::Pascal)),
+                };
+                Ok(Some(FunctionInput::Struct {
+                    name,
+                    fields: fields.clone(),
+                }))
+            }
+        }
+    }
+
+    fn rust_type(&self) -> &str {
+        match self {
+            FunctionInput::Type(t) => t,
+            FunctionInput::Struct { name, .. } => name,
+        }
+    }
+
+    fn struct_name(&self) -> Option<&str> {
+        match self {
+            FunctionInput::Type(_) => None,
+            FunctionInput::Struct { name, .. } => Some(name),
+        }
+    }
+}
+
+/// Scaffolds an `#[hdk_extern]` function with the given input and output types into a
+/// coordinator zome, along with its sweettest stub and UI client call
+#[allow(clippy::too_many_arguments)]
+pub fn scaffold_function(
+    zome_file_tree: ZomeFileTree,
+    template_file_tree: &FileTree,
+    function_name: &str,
+    maybe_file_name: Option<&str>,
+    input: Option<FunctionInput>,
+    output_type: Option<&str>,
+    no_ui: bool,
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    check_for_reserved_keywords(function_name)?;
+
+    if find_extern_function_in_zome(&zome_file_tree, function_name)?.is_some() {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "function {function_name} already exists in zome {}",
+            zome_file_tree.zome_manifest.name
+        )));
+    }
+
+    let output_type = output_type.unwrap_or("()");
+
+    let dna_manifest_path = zome_file_tree.dna_file_tree.dna_manifest_path.clone();
+    let zome_manifest = zome_file_tree.zome_manifest.clone();
+    let crate_src_path = zome_file_tree.zome_crate_path.join("src");
+
+    let mut file_tree = zome_file_tree.dna_file_tree.file_tree();
+
+    let file_name = match maybe_file_name {
+        Some(f) => {
+            let file_name = format!("{}.rs", f.trim_end_matches(".rs"));
+            let file_path = crate_src_path.join(&file_name);
+            // Create the file as a new module of the zome if it doesn't exist yet
+            if !file_exists(&file_tree, &file_path) {
+                insert_file(&mut file_tree, &file_path, "use hdk::prelude::*;")?;
+                let module_name = f.trim_end_matches(".rs").to_case(Case::Snake);
+                map_file(&mut file_tree, &crate_src_path.join("lib.rs"), |contents| {
+                    Ok(format!(
+                        r#"pub mod {module_name};
+{contents}"#
+                    ))
+                })?;
+            }
+            file_name
+        }
+        None => choose_rust_file(&file_tree, &crate_src_path)?,
+    };
+
+    let items: syn::File = syn::parse2(function_items(
+        function_name,
+        input.as_ref(),
+        &parse_type(output_type)?,
+    )?)?;
+
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+    map_rust_files(
+        file_tree
+            .path_mut(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        |file_path, mut file| {
+            if *file_path == *file_name {
+                file.items.extend(items.items.clone());
+            }
+            Ok(file)
+        },
+    )
+    .map_err(|e| match e {
+        ScaffoldError::MalformedFile(path, error) => {
+            ScaffoldError::MalformedFile(crate_src_path.join(path), error)
+        }
+        _ => e,
+    })?;
+
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    let dna_role_name = dna_file_tree.dna_manifest.name();
+
+    let sweettest_input = match &input {
+        Some(i) => Some(SweettestInput::for_type(
+            &ZomeFileTree::from_zome_manifest(dna_file_tree.clone(), zome_manifest.clone())?,
+            i.rust_type(),
+        )?),
+        None => None,
+    };
+
+    let app_file_tree = AppFileTree::get_or_choose(dna_file_tree.file_tree(), None)?;
+    let app_name = app_file_tree.app_manifest.app_name().to_string();
+
+    let module_name = file_name.trim_end_matches(".rs");
+    let input_struct_names: Vec<&str> = input.iter().filter_map(|i| i.struct_name()).collect();
+
+    let input_ts_type = match &input {
+        Some(i) => Some(ts_type(&parse_type(i.rust_type())?, &input_struct_names)),
+        None => None,
+    };
+
+    scaffold_function_templates(
+        app_file_tree.file_tree(),
+        template_file_tree,
+        &app_name,
+        &dna_role_name,
+        &zome_manifest,
+        function_name,
+        (module_name != "lib").then_some(module_name),
+        input.as_ref().map(|i| i.rust_type()),
+        input_ts_type.as_deref(),
+        input.as_ref().and_then(input_ts_definition).as_deref(),
+        output_type,
+        &ts_type(&parse_type(output_type)?, &input_struct_names),
+        sweettest_input.as_ref(),
+        no_ui,
+        no_spec,
+    )
+}
+
+fn parse_type(t: &str) -> ScaffoldResult<syn::Type> {
+    syn::parse_str(t)
+        .map_err(|_| ScaffoldError::InvalidArguments(format!("{t} is not a valid Rust type")))
+}
+
+fn choose_rust_file(file_tree: &FileTree, crate_src_path: &Path) -> ScaffoldResult<String> {
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+    let mut file_names: Vec<String> = file_tree
+        .path(&mut v.iter())
+        .and_then(|src| src.dir_content())
+        .ok_or(ScaffoldError::PathNotFound(crate_src_path.to_path_buf()))?
+        .iter()
+        .filter(|(_, content)| content.file_content().is_some())
+        .filter_map(|(name, _)| name.to_str().map(|s| s.to_string()))
+        .filter(|name| name.ends_with(".rs"))
+        .collect();
+    file_names.sort();
+
+    let default = file_names.iter().position(|f| f == "lib.rs").unwrap_or(0);
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Which file should the function be scaffolded in?")
+        .default(default)
+        .items(&file_names[..])
+        .interact()?;
+
+    Ok(file_names[selection].clone())
+}
+
+fn function_items(
+    function_name: &str,
+    input: Option<&FunctionInput>,
+    output_type: &syn::Type,
+) -> ScaffoldResult<TokenStream> {
+    let function_ident = format_ident!("{function_name}");
+    let not_implemented = format!("{function_name} is not implemented yet");
+
+    let input_struct = match input {
+        Some(FunctionInput::Struct { name, fields }) => {
+            let name = format_ident!("{name}");
+            let type_definitions: Vec<TokenStream> = fields
+                .iter()
+                .filter_map(|f| f.field_type.rust_type_definition())
+                .collect();
+            let fields: Vec<TokenStream> = fields
+                .iter()
+                .map(|f| {
+                    let field_name = format_ident!("{}", f.field_name);
+                    let rust_type = f.rust_type();
+                    quote! { pub #field_name: #rust_type }
+                })
+                .collect();
+            quote! {
+                #(#type_definitions)*
+
+                #[derive(Serialize, Deserialize, Debug)]
+                pub struct #name {
+                    #(#fields),*
+                }
+            }
+        }
+        _ => quote! {},
+    };
+
+    let params = match input {
+        Some(input) => {
+            let input_type = parse_type(input.rust_type())?;
+            quote! { _input: #input_type }
+        }
+        None => quote! {},
+    };
+
+    Ok(quote! {
+        #input_struct
+
+        #[hdk_extern]
+        pub fn #function_ident(#params) -> ExternResult<#output_type> {
+            Err(wasm_error!(WasmErrorInner::Guest(#not_implemented.to_string())))
+        }
+    })
+}
+
+fn input_ts_definition(input: &FunctionInput) -> Option<String> {
+    match input {
+        FunctionInput::Type(_) => None,
+        FunctionInput::Struct { name, fields } => Some(
+            EntryDefinition {
+                name: name.clone(),
+                fields: fields.clone(),
+                reference_entry_hash: false,
+            }
+            .ts_type_codegen(),
+        ),
+    }
+}
+
+/// Best effort translation of a Rust type to the TypeScript type of its msgpack encoding
+/// in `@holochain/client`; types that can't be translated become `any`
+pub fn ts_type(rust_type: &syn::Type, known_types: &[&str]) -> String {
+    match rust_type {
+        syn::Type::Tuple(t) if t.elems.is_empty() => "void".to_string(),
+        syn::Type::Reference(r) => ts_type(&r.elem, known_types),
+        syn::Type::Path(p) => {
+            let Some(segment) = p.path.segments.last() else {
+                return "any".to_string();
+            };
+            let ident = segment.ident.to_string();
+            let generic = match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) => {
+                    args.args.iter().find_map(|a| match a {
+                        syn::GenericArgument::Type(t) => Some(ts_type(t, known_types)),
+                        _ => None,
+                    })
+                }
+                _ => None,
+            };
+
+            match (ident.as_str(), generic) {
+                ("String", _) => "string".to_string(),
+                ("bool", _) => "boolean".to_string(),
+                (
+                    "u8" | "u16" | "u32" | "u64" | "usize" | "i8" | "i16" | "i32" | "i64" | "isize"
+                    | "f32" | "f64" | "Timestamp",
+                    _,
+                ) => "number".to_string(),
+                ("Vec", Some(_)) if is_vec_u8(segment) => "Uint8Array".to_string(),
+                ("Vec", Some(t)) => format!("Array<{t}>"),
+                ("Option", Some(t)) => format!("{t} | undefined"),
+                (
+                    "ActionHash" | "EntryHash" | "AgentPubKey" | "DnaHash" | "ExternalHash"
+                    | "AnyDhtHash" | "AnyLinkableHash" | "Record" | "Link",
+                    _,
+                ) => ident,
+                (i, _) if known_types.contains(&i) => ident,
+                _ => "any".to_string(),
+            }
+        }
+        _ => "any".to_string(),
+    }
+}
+
+fn is_vec_u8(segment: &syn::PathSegment) -> bool {
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().any(|a| {
+            matches!(a, syn::GenericArgument::Type(syn::Type::Path(p)) if p.path.is_ident("u8"))
+        }),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::utils::unparse_pretty;
+
+    fn unparse_function_items(
+        function_name: &str,
+        input: Option<&FunctionInput>,
+        output_type: &syn::Type,
+    ) -> String {
+        unparse_pretty(
+            &syn::parse2(function_items(function_name, input, output_type).unwrap()).unwrap(),
+        )
+    }
+
+    #[test]
+    fn function_with_an_input_struct() {
+        let input = FunctionInput::from_args(
+            "publish_post",
+            None,
+            Some(&vec![
+                FieldDefinition::from_str("title:String").unwrap(),
+                FieldDefinition::from_str("tags:Vec<String>").unwrap(),
+            ]),
+        )
+        .unwrap();
+
+        let generated = unparse_function_items(
+            "publish_post",
+            input.as_ref(),
+            &parse_type("Record").unwrap(),
+        );
+
+        assert!(generated.contains("pub struct PublishPostInput {"));
+        assert!(generated.contains("pub tags: Vec<String>,"));
+        assert!(generated
+            .contains("pub fn publish_post(_input: PublishPostInput) -> ExternResult<Record> {"));
+    }
+
+    #[test]
+    fn function_without_input() {
+        let generated = unparse_function_items("ping", None, &parse_type("()").unwrap());
+        assert!(generated.contains("pub fn ping() -> ExternResult<()> {"));
+    }
+
+    #[test]
+    fn rust_types_to_ts_types() {
+        let ts = |t: &str| ts_type(&parse_type(t).unwrap(), &["PublishPostInput"]);
+
+        assert_eq!(ts("()"), "void");
+        assert_eq!(
+            ts("Vec<Option<ActionHash>>"),
+            "Array<ActionHash | undefined>"
+        );
+        assert_eq!(ts("Vec<u8>"), "Uint8Array");
+        assert_eq!(ts("u32"), "number");
+        assert_eq!(ts("PublishPostInput"), "PublishPostInput");
+        assert_eq!(ts("MyOwnType"), "any");
+    }
+}
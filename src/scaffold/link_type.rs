@@ -7,11 +7,16 @@ use crate::{
     error::{ScaffoldError, ScaffoldResult},
     file_tree::{insert_file, map_file, FileTree},
     templates::{link_type::scaffold_link_type_templates, ScaffoldedTemplate},
-    utils::input_with_case,
+    utils::{check_case, input_with_case},
 };
 
 use self::{
-    coordinator::add_link_type_functions_to_coordinator, integrity::add_link_type_to_integrity_zome,
+    coordinator::add_link_type_functions_to_coordinator,
+    integrity::add_link_type_to_integrity_zome,
+    on_delete::{
+        add_on_delete_to_coordinator, add_restrict_validation_to_integrity_zome, deletable_base,
+        DependentLinks, OnDelete,
+    },
 };
 
 use super::{
@@ -27,6 +32,7 @@ use super::{
 
 pub mod coordinator;
 pub mod integrity;
+pub mod on_delete;
 
 #[allow(clippy::too_many_arguments)]
 pub fn scaffold_link_type(
@@ -34,8 +40,12 @@ pub fn scaffold_link_type(
     template_file_tree: &FileTree,
     from_referenceable: Option<&Referenceable>,
     to_referenceable: Option<&Referenceable>,
+    name: Option<&str>,
     delete: Option<bool>,
     bidirectional: Option<bool>,
+    query_functions: Option<bool>,
+    on_delete: Option<OnDelete>,
+    delete_dependent_entries: bool,
     no_ui: bool,
     no_spec: bool,
 ) -> ScaffoldResult<ScaffoldedTemplate> {
@@ -50,16 +60,25 @@ pub fn scaffold_link_type(
         &all_entry_types,
     )?;
 
-    let to_referenceable = get_or_choose_optional_reference_type(
-        "Link to which entry type?",
-        &zome_file_tree,
-        to_referenceable,
-        &all_entry_types,
-    )?;
+    // A name without a target scaffolds a link type without a target, as naming it is only
+    // needed in that case
+    let to_referenceable = match (to_referenceable, name) {
+        (None, Some(_)) => None,
+        (to_referenceable, _) => get_or_choose_optional_reference_type(
+            "Link to which entry type?",
+            &zome_file_tree,
+            to_referenceable,
+            &all_entry_types,
+        )?,
+    };
 
-    let link_type = match &to_referenceable {
-        Some(to_referenceable) => link_type_name(&from_referenceable, to_referenceable),
-        None => input_with_case("Enter link type name:", None, Case::Pascal)?,
+    let link_type = match (&to_referenceable, name) {
+        (Some(to_referenceable), _) => link_type_name(&from_referenceable, to_referenceable),
+        (None, Some(name)) => {
+            check_case(name, "link type name", Case::Pascal)?;
+            name.to_string()
+        }
+        (None, None) => input_with_case("Enter link type name:", None, Case::Pascal)?,
     };
 
     let bidirectional = match (&to_referenceable, bidirectional) {
@@ -70,6 +89,14 @@ pub fn scaffold_link_type(
             .interact()?,
     };
 
+    let query_functions = match (&to_referenceable, query_functions) {
+        (None, _) => false,
+        (_, Some(q)) => q,
+        _ => Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Should functions to count the links and check whether two entries are linked be scaffolded?")
+            .interact()?,
+    };
+
     let delete = match delete {
         Some(d) => d,
         None => Confirm::with_theme(&ColorfulTheme::default())
@@ -77,6 +104,22 @@ pub fn scaffold_link_type(
             .interact()?,
     };
 
+    let on_delete = on_delete.unwrap_or_default();
+    if on_delete == OnDelete::Cascade && !delete {
+        return Err(ScaffoldError::InvalidArguments(
+            "the cascade on-delete policy deletes the links, so they must be deletable".to_string(),
+        ));
+    }
+    let deletable_base_entry_type = match (deletable_base(&from_referenceable, on_delete)?, &to_referenceable) {
+        (Some(_), None) => {
+            return Err(ScaffoldError::InvalidArguments(format!(
+                "the \"{on_delete}\" on-delete policy needs the target of the links to be an entry type or an agent role"
+            )))
+        }
+        (Some(base_entry_type), Some(to)) => Some((base_entry_type, to.clone())),
+        (None, _) => None,
+    };
+
     // 1. Create an LINK_TYPE_NAME.rs in "src/", with the link type validation
     let crate_src_path = zome_file_tree.zome_crate_path.join("src");
 
@@ -137,6 +180,13 @@ pub use {}::*;
         None
     };
 
+    if let (Some((base_entry_type, _)), OnDelete::Restrict) =
+        (&deletable_base_entry_type, on_delete)
+    {
+        zome_file_tree =
+            add_restrict_validation_to_integrity_zome(zome_file_tree, base_entry_type, &link_type)?;
+    }
+
     let integrity_zome_name = zome_file_tree.zome_manifest.name.0.to_string();
 
     let coordinator_zomes_for_integrity = get_coordinator_zomes_for_integrity(
@@ -180,8 +230,28 @@ pub use {}::*;
         &to_referenceable,
         delete,
         bidirectional,
+        query_functions,
     )?;
 
+    let zome_file_tree = match &deletable_base_entry_type {
+        Some((base_entry_type, to)) => {
+            let dna_file_tree = add_on_delete_to_coordinator(
+                zome_file_tree.dna_file_tree,
+                &coordinator_zomes_for_integrity,
+                base_entry_type,
+                &DependentLinks {
+                    link_type_name: &link_type,
+                    target_referenceable: to,
+                    inverse_link_type_name: inverse_link_type.as_deref(),
+                    on_delete,
+                    delete_dependent_entries,
+                },
+            )?;
+            ZomeFileTree::from_zome_manifest(dna_file_tree, coordinator_zome.clone())?
+        }
+        None => zome_file_tree,
+    };
+
     let app_file_tree = AppFileTree::get_or_choose(zome_file_tree.dna_file_tree.file_tree(), None)?;
 
     let app_name = app_file_tree.app_manifest.app_name().to_string();
@@ -197,6 +267,7 @@ pub use {}::*;
         &to_referenceable,
         delete,
         inverse_link_type.as_deref(),
+        query_functions,
         no_ui,
         no_spec,
     )
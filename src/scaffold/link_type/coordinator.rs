@@ -15,6 +15,7 @@ use crate::{
 
 use super::link_type_name;
 
+#[allow(clippy::too_many_arguments)]
 pub fn add_link_type_functions_to_coordinator(
     coordinator_zome_file_tree: ZomeFileTree,
     integrity_zome_name: &str,
@@ -23,6 +24,7 @@ pub fn add_link_type_functions_to_coordinator(
     to_referenceable: &Option<Referenceable>,
     delete: bool,
     bidirectional: bool,
+    query_functions: bool,
 ) -> ScaffoldResult<ZomeFileTree> {
     let dna_manifest_path = coordinator_zome_file_tree
         .dna_file_tree
@@ -47,6 +49,7 @@ pub fn add_link_type_functions_to_coordinator(
             r,
             delete,
             bidirectional,
+            query_functions,
         ),
         None => metadata_handlers(integrity_zome_name, link_type_name, from_referenceable),
     };
@@ -73,6 +76,7 @@ fn normal_handlers(
     to_referenceable: &Referenceable,
     delete: bool,
     bidirectional: bool,
+    query_functions: bool,
 ) -> TokenStream {
     let inverse_get_handler = if bidirectional {
         get_links_handler(to_referenceable, from_referenceable, delete)
@@ -80,6 +84,12 @@ fn normal_handlers(
         Default::default()
     };
 
+    let link_query_handlers = if query_functions {
+        link_query_handlers(from_referenceable, to_referenceable, bidirectional)
+    } else {
+        Default::default()
+    };
+
     let delete_link_handler = if delete {
         remove_link_handlers(from_referenceable, to_referenceable, bidirectional)
     } else {
@@ -100,6 +110,8 @@ fn normal_handlers(
 
         #inverse_get_handler
 
+        #link_query_handlers
+
         #delete_link_handler
     }
 }
@@ -483,6 +495,112 @@ fn get_links_to_any_linkable_hash_handler(
     }
 }
 
+fn link_query_handlers(
+    from_referenceable: &Referenceable,
+    to_referenceable: &Referenceable,
+    bidirectional: bool,
+) -> TokenStream {
+    let inverse_count_links_handler = if bidirectional {
+        count_links_handler(to_referenceable, from_referenceable)
+    } else {
+        Default::default()
+    };
+    let count_links_handler = count_links_handler(from_referenceable, to_referenceable);
+    let is_linked_handler = is_linked_handler(from_referenceable, to_referenceable);
+
+    quote! {
+        #count_links_handler
+
+        #inverse_count_links_handler
+
+        #is_linked_handler
+    }
+}
+
+pub fn count_links_handler(
+    from_referenceable: &Referenceable,
+    to_referenceable: &Referenceable,
+) -> TokenStream {
+    let from_field_type = format_ident!("{}", from_referenceable.field_type().to_string());
+    let from_arg_name = format_ident!("{}", from_referenceable.field_name(&Cardinality::Single));
+
+    let pascal_link_type_name =
+        format_ident!("{}", link_type_name(from_referenceable, to_referenceable));
+    let singular_snake_from_entry_type = from_referenceable
+        .to_string(&Cardinality::Single)
+        .to_case(Case::Snake);
+    let plural_snake_to_entry_type = to_referenceable
+        .to_string(&Cardinality::Vector)
+        .to_case(Case::Snake);
+
+    let count_links_function_name =
+        format_ident!("count_{plural_snake_to_entry_type}_for_{singular_snake_from_entry_type}");
+
+    quote! {
+        #[hdk_extern]
+        pub fn #count_links_function_name(#from_arg_name: #from_field_type) -> ExternResult<usize> {
+            count_links(LinkQuery::try_new(#from_arg_name, LinkTypes::#pascal_link_type_name)?)
+        }
+    }
+}
+
+/// Only the links are fetched: the target of each link is compared against the given target
+/// hash, so no records need to be retrieved to know whether the two are linked
+pub fn is_linked_handler(
+    from_referenceable: &Referenceable,
+    to_referenceable: &Referenceable,
+) -> TokenStream {
+    let from_field_type = format_ident!("{}", from_referenceable.field_type().to_string());
+    let to_field_type = format_ident!("{}", to_referenceable.field_type().to_string());
+    let base_field_name = format_ident!(
+        "base_{}",
+        from_referenceable.field_name(&Cardinality::Single)
+    );
+    let target_field_name = format_ident!(
+        "target_{}",
+        to_referenceable.field_name(&Cardinality::Single)
+    );
+    let singular_pascal_from_entry_type = from_referenceable
+        .to_string(&Cardinality::Single)
+        .to_case(Case::Pascal);
+    let singular_pascal_to_entry_type = to_referenceable
+        .to_string(&Cardinality::Single)
+        .to_case(Case::Pascal);
+    let singular_snake_from_entry_type = from_referenceable
+        .to_string(&Cardinality::Single)
+        .to_case(Case::Snake);
+    let singular_snake_to_entry_type = to_referenceable
+        .to_string(&Cardinality::Single)
+        .to_case(Case::Snake);
+
+    let is_linked_input_struct_name = format_ident!(
+        "Is{singular_pascal_from_entry_type}LinkedTo{singular_pascal_to_entry_type}Input"
+    );
+    let is_linked_function_name = format_ident!(
+        "is_{singular_snake_from_entry_type}_linked_to_{singular_snake_to_entry_type}"
+    );
+    let pascal_link_type_name =
+        format_ident!("{}", link_type_name(from_referenceable, to_referenceable));
+
+    quote! {
+        #[derive(Serialize, Deserialize, Debug)]
+        pub struct #is_linked_input_struct_name {
+            pub #base_field_name: #from_field_type,
+            pub #target_field_name: #to_field_type,
+        }
+
+        #[hdk_extern]
+        pub fn #is_linked_function_name(input: #is_linked_input_struct_name) -> ExternResult<bool> {
+            let links = get_links(
+                LinkQuery::try_new(input.#base_field_name, LinkTypes::#pascal_link_type_name)?,
+                GetStrategy::default(),
+            )?;
+            let target: AnyLinkableHash = input.#target_field_name.into();
+            Ok(links.into_iter().any(|link| link.target == target))
+        }
+    }
+}
+
 fn remove_link_handlers(
     from_referenceable: &Referenceable,
     to_referenceable: &Referenceable,
@@ -584,4 +702,28 @@ mod tests {
         assert!(generated.contains("if link.target == input.target_to.clone().into()"));
         assert!(generated.contains("if link.target == input.base_from.clone().into()"));
     }
+
+    #[test]
+    fn link_query_handlers_count_links_and_filter_by_target() {
+        let from_referenceable = Referenceable::EntryType(EntryTypeReference {
+            entry_type: "post".to_string(),
+            reference_entry_hash: false,
+        });
+        let to_referenceable = Referenceable::Agent {
+            role: "reader".to_string(),
+        };
+
+        let handlers = link_query_handlers(&from_referenceable, &to_referenceable, true);
+        let generated = unparse_pretty(&syn::parse_quote! { #handlers });
+
+        assert!(generated.contains("pub fn count_readers_for_post(post_hash: ActionHash)"));
+        assert!(generated
+            .contains("count_links(LinkQuery::try_new(post_hash, LinkTypes::PostToReaders)?)"));
+        assert!(generated.contains("pub fn count_posts_for_reader(reader: AgentPubKey)"));
+        assert!(
+            generated.contains("pub fn is_post_linked_to_reader(input: IsPostLinkedToReaderInput)")
+        );
+        assert!(generated.contains("links.into_iter().any(|link| link.target == target)"));
+        assert!(!generated.contains("get_details("));
+    }
 }
@@ -0,0 +1,619 @@
+use std::{ffi::OsString, str::FromStr};
+
+use colored::Colorize;
+use convert_case::{Case, Casing};
+use holochain_types::prelude::ZomeManifest;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use serde::Serialize;
+use syn::parse_quote;
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::map_rust_files,
+    scaffold::{
+        dna::DnaFileTree,
+        entry_type::definitions::{Cardinality, EntryTypeReference, Referenceable},
+        zome::{
+            coordinator::{find_extern_function_in_zomes, find_extern_function_module_path},
+            ZomeFileTree,
+        },
+    },
+};
+
+/// What happens to the links (and the entries they point to) when the base entry of a link
+/// type is deleted
+#[derive(Debug, Default, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OnDelete {
+    /// The base's delete function also deletes the links, and optionally their targets
+    Cascade,
+    /// The base can't be deleted while there are still live links from it
+    Restrict,
+    /// The links are left behind
+    #[default]
+    Orphan,
+}
+
+impl FromStr for OnDelete {
+    type Err = ScaffoldError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cascade" => Ok(OnDelete::Cascade),
+            "restrict" => Ok(OnDelete::Restrict),
+            "orphan" => Ok(OnDelete::Orphan),
+            _ => Err(ScaffoldError::InvalidArguments(format!(
+                "Invalid on-delete policy \"{s}\": only \"cascade\", \"restrict\" and \"orphan\" are allowed"
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for OnDelete {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            OnDelete::Cascade => "cascade",
+            OnDelete::Restrict => "restrict",
+            OnDelete::Orphan => "orphan",
+        };
+        write!(f, "{str}")
+    }
+}
+
+/// On-delete policy for a `linked_from` field of an entry type
+/// Syntax: <FIELD_NAME>:<cascade|restrict|orphan>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldOnDelete {
+    pub field_name: String,
+    pub on_delete: OnDelete,
+}
+
+impl FromStr for FieldOnDelete {
+    type Err = ScaffoldError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((field_name, on_delete)) = s.split_once(':') else {
+            return Err(ScaffoldError::InvalidArguments(format!(
+                "Invalid on-delete policy \"{s}\", expected <FIELD_NAME>:<cascade|restrict|orphan>"
+            )));
+        };
+
+        Ok(FieldOnDelete {
+            field_name: field_name.to_string(),
+            on_delete: OnDelete::from_str(on_delete)?,
+        })
+    }
+}
+
+/// Returns the entry type whose deletion the policy applies to, if there is anything to scaffold
+pub fn deletable_base(
+    base_referenceable: &Referenceable,
+    on_delete: OnDelete,
+) -> ScaffoldResult<Option<EntryTypeReference>> {
+    if on_delete == OnDelete::Orphan {
+        return Ok(None);
+    }
+
+    match base_referenceable {
+        Referenceable::EntryType(entry_type) if !entry_type.reference_entry_hash => {
+            Ok(Some(entry_type.clone()))
+        }
+        _ => Err(ScaffoldError::InvalidArguments(format!(
+            "the \"{on_delete}\" on-delete policy needs the base of the links to be an entry type referenced by its ActionHash, but it is {}",
+            base_referenceable.to_string(&Cardinality::Single)
+        ))),
+    }
+}
+
+/// Rejects the deletion of the base entry while it still has live links of the given type
+///
+/// Validation can only deterministically see the source chain of the deleting agent, so this
+/// only covers the links it created itself: the coordinator zome checks the others before
+/// the delete is committed
+pub fn add_restrict_validation_to_integrity_zome(
+    zome_file_tree: ZomeFileTree,
+    base_entry_type: &EntryTypeReference,
+    link_type_name: &str,
+) -> ScaffoldResult<ZomeFileTree> {
+    let dna_manifest_path = zome_file_tree.dna_file_tree.dna_manifest_path.clone();
+    let zome_manifest = zome_file_tree.zome_manifest.clone();
+    let crate_src_path = zome_file_tree.zome_crate_path.join("src");
+
+    let validate_delete_fn = format!(
+        "validate_delete_{}",
+        base_entry_type.entry_type.to_case(Case::Snake)
+    );
+    let restrict_stmt = restrict_validation_stmt(base_entry_type, link_type_name);
+
+    let mut file_tree = zome_file_tree.dna_file_tree.file_tree();
+
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+    map_rust_files(
+        file_tree
+            .path_mut(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        |_, mut file| {
+            for item in &mut file.items {
+                if let syn::Item::Fn(item_fn) = item {
+                    if item_fn.sig.ident != validate_delete_fn {
+                        continue;
+                    }
+                    // The delete action is now read by the restrict check
+                    if let Some(syn::FnArg::Typed(pat_type)) = item_fn.sig.inputs.first_mut() {
+                        if let syn::Pat::Ident(pat_ident) = &mut *pat_type.pat {
+                            if pat_ident.ident == "_action" {
+                                pat_ident.ident = format_ident!("action");
+                            }
+                        }
+                    }
+                    item_fn.block.stmts.insert(0, restrict_stmt.clone());
+                }
+            }
+            Ok(file)
+        },
+    )
+    .map_err(|e| match e {
+        ScaffoldError::MalformedFile(path, error) => {
+            ScaffoldError::MalformedFile(crate_src_path.join(path), error)
+        }
+        _ => e,
+    })?;
+
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    ZomeFileTree::from_zome_manifest(dna_file_tree, zome_manifest)
+}
+
+fn restrict_validation_stmt(
+    base_entry_type: &EntryTypeReference,
+    link_type_name: &str,
+) -> syn::Stmt {
+    let link_type = format_ident!("{}", link_type_name.to_case(Case::Pascal));
+    let invalid_reason = format!(
+        "{} still has live {} links and can't be deleted",
+        base_entry_type.entry_type.to_case(Case::Pascal),
+        link_type_name.to_case(Case::Pascal)
+    );
+
+    parse_quote! {
+        if let Some(chain_top) = action.prev_action() {
+            let link_type: ScopedLinkType = crate::LinkTypes::#link_type.try_into()?;
+            let base_address: AnyLinkableHash = action.data.deletes_address.clone().into();
+            let activity = must_get_agent_activity(
+                action.author().clone(),
+                ChainFilter::new(chain_top.clone()),
+            )?;
+            let mut created_links = Vec::new();
+            let mut deleted_links = Vec::new();
+            for activity in activity {
+                match activity.action.hashed.content.data {
+                    ActionData::CreateLink(create_link)
+                        if create_link.base_address == base_address
+                            && create_link.zome_index == link_type.zome_index
+                            && create_link.link_type == link_type.zome_type =>
+                    {
+                        created_links.push(activity.action.hashed.hash);
+                    }
+                    ActionData::DeleteLink(delete_link) => {
+                        deleted_links.push(delete_link.link_add_address);
+                    }
+                    _ => {}
+                }
+            }
+            if created_links
+                .iter()
+                .any(|create_link_hash| !deleted_links.contains(create_link_hash))
+            {
+                return Ok(ValidateCallbackResult::Invalid(#invalid_reason.to_string()));
+            }
+        }
+    }
+}
+
+/// Links from a deleted base entry, and how their deletion is propagated
+pub struct DependentLinks<'a> {
+    pub link_type_name: &'a str,
+    pub target_referenceable: &'a Referenceable,
+    /// Link type from the targets back to the base, for bidirectional link types
+    pub inverse_link_type_name: Option<&'a str>,
+    pub on_delete: OnDelete,
+    /// Whether cascading also deletes the target entries
+    pub delete_dependent_entries: bool,
+}
+
+/// Patches the `delete_*` function of the base entry type according to the on-delete policy
+pub fn add_on_delete_to_coordinator(
+    dna_file_tree: DnaFileTree,
+    coordinator_zomes_for_integrity: &Vec<ZomeManifest>,
+    base_entry_type: &EntryTypeReference,
+    dependent_links: &DependentLinks,
+) -> ScaffoldResult<DnaFileTree> {
+    let dna_manifest_path = dna_file_tree.dna_manifest_path.clone();
+    let snake_base_entry_type = base_entry_type.entry_type.to_case(Case::Snake);
+
+    let Some((chosen_coordinator_zome, fn_name)) = find_extern_function_in_zomes(
+        &dna_file_tree,
+        coordinator_zomes_for_integrity,
+        &format!("delete_{snake_base_entry_type}"),
+    )?
+    else {
+        let warning_text = format!(
+            "WARNING: no delete_{snake_base_entry_type} function was found, the \"{}\" on-delete policy for {} was not scaffolded in the coordinator zome",
+            dependent_links.on_delete, dependent_links.link_type_name
+        )
+        .yellow();
+        println!("{warning_text}");
+        return Ok(dna_file_tree);
+    };
+
+    // Dependent entries are deleted through their own delete function, so that their links and
+    // on-delete policies are handled too
+    let dependent_delete = match dependent_links.target_referenceable {
+        Referenceable::EntryType(target) if dependent_links.delete_dependent_entries => {
+            let delete_fn_name = format!("delete_{}", target.entry_type.to_case(Case::Snake));
+            match find_extern_function_in_zomes(
+                &dna_file_tree,
+                coordinator_zomes_for_integrity,
+                &delete_fn_name,
+            )? {
+                Some((zome, _)) if zome.name == chosen_coordinator_zome.name => {
+                    let dependent_zome_file_tree =
+                        ZomeFileTree::from_zome_manifest(dna_file_tree.clone(), zome)?;
+                    find_extern_function_module_path(&dependent_zome_file_tree, &delete_fn_name)?
+                        .map(DependentDelete::Module)
+                }
+                Some((zome, _)) => Some(DependentDelete::Zome(zome.name.0.to_string())),
+                None => None,
+            }
+        }
+        _ => None,
+    };
+
+    let zome_file_tree = ZomeFileTree::from_zome_manifest(dna_file_tree, chosen_coordinator_zome)?;
+
+    let crate_src_path = zome_file_tree.zome_crate_path.join("src");
+
+    let mut file_tree = zome_file_tree.dna_file_tree.file_tree();
+
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+    map_rust_files(
+        file_tree
+            .path_mut(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        |_, mut file| {
+            for item in &mut file.items {
+                if let syn::Item::Fn(item_fn) = item {
+                    if item_fn
+                        .attrs
+                        .iter()
+                        .any(|a| a.path().segments.iter().any(|s| s.ident == "hdk_extern"))
+                        && item_fn.sig.ident == fn_name.sig.ident
+                    {
+                        match dependent_links.on_delete {
+                            OnDelete::Cascade => {
+                                if let Some(delete_stmt) = item_fn.block.stmts.pop() {
+                                    item_fn.block.stmts.extend(cascade_stmts(
+                                        base_entry_type,
+                                        dependent_links,
+                                        dependent_delete.as_ref(),
+                                    ));
+                                    item_fn.block.stmts.push(delete_stmt);
+                                }
+                            }
+                            OnDelete::Restrict => {
+                                let restrict_stmts =
+                                    restrict_stmts(base_entry_type, dependent_links);
+                                item_fn.block.stmts.splice(0..0, restrict_stmts);
+                            }
+                            OnDelete::Orphan => {}
+                        }
+                    }
+                }
+            }
+            Ok(file)
+        },
+    )
+    .map_err(|e| match e {
+        ScaffoldError::MalformedFile(path, error) => {
+            ScaffoldError::MalformedFile(crate_src_path.join(path), error)
+        }
+        _ => e,
+    })?;
+
+    DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)
+}
+
+fn dependent_links_ident(dependent_links: &DependentLinks) -> syn::Ident {
+    format_ident!(
+        "{}_links",
+        dependent_links
+            .target_referenceable
+            .to_string(&Cardinality::Vector)
+            .to_case(Case::Snake)
+    )
+}
+
+/// Where the delete function of the dependent entries is
+enum DependentDelete {
+    /// In the given module of the coordinator zome being patched
+    Module(String),
+    /// In the given other coordinator zome of the DNA
+    Zome(String),
+}
+
+fn cascade_stmts(
+    base_entry_type: &EntryTypeReference,
+    dependent_links: &DependentLinks,
+    dependent_delete: Option<&DependentDelete>,
+) -> Vec<syn::Stmt> {
+    let original_base_hash = format_ident!(
+        "original_{}_hash",
+        base_entry_type.entry_type.to_case(Case::Snake)
+    );
+    let link_type = format_ident!("{}", dependent_links.link_type_name.to_case(Case::Pascal));
+    let links = dependent_links_ident(dependent_links);
+
+    let get_links_stmt: syn::Stmt = parse_quote! {
+        let #links = get_links(
+            LinkQuery::try_new(#original_base_hash.clone(), LinkTypes::#link_type)?,
+            GetStrategy::default(),
+        )?;
+    };
+
+    let dependent_target = match dependent_links.target_referenceable {
+        Referenceable::EntryType(target)
+            if dependent_links.delete_dependent_entries && !target.reference_entry_hash =>
+        {
+            Some(target)
+        }
+        _ => None,
+    };
+
+    // The delete function of the dependent entries deletes their links from the base itself
+    if let (Some(target), Some(dependent_delete)) = (dependent_target, dependent_delete) {
+        let snake_target = target.entry_type.to_case(Case::Snake);
+        let target_hash = format_ident!("{snake_target}_hash");
+        let delete_fn_name = format!("delete_{snake_target}");
+        let delete_target: TokenStream = match dependent_delete {
+            DependentDelete::Module(module_path) => {
+                let delete_fn_path: syn::Path =
+                    syn::parse_str(&format!("{module_path}::{delete_fn_name}"))
+                        .expect("module path and function name are valid identifiers");
+                quote! { #delete_fn_path(#target_hash)?; }
+            }
+            DependentDelete::Zome(zome_name) => {
+                let failed_call_message =
+                    format!("Call to {delete_fn_name} failed: {{response:?}}");
+                quote! {
+                    let response = call(
+                        CallTargetCell::Local,
+                        ZomeName::from(#zome_name),
+                        FunctionName::from(#delete_fn_name),
+                        None,
+                        #target_hash,
+                    )?;
+                    if !matches!(response, ZomeCallResponse::Ok(_)) {
+                        return Err(wasm_error!(WasmErrorInner::Guest(format!(#failed_call_message))));
+                    }
+                }
+            }
+        };
+
+        return vec![
+            get_links_stmt,
+            parse_quote! {
+                for link in #links {
+                    if let Some(#target_hash) = link.target.into_action_hash() {
+                        #delete_target
+                    }
+                }
+            },
+        ];
+    }
+
+    let delete_inverse_links: TokenStream = match dependent_links.inverse_link_type_name {
+        Some(inverse_link_type_name) => {
+            let inverse_link_type =
+                format_ident!("{}", inverse_link_type_name.to_case(Case::Pascal));
+            quote! {
+                let inverse_links = get_links(
+                    LinkQuery::try_new(link.target.clone(), LinkTypes::#inverse_link_type)?,
+                    GetStrategy::default(),
+                )?;
+                for inverse_link in inverse_links {
+                    if inverse_link.target == #original_base_hash.clone().into() {
+                        delete_link(inverse_link.create_link_hash, GetOptions::default())?;
+                    }
+                }
+            }
+        }
+        None => quote! {},
+    };
+
+    let delete_dependent_entry: TokenStream = match dependent_target {
+        Some(target) => {
+            let target_hash = format_ident!("{}_hash", target.entry_type.to_case(Case::Snake));
+            quote! {
+                if let Some(#target_hash) = link.target.clone().into_action_hash() {
+                    delete_entry(#target_hash)?;
+                }
+            }
+        }
+        None => quote! {},
+    };
+
+    vec![
+        get_links_stmt,
+        parse_quote! {
+            for link in #links {
+                #delete_inverse_links
+                #delete_dependent_entry
+                delete_link(link.create_link_hash, GetOptions::default())?;
+            }
+        },
+    ]
+}
+
+fn restrict_stmts(
+    base_entry_type: &EntryTypeReference,
+    dependent_links: &DependentLinks,
+) -> Vec<syn::Stmt> {
+    let original_base_hash = format_ident!(
+        "original_{}_hash",
+        base_entry_type.entry_type.to_case(Case::Snake)
+    );
+    let link_type = format_ident!("{}", dependent_links.link_type_name.to_case(Case::Pascal));
+    let links = dependent_links_ident(dependent_links);
+    let error_message = format!(
+        "{} still has {} and can't be deleted",
+        base_entry_type.entry_type.to_case(Case::Pascal),
+        dependent_links
+            .target_referenceable
+            .to_string(&Cardinality::Vector)
+            .to_case(Case::Lower)
+    );
+
+    vec![
+        parse_quote! {
+            let #links = get_links(
+                LinkQuery::try_new(#original_base_hash.clone(), LinkTypes::#link_type)?,
+                GetStrategy::default(),
+            )?;
+        },
+        parse_quote! {
+            if !#links.is_empty() {
+                return Err(wasm_error!(WasmErrorInner::Guest(#error_message.to_string())));
+            }
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::unparse_pretty;
+
+    fn post() -> EntryTypeReference {
+        EntryTypeReference {
+            entry_type: "Post".to_string(),
+            reference_entry_hash: false,
+        }
+    }
+
+    fn comment() -> Referenceable {
+        Referenceable::EntryType(EntryTypeReference {
+            entry_type: "Comment".to_string(),
+            reference_entry_hash: false,
+        })
+    }
+
+    #[test]
+    fn parse_field_on_delete() {
+        assert_eq!(
+            FieldOnDelete::from_str("post_hash:cascade").unwrap(),
+            FieldOnDelete {
+                field_name: "post_hash".to_string(),
+                on_delete: OnDelete::Cascade,
+            }
+        );
+        assert!(FieldOnDelete::from_str("post_hash").is_err());
+        assert!(FieldOnDelete::from_str("post_hash:delete").is_err());
+    }
+
+    #[test]
+    fn only_entry_types_referenced_by_action_hash_can_restrict_or_cascade() {
+        let agent = Referenceable::Agent {
+            role: "author".to_string(),
+        };
+        assert!(deletable_base(&agent, OnDelete::Orphan).unwrap().is_none());
+        assert!(deletable_base(&agent, OnDelete::Cascade).is_err());
+        assert_eq!(
+            deletable_base(&Referenceable::EntryType(post()), OnDelete::Restrict).unwrap(),
+            Some(post())
+        );
+    }
+
+    #[test]
+    fn cascade_deletes_dependent_links_inverse_links_and_entries() {
+        let comment = comment();
+        let dependent_links = DependentLinks {
+            link_type_name: "PostToComments",
+            target_referenceable: &comment,
+            inverse_link_type_name: Some("CommentToPosts"),
+            on_delete: OnDelete::Cascade,
+            delete_dependent_entries: true,
+        };
+
+        let stmts = cascade_stmts(&post(), &dependent_links, None);
+        let generated = unparse_pretty(&syn::parse_quote! { fn delete_post() { #(#stmts)* } });
+
+        assert!(generated.contains("let comments_links = get_links("));
+        assert!(generated.contains("LinkTypes::PostToComments"));
+        assert!(generated.contains("LinkTypes::CommentToPosts"));
+        assert!(generated.contains("if inverse_link.target == original_post_hash.clone().into()"));
+        assert!(generated.contains("delete_entry(comment_hash)?;"));
+        assert!(generated.contains("delete_link(link.create_link_hash, GetOptions::default())?;"));
+    }
+
+    #[test]
+    fn cascade_delegates_to_the_delete_function_of_dependent_entries() {
+        let comment = comment();
+        let dependent_links = DependentLinks {
+            link_type_name: "PostToComments",
+            target_referenceable: &comment,
+            inverse_link_type_name: Some("CommentToPosts"),
+            on_delete: OnDelete::Cascade,
+            delete_dependent_entries: true,
+        };
+
+        // In the same coordinator zome, the delete function is called directly
+        let stmts = cascade_stmts(
+            &post(),
+            &dependent_links,
+            Some(&DependentDelete::Module("crate::comment".to_string())),
+        );
+        let generated = unparse_pretty(&syn::parse_quote! { fn delete_post() { #(#stmts)* } });
+
+        assert!(generated.contains("let comments_links = get_links("));
+        assert!(generated.contains("crate::comment::delete_comment(comment_hash)?;"));
+        assert!(!generated.contains("delete_entry("));
+        assert!(!generated.contains("delete_link("));
+        assert!(!generated.contains("LinkTypes::CommentToPosts"));
+
+        // In another coordinator zome, it's called through a zome call
+        let stmts = cascade_stmts(
+            &post(),
+            &dependent_links,
+            Some(&DependentDelete::Zome("comments".to_string())),
+        );
+        let generated = unparse_pretty(&syn::parse_quote! { fn delete_post() { #(#stmts)* } });
+
+        assert!(generated.contains(r#"ZomeName::from("comments"),"#));
+        assert!(generated.contains(r#"FunctionName::from("delete_comment"),"#));
+        assert!(!generated.contains("delete_entry("));
+        assert!(!generated.contains("delete_link("));
+    }
+
+    #[test]
+    fn restrict_rejects_deleting_a_base_with_live_links() {
+        let comment = comment();
+        let dependent_links = DependentLinks {
+            link_type_name: "PostToComments",
+            target_referenceable: &comment,
+            inverse_link_type_name: None,
+            on_delete: OnDelete::Restrict,
+            delete_dependent_entries: false,
+        };
+
+        let stmts = restrict_stmts(&post(), &dependent_links);
+        let generated = unparse_pretty(&syn::parse_quote! { fn delete_post() { #(#stmts)* } });
+        assert!(generated.contains("if !comments_links.is_empty()"));
+        assert!(generated.contains("Post still has comments and can't be deleted"));
+
+        let stmt = restrict_validation_stmt(&post(), "PostToComments");
+        let generated = unparse_pretty(&syn::parse_quote! { fn validate_delete_post() { #stmt } });
+        assert!(generated.contains("crate::LinkTypes::PostToComments.try_into()?"));
+        assert!(generated.contains("must_get_agent_activity("));
+        assert!(generated.contains("Post still has live PostToComments links and can't be deleted"));
+    }
+}
@@ -0,0 +1,589 @@
+use std::{ffi::OsString, path::PathBuf, str::FromStr};
+
+use colored::Colorize;
+use convert_case::{Case, Casing};
+use dialoguer::{theme::ColorfulTheme, Select};
+use holochain_types::prelude::AppManifest;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use serde::Serialize;
+use syn::{parse_quote, ItemFn};
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::{
+        file_exists, find_map_rust_files, insert_file, map_file, map_rust_files, FileTree,
+    },
+    templates::{membrane::scaffold_membrane_templates, ScaffoldedTemplate},
+    utils::unparse_pretty,
+};
+
+use super::{
+    app::{find_app_manifests, AppFileTree},
+    dna::{properties::ensure_dna_property, DnaFileTree},
+    entry_type::definitions::FieldDefinition,
+    zome::{
+        coordinator::find_extern_function_in_zome, utils::get_coordinator_zomes_for_integrity,
+        ZomeFileTree,
+    },
+};
+
+/// How the agents joining the network of a DNA prove that they are allowed to
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MembraneKind {
+    /// The membrane proof is one of the invite codes listed in the DNA properties
+    InviteCode,
+    /// The membrane proof is the signature of the joining agent's key by the progenitor
+    SignedByProgenitor,
+    /// Only the agents listed in the DNA properties can join, no membrane proof is needed
+    Allowlist,
+}
+
+impl MembraneKind {
+    pub fn choose() -> ScaffoldResult<MembraneKind> {
+        let options = [
+            MembraneKind::InviteCode,
+            MembraneKind::SignedByProgenitor,
+            MembraneKind::Allowlist,
+        ];
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("How should agents prove that they can join the network?")
+            .default(0)
+            .item("With one of the invite codes set in the DNA properties (invite-code)")
+            .item("With a membrane proof signed by the progenitor (signed-by-progenitor)")
+            .item("By being in the list of agents set in the DNA properties (allowlist)")
+            .interact()?;
+        Ok(options[selection])
+    }
+
+    /// DNA property the membrane proofs are checked against
+    fn dna_property(&self) -> ScaffoldResult<FieldDefinition> {
+        FieldDefinition::from_str(match self {
+            MembraneKind::InviteCode => "invite_codes:Vec<String>",
+            MembraneKind::SignedByProgenitor => "progenitor:AgentPubKey",
+            MembraneKind::Allowlist => "allowed_agents:Vec<AgentPubKey>",
+        })
+    }
+}
+
+impl FromStr for MembraneKind {
+    type Err = ScaffoldError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "invite-code" => Ok(MembraneKind::InviteCode),
+            "signed-by-progenitor" => Ok(MembraneKind::SignedByProgenitor),
+            "allowlist" => Ok(MembraneKind::Allowlist),
+            _ => Err(ScaffoldError::InvalidArguments(format!(
+                "Invalid membrane kind \"{s}\": only \"invite-code\", \"signed-by-progenitor\" and \"allowlist\" are allowed"
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for MembraneKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            MembraneKind::InviteCode => "invite-code",
+            MembraneKind::SignedByProgenitor => "signed-by-progenitor",
+            MembraneKind::Allowlist => "allowlist",
+        };
+        write!(f, "{str}")
+    }
+}
+
+/// Scaffolds the membrane proof type of the given kind in the integrity zome, validates it in
+/// `genesis_self_check` and `validate_agent_joining`, and adds sweettests installing agents with
+/// valid and invalid membrane proofs
+pub fn scaffold_membrane(
+    integrity_zome_file_tree: ZomeFileTree,
+    template_file_tree: &FileTree,
+    maybe_kind: Option<MembraneKind>,
+    no_ui: bool,
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    let integrity_zome_name = integrity_zome_file_tree.zome_manifest.name.0.to_string();
+    let crate_src_path = integrity_zome_file_tree.zome_crate_path.join("src");
+    let membrane_file_path = crate_src_path.join("membrane.rs");
+
+    if file_exists(
+        integrity_zome_file_tree.dna_file_tree.file_tree_ref(),
+        &membrane_file_path,
+    ) {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "a membrane was already scaffolded in zome {integrity_zome_name}"
+        )));
+    }
+    for callback in ["genesis_self_check", "validate_agent_joining"] {
+        if !has_function(&integrity_zome_file_tree, callback)? {
+            return Err(ScaffoldError::InvalidArguments(format!(
+                "function {callback} was not found in zome {integrity_zome_name}"
+            )));
+        }
+    }
+
+    let kind = match maybe_kind {
+        Some(k) => k,
+        None => MembraneKind::choose()?,
+    };
+
+    // 1. The membrane proofs are checked against a DNA property
+    let (integrity_zome_file_tree, other_dna_properties) = ensure_dna_property(
+        integrity_zome_file_tree,
+        template_file_tree,
+        &kind.dna_property()?,
+        &format!("the {kind} membrane"),
+        no_spec,
+    )?;
+
+    let dna_manifest_path = integrity_zome_file_tree
+        .dna_file_tree
+        .dna_manifest_path
+        .clone();
+    let dna_name = integrity_zome_file_tree.dna_file_tree.dna_manifest.name();
+    let integrity_zome_manifest = integrity_zome_file_tree.zome_manifest.clone();
+
+    let coordinator_zomes_for_integrity = get_coordinator_zomes_for_integrity(
+        &integrity_zome_file_tree.dna_file_tree.dna_manifest,
+        &integrity_zome_name,
+    );
+    let coordinator_zome = match coordinator_zomes_for_integrity.len() {
+        0 => None,
+        1 => Some(coordinator_zomes_for_integrity[0].clone()),
+        _ => {
+            let names: Vec<String> = coordinator_zomes_for_integrity
+                .iter()
+                .map(|z| z.name.0.to_string())
+                .collect();
+            let selection = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Which coordinator zome should the membrane tests be scaffolded in?")
+                .default(0)
+                .items(&names[..])
+                .interact()?;
+
+            Some(coordinator_zomes_for_integrity[selection].clone())
+        }
+    };
+
+    // 2. Add the membrane proof type and its validation to the integrity zome
+    let mut file_tree = integrity_zome_file_tree.dna_file_tree.file_tree();
+
+    let membrane_module: syn::File = syn::parse2(membrane_module(kind))?;
+    insert_file(
+        &mut file_tree,
+        &membrane_file_path,
+        &unparse_pretty(&membrane_module),
+    )?;
+    map_file(&mut file_tree, &crate_src_path.join("lib.rs"), |contents| {
+        Ok(format!(
+            r#"pub mod membrane;
+pub use membrane::*;
+{contents}"#
+        ))
+    })?;
+
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+    map_rust_files(
+        file_tree
+            .path_mut(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        |file_path, mut file| {
+            for item in &mut file.items {
+                if let syn::Item::Fn(item_fn) = item {
+                    if item_fn.sig.ident == "genesis_self_check"
+                        || item_fn.sig.ident == "validate_agent_joining"
+                    {
+                        add_membrane_proof_check(item_fn).ok_or(ScaffoldError::MalformedFile(
+                            file_path.clone(),
+                            format!(
+                                "couldn't find the parameters of {} to check the membrane proof with",
+                                item_fn.sig.ident
+                            ),
+                        ))?;
+                    }
+                }
+            }
+            Ok(file)
+        },
+    )
+    .map_err(|e| match e {
+        ScaffoldError::MalformedFile(path, error) => {
+            ScaffoldError::MalformedFile(crate_src_path.join(path), error)
+        }
+        _ => e,
+    })?;
+
+    // 3. The progenitor signs the membrane proofs from the coordinator zome
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    let mut file_tree = match (&coordinator_zome, kind) {
+        (Some(coordinator_zome), MembraneKind::SignedByProgenitor) => {
+            let coordinator_zome_file_tree =
+                ZomeFileTree::from_zome_manifest(dna_file_tree, coordinator_zome.clone())?;
+            add_create_membrane_proof_to_coordinator(
+                coordinator_zome_file_tree,
+                &integrity_zome_name,
+            )?
+            .dna_file_tree
+            .file_tree()
+        }
+        _ => dna_file_tree.file_tree(),
+    };
+
+    // 4. With an invite code form, the apps are installed without membrane proofs, which the UI provides afterwards
+    let membrane_ui_path = PathBuf::from("membrane/ui");
+    let v: Vec<OsString> = membrane_ui_path.iter().map(|s| s.to_os_string()).collect();
+    let invite_code_form = kind == MembraneKind::InviteCode
+        && !no_ui
+        && template_file_tree.path(&mut v.iter()).is_some();
+    if invite_code_form {
+        let dna_bundle_file_name = format!("{dna_name}.dna");
+        for (app_manifest_path, app_manifest) in find_app_manifests(&file_tree)? {
+            let AppManifest::V0(mut m) = app_manifest;
+            let uses_dna = m.roles.iter().any(|role| {
+                role.dna.path.as_ref().is_some_and(|path| {
+                    PathBuf::from(path)
+                        .file_name()
+                        .is_some_and(|f| f.to_string_lossy() == dna_bundle_file_name)
+                })
+            });
+            if uses_dna {
+                m.allow_deferred_memproofs = true;
+                insert_file(
+                    &mut file_tree,
+                    &app_manifest_path,
+                    &serde_yaml::to_string(&AppManifest::V0(m))?,
+                )?;
+            }
+        }
+    }
+
+    println!(
+        "{}",
+        format!(
+            "WARNING: agents now need a valid membrane proof to join the network of the {dna_name} DNA, update the existing sweettests installing it"
+        )
+        .yellow()
+    );
+
+    let app_file_tree = AppFileTree::get_or_choose(file_tree, None)?;
+    let app_name = app_file_tree.app_manifest.app_name().to_string();
+
+    let Some(coordinator_zome) = coordinator_zome else {
+        println!(
+            "{}",
+            format!(
+                "WARNING: no coordinator zome depends on {integrity_zome_name}, skipping the membrane tests and UI"
+            )
+            .yellow()
+        );
+        return Ok(ScaffoldedTemplate {
+            file_tree: app_file_tree.file_tree(),
+            next_instructions: None,
+        });
+    };
+
+    scaffold_membrane_templates(
+        app_file_tree.file_tree(),
+        template_file_tree,
+        &app_name,
+        &dna_name,
+        &integrity_zome_manifest,
+        &coordinator_zome,
+        kind,
+        &other_dna_properties,
+        !invite_code_form,
+        no_spec,
+    )
+}
+
+fn has_function(zome_file_tree: &ZomeFileTree, fn_name: &str) -> ScaffoldResult<bool> {
+    let crate_src_path = zome_file_tree.zome_crate_path.join("src");
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+
+    let found = find_map_rust_files(
+        zome_file_tree
+            .dna_file_tree
+            .file_tree_ref()
+            .path(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        &|_, file| {
+            file.items.iter().find_map(|item| match item {
+                syn::Item::Fn(item_fn) if item_fn.sig.ident == fn_name => Some(()),
+                _ => None,
+            })
+        },
+    );
+
+    Ok(!found.is_empty())
+}
+
+fn membrane_module(kind: MembraneKind) -> TokenStream {
+    match kind {
+        MembraneKind::InviteCode => quote! {
+            use hdi::prelude::*;
+
+            /// Membrane proof of the agents joining the network, with the invite code they were given
+            #[derive(Serialize, Deserialize, SerializedBytes, Debug, Clone, PartialEq)]
+            pub struct InviteCodeMembraneProof {
+                pub invite_code: String,
+            }
+
+            /// Valid if the membrane proof has one of the invite codes listed in the DNA properties
+            pub fn validate_membrane_proof(
+                _agent_pub_key: &AgentPubKey,
+                membrane_proof: &Option<MembraneProof>,
+            ) -> ExternResult<ValidateCallbackResult> {
+                let Some(membrane_proof) = membrane_proof else {
+                    return Ok(ValidateCallbackResult::Invalid(String::from(
+                        "An invite code is needed to join this network",
+                    )));
+                };
+                let Ok(membrane_proof) =
+                    InviteCodeMembraneProof::try_from(membrane_proof.as_ref().clone())
+                else {
+                    return Ok(ValidateCallbackResult::Invalid(String::from(
+                        "Malformed membrane proof",
+                    )));
+                };
+                if !crate::get_dna_properties()?
+                    .invite_codes
+                    .contains(&membrane_proof.invite_code)
+                {
+                    return Ok(ValidateCallbackResult::Invalid(String::from(
+                        "Invalid invite code",
+                    )));
+                }
+                Ok(ValidateCallbackResult::Valid)
+            }
+        },
+        MembraneKind::SignedByProgenitor => quote! {
+            use hdi::prelude::*;
+
+            /// Membrane proof of the agents joining the network, with the signature of their key by the progenitor
+            #[derive(Serialize, Deserialize, SerializedBytes, Debug, Clone, PartialEq)]
+            pub struct ProgenitorSignedMembraneProof {
+                pub signature: Signature,
+            }
+
+            /// Valid if the joining agent is the progenitor, or if the membrane proof has the
+            /// progenitor's signature of the joining agent's key
+            pub fn validate_membrane_proof(
+                agent_pub_key: &AgentPubKey,
+                membrane_proof: &Option<MembraneProof>,
+            ) -> ExternResult<ValidateCallbackResult> {
+                let progenitor = crate::get_dna_properties()?.progenitor;
+                if agent_pub_key == &progenitor {
+                    return Ok(ValidateCallbackResult::Valid);
+                }
+                let Some(membrane_proof) = membrane_proof else {
+                    return Ok(ValidateCallbackResult::Invalid(String::from(
+                        "A membrane proof signed by the progenitor is needed to join this network",
+                    )));
+                };
+                let Ok(membrane_proof) =
+                    ProgenitorSignedMembraneProof::try_from(membrane_proof.as_ref().clone())
+                else {
+                    return Ok(ValidateCallbackResult::Invalid(String::from(
+                        "Malformed membrane proof",
+                    )));
+                };
+                if !verify_signature(progenitor, membrane_proof.signature, agent_pub_key)? {
+                    return Ok(ValidateCallbackResult::Invalid(String::from(
+                        "The membrane proof was not signed by the progenitor for this agent",
+                    )));
+                }
+                Ok(ValidateCallbackResult::Valid)
+            }
+        },
+        MembraneKind::Allowlist => quote! {
+            use hdi::prelude::*;
+
+            /// Valid if the joining agent is listed in the DNA properties, the membrane proof is not used
+            pub fn validate_membrane_proof(
+                agent_pub_key: &AgentPubKey,
+                _membrane_proof: &Option<MembraneProof>,
+            ) -> ExternResult<ValidateCallbackResult> {
+                if !crate::get_dna_properties()?
+                    .allowed_agents
+                    .contains(agent_pub_key)
+                {
+                    return Ok(ValidateCallbackResult::Invalid(String::from(
+                        "Only the agents in the allowlist can join this network",
+                    )));
+                }
+                Ok(ValidateCallbackResult::Valid)
+            }
+        },
+    }
+}
+
+/// Adds a `create_membrane_proof_for` function to the coordinator zome, with which the
+/// progenitor invites other agents
+fn add_create_membrane_proof_to_coordinator(
+    zome_file_tree: ZomeFileTree,
+    integrity_zome_name: &str,
+) -> ScaffoldResult<ZomeFileTree> {
+    if find_extern_function_in_zome(&zome_file_tree, "create_membrane_proof_for")?.is_some() {
+        return Ok(zome_file_tree);
+    }
+
+    let dna_manifest_path = zome_file_tree.dna_file_tree.dna_manifest_path.clone();
+    let zome_manifest = zome_file_tree.zome_manifest.clone();
+    let lib_rs_path = zome_file_tree.zome_crate_path.join("src").join("lib.rs");
+
+    let integrity_crate = format_ident!("{}", integrity_zome_name.to_case(Case::Snake));
+    let create_membrane_proof: syn::File = parse_quote! {
+        #[hdk_extern]
+        pub fn create_membrane_proof_for(
+            agent: AgentPubKey,
+        ) -> ExternResult<#integrity_crate::ProgenitorSignedMembraneProof> {
+            let progenitor = #integrity_crate::get_dna_properties()?.progenitor;
+            let my_pub_key = agent_info()?.agent_initial_pubkey;
+            if my_pub_key != progenitor {
+                return Err(wasm_error!(WasmErrorInner::Guest(String::from(
+                    "Only the progenitor can create membrane proofs"
+                ))));
+            }
+            let signature = sign(my_pub_key, agent)?;
+            Ok(#integrity_crate::ProgenitorSignedMembraneProof { signature })
+        }
+    };
+
+    let mut file_tree = zome_file_tree.dna_file_tree.file_tree();
+    map_file(&mut file_tree, &lib_rs_path, |contents| {
+        Ok(format!(
+            "{contents}\n{}",
+            unparse_pretty(&create_membrane_proof)
+        ))
+    })?;
+
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    ZomeFileTree::from_zome_manifest(dna_file_tree, zome_manifest)
+}
+
+/// Checks the membrane proof before the rest of the validation of `genesis_self_check` or
+/// `validate_agent_joining`, returns `None` if their parameters aren't the expected ones
+fn add_membrane_proof_check(item_fn: &mut ItemFn) -> Option<()> {
+    let check_stmts: Vec<syn::Stmt> = if item_fn.sig.ident == "genesis_self_check" {
+        let [data] = used_param_names(item_fn)?;
+        parse_quote! {
+            let result = validate_membrane_proof(&#data.agent_key, &#data.membrane_proof)?;
+            if result != ValidateCallbackResult::Valid {
+                return Ok(result);
+            }
+        }
+    } else {
+        let [agent_pub_key, membrane_proof] = used_param_names(item_fn)?;
+        parse_quote! {
+            let result = validate_membrane_proof(&#agent_pub_key, #membrane_proof)?;
+            if result != ValidateCallbackResult::Valid {
+                return Ok(result);
+            }
+        }
+    };
+    item_fn.block.stmts.splice(0..0, check_stmts);
+    Some(())
+}
+
+/// Names of the parameters of the function, which are now used so they lose their leading
+/// underscore, unless the existing body refers to them with it
+fn used_param_names<const N: usize>(item_fn: &mut ItemFn) -> Option<[syn::Ident; N]> {
+    if item_fn.sig.inputs.len() != N {
+        return None;
+    }
+    let body = item_fn.block.to_token_stream().to_string();
+    let body_words: Vec<&str> = body
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .collect();
+    let mut names = Vec::with_capacity(N);
+    for input in item_fn.sig.inputs.iter_mut() {
+        let syn::FnArg::Typed(pat_type) = input else {
+            return None;
+        };
+        let syn::Pat::Ident(pat_ident) = &mut *pat_type.pat else {
+            return None;
+        };
+        let ident = pat_ident.ident.to_string();
+        if let Some(name) = ident.strip_prefix('_') {
+            if !name.is_empty() && !body_words.contains(&ident.as_str()) {
+                pat_ident.ident = format_ident!("{name}");
+            }
+        }
+        names.push(pat_ident.ident.clone());
+    }
+    names.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn membrane_kind_round_trips_through_its_name() {
+        for kind in [
+            MembraneKind::InviteCode,
+            MembraneKind::SignedByProgenitor,
+            MembraneKind::Allowlist,
+        ] {
+            assert_eq!(MembraneKind::from_str(&kind.to_string()).unwrap(), kind);
+        }
+        assert!(MembraneKind::from_str("password").is_err());
+    }
+
+    #[test]
+    fn signed_by_progenitor_membrane_verifies_the_signature() {
+        let generated = unparse_pretty(
+            &syn::parse2(membrane_module(MembraneKind::SignedByProgenitor)).unwrap(),
+        );
+
+        assert!(generated.contains("pub struct ProgenitorSignedMembraneProof {"));
+        assert!(generated.contains("if agent_pub_key == &progenitor {"));
+        assert!(generated
+            .contains("verify_signature(progenitor, membrane_proof.signature, agent_pub_key)?"));
+    }
+
+    #[test]
+    fn membrane_proof_is_checked_before_the_existing_validation() {
+        let mut genesis_self_check: ItemFn = parse_quote! {
+            #[hdk_extern]
+            pub fn genesis_self_check(_data: GenesisSelfCheckData) -> ExternResult<ValidateCallbackResult> {
+                if _data.membrane_proof.is_none() {
+                    return Ok(ValidateCallbackResult::Invalid("No membrane proof".to_string()));
+                }
+                Ok(ValidateCallbackResult::Valid)
+            }
+        };
+        add_membrane_proof_check(&mut genesis_self_check).unwrap();
+        let generated = unparse_pretty(&parse_quote! { #genesis_self_check });
+
+        // The existing body still refers to the parameter by its name
+        assert!(generated.contains("_data: GenesisSelfCheckData,"));
+        assert!(generated
+            .contains("validate_membrane_proof(&_data.agent_key, &_data.membrane_proof)?;"));
+        assert!(generated.contains("if result != ValidateCallbackResult::Valid {"));
+        assert!(generated.contains("if _data.membrane_proof.is_none() {"));
+        assert!(generated.find("validate_membrane_proof") < generated.find("No membrane proof"));
+
+        let mut validate_agent_joining: ItemFn = parse_quote! {
+            pub fn validate_agent_joining(agent: AgentPubKey, _membrane_proof: &Option<MembraneProof>) -> ExternResult<ValidateCallbackResult> {
+                let blocked = must_get_valid_record(blocked_agents_hash())?;
+                Ok(ValidateCallbackResult::Valid)
+            }
+        };
+        add_membrane_proof_check(&mut validate_agent_joining).unwrap();
+        let generated = unparse_pretty(&parse_quote! { #validate_agent_joining });
+
+        assert!(generated.contains("agent: AgentPubKey,"));
+        assert!(generated.contains("membrane_proof: &Option<MembraneProof>,"));
+        assert!(generated.contains("validate_membrane_proof(&agent, membrane_proof)?"));
+        assert!(generated.contains("let blocked = must_get_valid_record(blocked_agents_hash())?;"));
+
+        let mut unexpected_params: ItemFn = parse_quote! {
+            pub fn validate_agent_joining(input: AgentJoiningInput) -> ExternResult<ValidateCallbackResult> {
+                Ok(ValidateCallbackResult::Valid)
+            }
+        };
+        assert!(add_membrane_proof_check(&mut unexpected_params).is_none());
+    }
+}
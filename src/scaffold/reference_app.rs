@@ -0,0 +1,597 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use convert_case::{Case, Casing};
+use ignore::WalkBuilder;
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::{build_file_tree, dir_content, flatten_file_tree, FileTree},
+    templates::ScaffoldedTemplate,
+    utils::format_code,
+};
+
+use super::{
+    app::AppFileTree,
+    collection::{scaffold_collection, CollectionType},
+    config::ScaffoldConfig,
+    dna::{scaffold_dna, DnaFileTree},
+    entry_type::{
+        create_policy::CreatePolicy,
+        crud::Crud,
+        definitions::{Cardinality, EntryTypeReference, FieldDefinition, FieldType, Referenceable},
+        fields::choosable_field_types_templates,
+        scaffold_entry_type,
+    },
+    file_storage::add_file_storage_to_zome_pair,
+    link_type::scaffold_link_type,
+    web_app::{
+        scaffold_web_app,
+        template_manifest::{TemplateCommand, TemplateManifest},
+        template_source::TemplateCommits,
+    },
+    zome::{
+        integrity_zome_name, scaffold_coordinator_zome_in_path, scaffold_integrity_zome_with_path,
+        ZomeFileTree,
+    },
+};
+
+const APP_NAME: &str = "reference_app";
+const DNA_NAME: &str = "reference";
+const INTEGRITY_ZOME_NAME: &str = "reference_integrity";
+const COORDINATOR_ZOME_NAME: &str = "reference";
+/// Stores the files of the `File` fields of the reference entry types
+const FILE_STORAGE_ZOME_NAME: &str = "file_storage";
+
+/// File marking a directory as a snapshot written by [`write_snapshot`]
+const SNAPSHOT_MARKER_FILE: &str = ".hc-scaffold-snapshot";
+
+/// Entry type with a field for every field type and cardinality
+const EVERY_FIELD: &str = "every_field";
+/// Entry type linking to the other entry types and to agents through its fields
+const LINKED: &str = "linked";
+/// Read-only entry type referenced by its entry hash
+const BY_ENTRY_HASH: &str = "by_entry_hash";
+
+/// Scaffolds the reference hApp in memory, returning its file tree
+pub fn scaffold_reference_app(template_file_tree: &FileTree) -> ScaffoldResult<FileTree> {
+    let manifest = TemplateManifest::from_template_file_tree(template_file_tree)?;
+    let implements = |command: TemplateCommand| {
+        manifest
+            .as_ref()
+            .is_none_or(|m| m.commands.contains(&command))
+    };
+
+    let vars = manifest
+        .as_ref()
+        .map(TemplateManifest::default_variables)
+        .unwrap_or_default();
+
+    let ScaffoldedTemplate { mut file_tree, .. } = step(
+        "web-app",
+        scaffold_web_app(APP_NAME, None, false, template_file_tree, &vars),
+    )?;
+    if !vars.is_empty() {
+        ScaffoldConfig {
+            template: None,
+            template_commit: None,
+            template_base_commits: TemplateCommits::new(),
+            vars,
+        }
+        .write_to_package_json(&mut file_tree)?;
+    }
+
+    let app_file_tree = AppFileTree::get_or_choose(file_tree, Some(APP_NAME))?;
+    let ScaffoldedTemplate { file_tree, .. } = step(
+        "dna",
+        scaffold_dna(app_file_tree, template_file_tree, DNA_NAME, 0, false),
+    )?;
+
+    let dna_file_tree = DnaFileTree::get_or_choose(file_tree, Some(DNA_NAME))?;
+    let dna_manifest_path = dna_file_tree.dna_manifest_path.clone();
+    let zomes_path = PathBuf::from("dnas").join(DNA_NAME).join("zomes");
+    let ScaffoldedTemplate { file_tree, .. } = step(
+        "integrity zome",
+        scaffold_integrity_zome_with_path(
+            dna_file_tree,
+            template_file_tree,
+            INTEGRITY_ZOME_NAME,
+            &zomes_path.join("integrity"),
+        ),
+    )?;
+
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    let ScaffoldedTemplate { file_tree, .. } = step(
+        "coordinator zome",
+        scaffold_coordinator_zome_in_path(
+            dna_file_tree,
+            template_file_tree,
+            COORDINATOR_ZOME_NAME,
+            Some(&vec![INTEGRITY_ZOME_NAME.to_string()]),
+            &zomes_path.join("coordinator"),
+        ),
+    )?;
+
+    // Scaffolded like `scaffold_file_storage` does, but without asking where to put the zomes
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    let ScaffoldedTemplate { file_tree, .. } = step(
+        "file-storage integrity zome",
+        scaffold_integrity_zome_with_path(
+            dna_file_tree,
+            template_file_tree,
+            &integrity_zome_name(FILE_STORAGE_ZOME_NAME),
+            &zomes_path.join("integrity"),
+        ),
+    )?;
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    let ScaffoldedTemplate { file_tree, .. } = step(
+        "file-storage coordinator zome",
+        scaffold_coordinator_zome_in_path(
+            dna_file_tree,
+            template_file_tree,
+            FILE_STORAGE_ZOME_NAME,
+            Some(&vec![integrity_zome_name(FILE_STORAGE_ZOME_NAME)]),
+            &zomes_path.join("coordinator"),
+        ),
+    )?;
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    let ScaffoldedTemplate { mut file_tree, .. } = step(
+        "file-storage",
+        add_file_storage_to_zome_pair(
+            dna_file_tree,
+            template_file_tree,
+            FILE_STORAGE_ZOME_NAME,
+            10,
+            false,
+            false,
+        ),
+    )?;
+
+    let integrity_zome = |file_tree: FileTree| {
+        let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+        ZomeFileTree::get_or_choose_integrity(dna_file_tree, Some(INTEGRITY_ZOME_NAME))
+    };
+
+    if implements(TemplateCommand::EntryType) {
+        for (name, crud, reference_entry_hash, fields) in reference_entry_types(template_file_tree)?
+        {
+            let ScaffoldedTemplate { file_tree: f, .. } = step(
+                &format!("entry-type {name}"),
+                scaffold_entry_type(
+                    integrity_zome(file_tree)?,
+                    template_file_tree,
+                    name,
+                    Some(crud),
+                    Some(reference_entry_hash),
+                    Some(crud.update),
+                    Some(&fields),
+                    &[],
+                    false,
+                    CreatePolicy::Anyone,
+                    None,
+                    false,
+                    false,
+                ),
+            )?;
+            file_tree = f;
+        }
+    }
+
+    if implements(TemplateCommand::Collection) {
+        for (name, collection_type, entry_type) in reference_collections() {
+            let ScaffoldedTemplate { file_tree: f, .. } = step(
+                &format!("collection {name}"),
+                scaffold_collection(
+                    integrity_zome(file_tree)?,
+                    template_file_tree,
+                    name,
+                    Some(collection_type),
+                    Some(entry_type),
+                    false,
+                    false,
+                ),
+            )?;
+            file_tree = f;
+        }
+    }
+
+    if implements(TemplateCommand::LinkType) {
+        for (from, to, delete, bidirectional, query_functions) in reference_link_types() {
+            let from_name = from.to_string(&Cardinality::Single);
+            // Link types without a target need to be named
+            let name = to
+                .is_none()
+                .then(|| format!("{}Metadata", from_name.to_case(Case::Pascal)));
+            let ScaffoldedTemplate { file_tree: f, .. } = step(
+                &format!(
+                    "link-type {from_name} {}",
+                    to.as_ref()
+                        .map(|to| to.to_string(&Cardinality::Single))
+                        .unwrap_or_default()
+                ),
+                scaffold_link_type(
+                    integrity_zome(file_tree)?,
+                    template_file_tree,
+                    Some(&from),
+                    to.as_ref(),
+                    name.as_deref(),
+                    Some(delete),
+                    Some(bidirectional),
+                    Some(query_functions),
+                    None,
+                    false,
+                    false,
+                    false,
+                ),
+            )?;
+            file_tree = f;
+        }
+    }
+
+    Ok(file_tree)
+}
+
+/// Adds the step of the reference app that failed to its error
+fn step<T>(name: &str, result: ScaffoldResult<T>) -> ScaffoldResult<T> {
+    result.map_err(|e| ScaffoldError::MalformedTemplate(format!("scaffolding {name} failed: {e}")))
+}
+
+/// Name, CRUD, whether it's referenced by entry hash, and fields of an entry type
+type ReferenceEntryType = (&'static str, Crud, bool, Vec<FieldDefinition>);
+
+fn reference_entry_types(template_file_tree: &FileTree) -> ScaffoldResult<Vec<ReferenceEntryType>> {
+    let field_types_templates = choosable_field_types_templates(template_file_tree)?;
+
+    let mut every_field = Vec::new();
+    for field_type in FieldType::list() {
+        for cardinality in [
+            Cardinality::Single,
+            Cardinality::Option,
+            Cardinality::Vector,
+        ] {
+            let cardinality_name = format!("{cardinality:?}");
+            let field_type = match field_type {
+                FieldType::Enum { .. } => FieldType::Enum {
+                    label: format!("{cardinality_name}Kind"),
+                    variants: vec!["First".to_string(), "Second".to_string()],
+                },
+                _ => field_type.clone(),
+            };
+            // Mirrors `choose_widget`, which doesn't offer widgets for Vec<u8>
+            let widget = match (&field_type, &cardinality) {
+                (FieldType::U8, Cardinality::Vector) => None,
+                _ => first_widget(&field_types_templates, &field_type),
+            };
+
+            every_field.push(FieldDefinition {
+                field_name: format!(
+                    "{}_{}",
+                    field_type.to_string().to_case(Case::Snake),
+                    cardinality_name.to_case(Case::Snake)
+                ),
+                field_type,
+                widget,
+                cardinality,
+                linked_from: None,
+            });
+        }
+    }
+
+    let linked = vec![
+        FieldDefinition {
+            field_name: "every_field_hash".to_string(),
+            field_type: FieldType::ActionHash,
+            widget: None,
+            cardinality: Cardinality::Single,
+            linked_from: Some(Referenceable::EntryType(entry_type_reference(
+                EVERY_FIELD,
+                false,
+            ))),
+        },
+        FieldDefinition {
+            field_name: "by_entry_hashes".to_string(),
+            field_type: FieldType::EntryHash,
+            widget: None,
+            cardinality: Cardinality::Vector,
+            linked_from: Some(Referenceable::EntryType(entry_type_reference(
+                BY_ENTRY_HASH,
+                true,
+            ))),
+        },
+        FieldDefinition {
+            field_name: "reviewer".to_string(),
+            field_type: FieldType::AgentPubKey,
+            widget: None,
+            cardinality: Cardinality::Option,
+            linked_from: Some(Referenceable::Agent {
+                role: "reviewer".to_string(),
+            }),
+        },
+    ];
+
+    let by_entry_hash = vec![FieldDefinition {
+        field_name: "title".to_string(),
+        field_type: FieldType::String,
+        widget: first_widget(&field_types_templates, &FieldType::String),
+        cardinality: Cardinality::Single,
+        linked_from: None,
+    }];
+
+    Ok(vec![
+        (
+            EVERY_FIELD,
+            Crud {
+                update: true,
+                delete: true,
+            },
+            false,
+            every_field,
+        ),
+        (
+            BY_ENTRY_HASH,
+            Crud {
+                update: false,
+                delete: false,
+            },
+            true,
+            by_entry_hash,
+        ),
+        (
+            LINKED,
+            Crud {
+                update: false,
+                delete: true,
+            },
+            false,
+            linked,
+        ),
+    ])
+}
+
+fn first_widget(field_types_templates: &FileTree, field_type: &FieldType) -> Option<String> {
+    dir_content(field_types_templates, Path::new(&field_type.to_string()))
+        .ok()?
+        .into_iter()
+        .find(|(_, t)| t.dir_content().is_some())
+        .and_then(|(widget, _)| widget.to_str().map(|s| s.to_string()))
+}
+
+fn entry_type_reference(entry_type: &str, reference_entry_hash: bool) -> EntryTypeReference {
+    EntryTypeReference {
+        entry_type: entry_type.to_case(Case::Pascal),
+        reference_entry_hash,
+    }
+}
+
+fn reference_collections() -> Vec<(&'static str, CollectionType, EntryTypeReference)> {
+    vec![
+        (
+            "all_every_fields",
+            CollectionType::Global,
+            entry_type_reference(EVERY_FIELD, false),
+        ),
+        (
+            "every_fields_by_author",
+            CollectionType::ByAuthor,
+            entry_type_reference(EVERY_FIELD, false),
+        ),
+        (
+            "all_by_entry_hashes",
+            CollectionType::Global,
+            entry_type_reference(BY_ENTRY_HASH, true),
+        ),
+        (
+            "by_entry_hashes_by_author",
+            CollectionType::ByAuthor,
+            entry_type_reference(BY_ENTRY_HASH, true),
+        ),
+    ]
+}
+
+/// Base, target, and whether it's deletable, bidirectional and has query functions, of a link type
+type ReferenceLinkType = (Referenceable, Option<Referenceable>, bool, bool, bool);
+
+/// Links between every kind of referenceable, with and without a target, deletion,
+/// bidirectionality and query functions
+fn reference_link_types() -> Vec<ReferenceLinkType> {
+    let every_field = Referenceable::EntryType(entry_type_reference(EVERY_FIELD, false));
+    let linked = Referenceable::EntryType(entry_type_reference(LINKED, false));
+    let by_entry_hash = Referenceable::EntryType(entry_type_reference(BY_ENTRY_HASH, true));
+    let agent = |role: &str| Referenceable::Agent {
+        role: role.to_string(),
+    };
+
+    vec![
+        (
+            every_field.clone(),
+            Some(by_entry_hash.clone()),
+            true,
+            true,
+            true,
+        ),
+        (
+            linked.clone(),
+            Some(every_field.clone()),
+            false,
+            false,
+            false,
+        ),
+        (
+            agent("creator"),
+            Some(every_field.clone()),
+            false,
+            true,
+            true,
+        ),
+        (
+            every_field.clone(),
+            Some(agent("follower")),
+            true,
+            false,
+            true,
+        ),
+        (
+            agent("sponsor"),
+            Some(by_entry_hash.clone()),
+            true,
+            false,
+            false,
+        ),
+        (linked, Some(by_entry_hash), true, false, true),
+        (every_field, None, true, false, false),
+        (agent("annotator"), None, false, false, false),
+    ]
+}
+
+/// Formats every file of the given tree, returning the ones that failed along with their error
+pub fn check_formatting(file_tree: &FileTree) -> Vec<(PathBuf, String)> {
+    flatten_file_tree(file_tree)
+        .into_iter()
+        .filter_map(|(path, contents)| {
+            let error = format_code(&contents?, path.clone()).err()?;
+            Some((path, error.to_string()))
+        })
+        .collect()
+}
+
+/// Compares the given file tree with the snapshot in the given directory, returning the
+/// differences found
+pub fn compare_with_snapshot(
+    file_tree: &FileTree,
+    snapshot_dir: &Path,
+) -> ScaffoldResult<Vec<String>> {
+    let files: BTreeMap<PathBuf, String> = flatten_file_tree(file_tree)
+        .into_iter()
+        .filter_map(|(path, contents)| Some((path, contents?)))
+        .collect();
+
+    let mut differences = Vec::new();
+
+    for (path, contents) in &files {
+        match fs::read_to_string(snapshot_dir.join(path)) {
+            Ok(snapshot) if &snapshot == contents => {}
+            Ok(_) => differences.push(format!("{}: differs from the snapshot", path.display())),
+            Err(_) => differences.push(format!("{}: missing from the snapshot", path.display())),
+        }
+    }
+
+    for entry in WalkBuilder::new(snapshot_dir)
+        .standard_filters(false)
+        .build()
+    {
+        let entry = entry?;
+        if entry.file_type().is_some_and(|t| t.is_file()) {
+            let path = entry.path().strip_prefix(snapshot_dir).map_err(|e| {
+                ScaffoldError::InvalidPath(entry.path().to_path_buf(), e.to_string())
+            })?;
+            if path != Path::new(SNAPSHOT_MARKER_FILE) && !files.contains_key(path) {
+                differences.push(format!("{}: is no longer generated", path.display()));
+            }
+        }
+    }
+
+    Ok(differences)
+}
+
+/// Replaces the snapshot in the given directory with the given file tree
+///
+/// Refuses to replace a directory that isn't empty and wasn't written by this function, so that
+/// a mistyped snapshot path doesn't wipe out an unrelated directory.
+pub fn write_snapshot(file_tree: FileTree, snapshot_dir: &Path) -> ScaffoldResult<()> {
+    if snapshot_dir.exists() {
+        let is_empty = fs::read_dir(snapshot_dir)?.next().is_none();
+        if !is_empty && !snapshot_dir.join(SNAPSHOT_MARKER_FILE).is_file() {
+            return Err(ScaffoldError::InvalidPath(
+                snapshot_dir.to_path_buf(),
+                format!("it is not empty and doesn't contain a snapshot (no {SNAPSHOT_MARKER_FILE} file), refusing to replace it"),
+            ));
+        }
+        fs::remove_dir_all(snapshot_dir)?;
+    }
+    fs::create_dir_all(snapshot_dir)?;
+    build_file_tree(file_tree, snapshot_dir)?;
+    fs::write(snapshot_dir.join(SNAPSHOT_MARKER_FILE), "")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaffold::web_app::template_type::TemplateType;
+
+    #[test]
+    fn reference_app_is_scaffolded_and_formatted_with_built_in_templates() {
+        for template_type in [TemplateType::Svelte, TemplateType::Headless] {
+            let file_tree = scaffold_reference_app(&template_type.file_tree().unwrap()).unwrap();
+            assert_eq!(check_formatting(&file_tree), vec![]);
+
+            let coordinator_src = PathBuf::from("dnas")
+                .join(DNA_NAME)
+                .join("zomes/coordinator")
+                .join(COORDINATOR_ZOME_NAME)
+                .join("src");
+            for link_type in ["every_field_metadata.rs", "annotator_metadata.rs"] {
+                assert!(
+                    crate::file_tree::file_exists(&file_tree, &coordinator_src.join(link_type)),
+                    "{link_type}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn snapshot_differences_are_reported() {
+        let snapshot_dir = std::env::temp_dir().join("hc_scaffold_snapshot_differences_test");
+        let _ = fs::remove_dir_all(&snapshot_dir);
+        write_snapshot(
+            build_fs_tree::dir! {
+                "kept.txt" => build_fs_tree::file!("same"),
+                "changed.txt" => build_fs_tree::file!("before"),
+                "removed.txt" => build_fs_tree::file!("gone")
+            },
+            &snapshot_dir,
+        )
+        .unwrap();
+
+        let differences = compare_with_snapshot(
+            &build_fs_tree::dir! {
+                "kept.txt" => build_fs_tree::file!("same"),
+                "changed.txt" => build_fs_tree::file!("after"),
+                "added.txt" => build_fs_tree::file!("new")
+            },
+            &snapshot_dir,
+        )
+        .unwrap();
+        fs::remove_dir_all(&snapshot_dir).unwrap();
+
+        assert_eq!(
+            differences,
+            vec![
+                "added.txt: missing from the snapshot",
+                "changed.txt: differs from the snapshot",
+                "removed.txt: is no longer generated",
+            ]
+        );
+    }
+
+    #[test]
+    fn directories_that_are_not_snapshots_are_not_replaced() {
+        let snapshot_dir = std::env::temp_dir().join("hc_scaffold_snapshot_refusal_test");
+        let _ = fs::remove_dir_all(&snapshot_dir);
+        fs::create_dir_all(&snapshot_dir).unwrap();
+        fs::write(snapshot_dir.join("important.txt"), "keep me").unwrap();
+
+        let result = write_snapshot(
+            build_fs_tree::dir! { "a.txt" => build_fs_tree::file!("a") },
+            &snapshot_dir,
+        );
+        let kept = fs::read_to_string(snapshot_dir.join("important.txt"));
+        fs::remove_dir_all(&snapshot_dir).unwrap();
+
+        assert!(result.is_err());
+        assert_eq!(kept.unwrap(), "keep me");
+    }
+}
@@ -0,0 +1,185 @@
+use std::path::PathBuf;
+
+use convert_case::{Case, Casing};
+use dialoguer::{theme::ColorfulTheme, Confirm};
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::FileTree,
+    templates::ScaffoldedTemplate,
+};
+
+use self::coordinator::add_relation_functions_to_coordinator;
+
+use super::{
+    dna::DnaFileTree,
+    entry_type::{
+        create_policy::CreatePolicy,
+        crud::Crud,
+        definitions::{Cardinality, EntryDefinition, FieldDefinition, Referenceable},
+        fields::{choosable_field_types_templates, choose_fields},
+        integrity::get_all_entry_types,
+        scaffold_entry_type,
+        utils::get_or_choose_referenceable,
+    },
+    link_type::{integrity::add_link_type_to_integrity_zome, link_type_name},
+    zome::{utils::get_coordinator_zomes_for_integrity, ZomeFileTree},
+};
+
+pub mod coordinator;
+
+/// Scaffolds a many-to-many relation between two referenceables through a join entry type
+///
+/// The join entry holds the addresses of both ends of the relation plus any extra fields,
+/// and is linked both ways: `from -> join -> to` and `to -> join -> from`
+#[allow(clippy::too_many_arguments)]
+pub fn scaffold_relation(
+    zome_file_tree: ZomeFileTree,
+    template_file_tree: &FileTree,
+    from_referenceable: Option<&Referenceable>,
+    to_referenceable: Option<&Referenceable>,
+    join_entry_name: &str,
+    maybe_fields: Option<&Vec<FieldDefinition>>,
+    no_ui: bool,
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    let dna_manifest_path = zome_file_tree.dna_file_tree.dna_manifest_path.clone();
+    let all_entry_types = get_all_entry_types(&zome_file_tree)?.unwrap_or_default();
+
+    let from_referenceable = get_or_choose_referenceable(
+        "Relate which entry type?",
+        &zome_file_tree,
+        from_referenceable,
+        &all_entry_types,
+    )?;
+    let to_referenceable = get_or_choose_referenceable(
+        "To which entry type?",
+        &zome_file_tree,
+        to_referenceable,
+        &all_entry_types,
+    )?;
+
+    if from_referenceable.to_string(&Cardinality::Single)
+        == to_referenceable.to_string(&Cardinality::Single)
+    {
+        return Err(ScaffoldError::InvalidArguments(
+            "both ends of a relation must be different entry types or agent roles".to_string(),
+        ));
+    }
+
+    let extra_fields = match maybe_fields {
+        Some(f) => f.clone(),
+        None => {
+            let add_fields = Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!(
+                    "Should the {} entry contain fields other than the addresses of both ends of the relation?",
+                    join_entry_name.to_case(Case::Pascal)
+                ))
+                .default(false)
+                .interact()?;
+
+            if add_fields {
+                choose_fields(
+                    join_entry_name,
+                    &zome_file_tree,
+                    &choosable_field_types_templates(template_file_tree)?,
+                    no_ui,
+                )?
+            } else {
+                vec![]
+            }
+        }
+    };
+
+    let mut fields = vec![
+        relation_end_field(&from_referenceable)?,
+        relation_end_field(&to_referenceable)?,
+    ];
+
+    if let Some(f) = extra_fields
+        .iter()
+        .find(|f| fields.iter().any(|end| end.field_name == f.field_name))
+    {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "field {} is reserved for the address of one of the ends of the relation",
+            f.field_name
+        )));
+    }
+    fields.extend(extra_fields);
+
+    let join_entry_def = EntryDefinition {
+        name: join_entry_name.to_owned(),
+        fields,
+        reference_entry_hash: false,
+    };
+    let integrity_zome_manifest = zome_file_tree.zome_manifest.clone();
+
+    // 1. Scaffold the join entry type, which also adds the `from -> join` and `to -> join` link types
+    let ScaffoldedTemplate {
+        file_tree,
+        next_instructions,
+    } = scaffold_entry_type(
+        zome_file_tree,
+        template_file_tree,
+        join_entry_name,
+        Some(Crud {
+            update: false,
+            delete: true,
+        }),
+        Some(false),
+        Some(false),
+        Some(&join_entry_def.fields),
+        &[],
+        false,
+        CreatePolicy::Anyone,
+        None,
+        no_ui,
+        no_spec,
+    )?;
+
+    // 2. Add the `join -> from` and `join -> to` link types
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    let mut zome_file_tree =
+        ZomeFileTree::from_zome_manifest(dna_file_tree, integrity_zome_manifest)?;
+
+    for end in [&from_referenceable, &to_referenceable] {
+        zome_file_tree = add_link_type_to_integrity_zome(
+            zome_file_tree,
+            &link_type_name(&join_entry_def.referenceable(), end),
+            &Some(join_entry_def.referenceable()),
+            &Some(end.clone()),
+            true,
+            &PathBuf::from(format!("{}.rs", join_entry_def.snake_case_name())),
+        )?;
+    }
+
+    // 3. Create and delete those links along with the join entry, and list each side of the relation
+    let coordinator_zomes_for_integrity = get_coordinator_zomes_for_integrity(
+        &zome_file_tree.dna_file_tree.dna_manifest,
+        zome_file_tree.zome_manifest.name.0.as_ref(),
+    );
+    let integrity_zome_name = zome_file_tree.zome_manifest.name.0.to_string();
+    let dna_file_tree = add_relation_functions_to_coordinator(
+        zome_file_tree.dna_file_tree,
+        &integrity_zome_name,
+        &coordinator_zomes_for_integrity,
+        &join_entry_def,
+        &from_referenceable,
+        &to_referenceable,
+    )?;
+
+    Ok(ScaffoldedTemplate {
+        file_tree: dna_file_tree.file_tree(),
+        next_instructions,
+    })
+}
+
+fn relation_end_field(referenceable: &Referenceable) -> ScaffoldResult<FieldDefinition> {
+    FieldDefinition::new(
+        referenceable.field_name(&Cardinality::Single),
+        referenceable.field_type(),
+        None,
+        Cardinality::Single,
+        Some(referenceable.clone()),
+    )
+}
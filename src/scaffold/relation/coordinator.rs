@@ -0,0 +1,389 @@
+use std::ffi::OsString;
+
+use convert_case::{Case, Casing};
+use holochain_types::prelude::ZomeManifest;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, ItemFn};
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::map_rust_files,
+    scaffold::{
+        dna::DnaFileTree,
+        entry_type::definitions::{Cardinality, EntryDefinition, Referenceable},
+        link_type::link_type_name,
+        zome::{coordinator::find_extern_function_in_zomes, ZomeFileTree},
+    },
+};
+
+pub fn add_relation_functions_to_coordinator(
+    dna_file_tree: DnaFileTree,
+    integrity_zome_name: &str,
+    coordinator_zomes_for_integrity: &Vec<ZomeManifest>,
+    join_entry_def: &EntryDefinition,
+    from_referenceable: &Referenceable,
+    to_referenceable: &Referenceable,
+) -> ScaffoldResult<DnaFileTree> {
+    let dna_manifest_path = dna_file_tree.dna_manifest_path.clone();
+    let snake_join_entry_name = join_entry_def.snake_case_name();
+
+    let Some((chosen_coordinator_zome, _)) = find_extern_function_in_zomes(
+        &dna_file_tree,
+        coordinator_zomes_for_integrity,
+        &format!("create_{snake_join_entry_name}"),
+    )?
+    else {
+        return Err(ScaffoldError::NoCoordinatorZomesFoundForIntegrityZome(
+            dna_file_tree.dna_manifest.name(),
+            integrity_zome_name.to_string(),
+        ));
+    };
+
+    let zome_file_tree = ZomeFileTree::from_zome_manifest(dna_file_tree, chosen_coordinator_zome)?;
+
+    let create_join_links_stmts =
+        create_join_links_stmts(join_entry_def, from_referenceable, to_referenceable);
+    let delete_join_links_stmts =
+        delete_join_links_stmts(join_entry_def, from_referenceable, to_referenceable);
+    let list_relation_handlers: syn::File = syn::parse2(list_relation_handlers(
+        join_entry_def,
+        from_referenceable,
+        to_referenceable,
+    ))?;
+
+    let create_fn_name = format!("create_{snake_join_entry_name}");
+    let join_hash = format!("{snake_join_entry_name}_hash");
+    let delete_fn_name = format!("delete_{snake_join_entry_name}");
+    let join_file_name = format!("{snake_join_entry_name}.rs");
+
+    let crate_src_path = zome_file_tree.zome_crate_path.join("src");
+
+    let mut file_tree = zome_file_tree.dna_file_tree.file_tree();
+
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+    map_rust_files(
+        file_tree
+            .path_mut(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        |file_path, mut file| {
+            if *file_path != *join_file_name {
+                return Ok(file);
+            }
+
+            for item in &mut file.items {
+                if let syn::Item::Fn(item_fn) = item {
+                    if item_fn.sig.ident == create_fn_name {
+                        // Create the links right before the newly created record is fetched
+                        let index = create_join_links_index(item_fn, &join_hash).ok_or(
+                            ScaffoldError::MalformedFile(
+                                file_path.clone(),
+                                format!(
+                                    "couldn't find where {create_fn_name} returns the created {snake_join_entry_name}, to create the links of the relation before it"
+                                ),
+                            ),
+                        )?;
+                        item_fn
+                            .block
+                            .stmts
+                            .splice(index..index, create_join_links_stmts.clone());
+                    } else if item_fn.sig.ident == delete_fn_name {
+                        if let Some(delete_stmt) = item_fn.block.stmts.pop() {
+                            item_fn.block.stmts.extend(delete_join_links_stmts.clone());
+                            item_fn.block.stmts.push(delete_stmt);
+                        }
+                    }
+                }
+            }
+
+            file.items.extend(list_relation_handlers.items.clone());
+
+            Ok(file)
+        },
+    )
+    .map_err(|e| match e {
+        ScaffoldError::MalformedFile(path, error) => {
+            ScaffoldError::MalformedFile(crate_src_path.join(path), error)
+        }
+        _ => e,
+    })?;
+
+    DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)
+}
+
+/// Index of the statement of the create function fetching the newly created join entry, or of
+/// its trailing `Ok(..)` if it doesn't fetch it, once the `<join>_hash` variable is declared
+fn create_join_links_index(create_fn: &ItemFn, join_hash: &str) -> Option<usize> {
+    let stmts = &create_fn.block.stmts;
+    let join_hash_index = stmts.iter().position(|stmt| match stmt {
+        syn::Stmt::Local(local) => pat_ident(&local.pat).is_some_and(|i| i == join_hash),
+        _ => false,
+    })?;
+
+    (join_hash_index + 1..stmts.len()).find(|i| match &stmts[*i] {
+        syn::Stmt::Local(local) => local
+            .init
+            .as_ref()
+            .is_some_and(|init| calls_fn(&init.expr, "get")),
+        syn::Stmt::Expr(expr, None) => calls_fn(expr, "Ok"),
+        _ => false,
+    })
+}
+
+fn pat_ident(pat: &syn::Pat) -> Option<&syn::Ident> {
+    match pat {
+        syn::Pat::Ident(pat_ident) => Some(&pat_ident.ident),
+        syn::Pat::Type(pat_type) => pat_ident(&pat_type.pat),
+        _ => None,
+    }
+}
+
+/// Whether the expression is a call to the given function, possibly followed by `?`, `.await`
+/// or method calls like `.ok_or(..)`
+fn calls_fn(expr: &syn::Expr, fn_name: &str) -> bool {
+    match expr {
+        syn::Expr::Call(call) => match &*call.func {
+            syn::Expr::Path(path) => path.path.is_ident(fn_name),
+            _ => false,
+        },
+        syn::Expr::Try(expr_try) => calls_fn(&expr_try.expr, fn_name),
+        syn::Expr::Await(expr_await) => calls_fn(&expr_await.base, fn_name),
+        syn::Expr::MethodCall(method_call) => calls_fn(&method_call.receiver, fn_name),
+        _ => false,
+    }
+}
+
+fn create_join_links_stmts(
+    join_entry_def: &EntryDefinition,
+    from_referenceable: &Referenceable,
+    to_referenceable: &Referenceable,
+) -> Vec<syn::Stmt> {
+    let snake_join_entry_name = format_ident!("{}", join_entry_def.snake_case_name());
+    let join_hash = format_ident!("{}_hash", join_entry_def.snake_case_name());
+
+    [from_referenceable, to_referenceable]
+        .into_iter()
+        .map(|end| {
+            let field_name = format_ident!("{}", end.field_name(&Cardinality::Single));
+            let link_type =
+                format_ident!("{}", link_type_name(&join_entry_def.referenceable(), end));
+            parse_quote! {
+                create_link(
+                    #join_hash.clone(),
+                    #snake_join_entry_name.#field_name.clone(),
+                    LinkTypes::#link_type,
+                    (),
+                )?;
+            }
+        })
+        .collect()
+}
+
+fn delete_join_links_stmts(
+    join_entry_def: &EntryDefinition,
+    from_referenceable: &Referenceable,
+    to_referenceable: &Referenceable,
+) -> Vec<syn::Stmt> {
+    let original_join_hash = format_ident!("original_{}_hash", join_entry_def.snake_case_name());
+
+    [from_referenceable, to_referenceable]
+        .into_iter()
+        .map(|end| {
+            let link_type =
+                format_ident!("{}", link_type_name(&join_entry_def.referenceable(), end));
+            parse_quote! {
+                for link in get_links(
+                    LinkQuery::try_new(#original_join_hash.clone(), LinkTypes::#link_type)?,
+                    GetStrategy::default(),
+                )? {
+                    delete_link(link.create_link_hash, GetOptions::default())?;
+                }
+            }
+        })
+        .collect()
+}
+
+fn list_relation_handlers(
+    join_entry_def: &EntryDefinition,
+    from_referenceable: &Referenceable,
+    to_referenceable: &Referenceable,
+) -> TokenStream {
+    let list_to_handler =
+        list_related_handler(join_entry_def, from_referenceable, to_referenceable);
+    let list_from_handler =
+        list_related_handler(join_entry_def, to_referenceable, from_referenceable);
+
+    quote! {
+        #list_to_handler
+
+        #list_from_handler
+    }
+}
+
+/// Walks `base -> join -> related`, only fetching links: the returned links target the
+/// related entries (or agents) directly
+pub fn list_related_handler(
+    join_entry_def: &EntryDefinition,
+    base_referenceable: &Referenceable,
+    related_referenceable: &Referenceable,
+) -> TokenStream {
+    let base_field_type = format_ident!("{}", base_referenceable.field_type().to_string());
+    let base_arg_name = format_ident!("{}", base_referenceable.field_name(&Cardinality::Single));
+
+    let base_to_join_link_type = format_ident!(
+        "{}",
+        link_type_name(base_referenceable, &join_entry_def.referenceable())
+    );
+    let join_to_related_link_type = format_ident!(
+        "{}",
+        link_type_name(&join_entry_def.referenceable(), related_referenceable)
+    );
+
+    let singular_snake_base = base_referenceable
+        .to_string(&Cardinality::Single)
+        .to_case(Case::Snake);
+    let plural_snake_related = related_referenceable
+        .to_string(&Cardinality::Vector)
+        .to_case(Case::Snake);
+    let snake_join_entry_name = join_entry_def.snake_case_name();
+
+    let function_name = format_ident!("get_{plural_snake_related}_for_{singular_snake_base}");
+    let join_links = format_ident!("{snake_join_entry_name}_links");
+    let join_link = format_ident!("{snake_join_entry_name}_link");
+    let join_hash = format_ident!("{snake_join_entry_name}_hash");
+
+    quote! {
+        #[hdk_extern]
+        pub fn #function_name(#base_arg_name: #base_field_type) -> ExternResult<Vec<Link>> {
+            let #join_links = get_links(
+                LinkQuery::try_new(#base_arg_name, LinkTypes::#base_to_join_link_type)?,
+                GetStrategy::default(),
+            )?;
+            let mut links = Vec::new();
+            for #join_link in #join_links {
+                let Some(#join_hash) = #join_link.target.into_action_hash() else {
+                    continue;
+                };
+                links.extend(get_links(
+                    LinkQuery::try_new(#join_hash, LinkTypes::#join_to_related_link_type)?,
+                    GetStrategy::default(),
+                )?);
+            }
+            Ok(links)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        scaffold::entry_type::definitions::{EntryTypeReference, FieldDefinition, FieldType},
+        utils::unparse_pretty,
+    };
+
+    fn membership() -> (EntryDefinition, Referenceable, Referenceable) {
+        let user = Referenceable::EntryType(EntryTypeReference {
+            entry_type: "User".to_string(),
+            reference_entry_hash: false,
+        });
+        let group = Referenceable::EntryType(EntryTypeReference {
+            entry_type: "Group".to_string(),
+            reference_entry_hash: false,
+        });
+        let field = |r: &Referenceable| {
+            FieldDefinition::new(
+                r.field_name(&Cardinality::Single),
+                FieldType::ActionHash,
+                None,
+                Cardinality::Single,
+                Some(r.clone()),
+            )
+            .unwrap()
+        };
+        let entry_def = EntryDefinition {
+            name: "membership".to_string(),
+            fields: vec![field(&user), field(&group)],
+            reference_entry_hash: false,
+        };
+        (entry_def, user, group)
+    }
+
+    #[test]
+    fn list_relation_handlers_walk_through_the_join_entry() {
+        let (entry_def, user, group) = membership();
+
+        let handlers = list_relation_handlers(&entry_def, &user, &group);
+        let generated = unparse_pretty(&syn::parse_quote! { #handlers });
+
+        assert!(generated.contains("pub fn get_groups_for_user(user_hash: ActionHash)"));
+        assert!(generated.contains("LinkQuery::try_new(user_hash, LinkTypes::UserToMemberships)?"));
+        assert!(generated
+            .contains("LinkQuery::try_new(membership_hash, LinkTypes::MembershipToGroups)?"));
+        assert!(generated.contains("pub fn get_users_for_group(group_hash: ActionHash)"));
+        assert!(generated
+            .contains("LinkQuery::try_new(membership_hash, LinkTypes::MembershipToUsers)?"));
+    }
+
+    #[test]
+    fn join_links_are_created_before_the_record_is_returned() {
+        let create_fn: ItemFn = parse_quote! {
+            #[hdk_extern]
+            pub fn create_membership(membership: Membership) -> ExternResult<Record> {
+                let membership_hash = create_entry(&EntryTypes::Membership(membership.clone()))?;
+                let record = get(membership_hash.clone(), GetOptions::default())?
+                    .ok_or(wasm_error!(WasmErrorInner::Guest("Could not find the newly created Membership".to_string())))?;
+                Ok(record)
+            }
+        };
+        assert_eq!(
+            create_join_links_index(&create_fn, "membership_hash"),
+            Some(1)
+        );
+
+        let create_fn: ItemFn = parse_quote! {
+            #[hdk_extern]
+            pub fn create_membership(membership: Membership) -> ExternResult<ActionHash> {
+                let membership_hash = create_entry(&EntryTypes::Membership(membership.clone()))?;
+                emit_signal(membership.clone())?;
+                Ok(membership_hash)
+            }
+        };
+        assert_eq!(
+            create_join_links_index(&create_fn, "membership_hash"),
+            Some(2)
+        );
+
+        let create_fn: ItemFn = parse_quote! {
+            #[hdk_extern]
+            pub fn create_membership(membership: Membership) -> ExternResult<ActionHash> {
+                create_entry(&EntryTypes::Membership(membership))
+            }
+        };
+        assert_eq!(create_join_links_index(&create_fn, "membership_hash"), None);
+    }
+
+    #[test]
+    fn join_links_are_created_and_deleted_for_both_ends() {
+        let (entry_def, user, group) = membership();
+
+        let create_stmts = create_join_links_stmts(&entry_def, &user, &group);
+        let delete_stmts = delete_join_links_stmts(&entry_def, &user, &group);
+        let generated = unparse_pretty(&syn::parse_quote! {
+            fn create() { #(#create_stmts)* }
+            fn delete() { #(#delete_stmts)* }
+        });
+
+        assert!(generated.contains("membership.user_hash.clone()"));
+        assert!(generated.contains("LinkTypes::MembershipToUsers"));
+        assert!(generated.contains("membership.group_hash.clone()"));
+        assert!(generated.contains("LinkTypes::MembershipToGroups"));
+        assert!(generated.contains("original_membership_hash.clone(),"));
+        assert_eq!(
+            generated
+                .matches("delete_link(link.create_link_hash, GetOptions::default())?;")
+                .count(),
+            2
+        );
+    }
+}
@@ -0,0 +1,277 @@
+use std::{ffi::OsString, path::Path};
+
+use convert_case::{Case, Casing};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::parse_quote;
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::{file_exists, insert_file, map_file, map_rust_files, FileTree},
+    reserved_words::check_for_reserved_keywords,
+    templates::{remote_signal::scaffold_remote_signal_templates, ScaffoldedTemplate},
+};
+
+use super::{
+    app::AppFileTree,
+    dna::DnaFileTree,
+    entry_type::definitions::{EntryDefinition, FieldDefinition},
+    zome::{
+        coordinator::{add_stmt_to_init, find_extern_function_in_zome, item_ident},
+        ZomeFileTree,
+    },
+};
+
+/// Scaffolds a remote signal that the agents running a coordinator zome can send to each other:
+/// the `send_<name>` function, the `recv_remote_signal` extern re-emitting it to the UI along with
+/// the capability grant that lets other agents call it, a typed UI listener and a sweettest
+pub fn scaffold_remote_signal(
+    zome_file_tree: ZomeFileTree,
+    template_file_tree: &FileTree,
+    signal_name: &str,
+    payload_fields: &[FieldDefinition],
+    no_ui: bool,
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    check_for_reserved_keywords(signal_name)?;
+
+    let dna_manifest_path = zome_file_tree.dna_file_tree.dna_manifest_path.clone();
+    let zome_manifest = zome_file_tree.zome_manifest.clone();
+    let crate_src_path = zome_file_tree.zome_crate_path.join("src");
+
+    if find_extern_function_in_zome(&zome_file_tree, "recv_remote_signal")?.is_some()
+        && !file_exists(
+            zome_file_tree.dna_file_tree.file_tree_ref(),
+            &crate_src_path.join("remote_signals.rs"),
+        )
+    {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "zome {} already receives remote signals in its own recv_remote_signal function",
+            zome_manifest.name
+        )));
+    }
+
+    let variant_name = format_ident!("{}", signal_name.to_case(Case::Pascal));
+    let base_items: syn::File = syn::parse2(remote_signals_base_items())?;
+    let signal_items: syn::File = syn::parse2(remote_signal_items(signal_name, payload_fields))?;
+
+    let mut file_tree = zome_file_tree.dna_file_tree.file_tree();
+
+    // 1. The first remote signal of the zome creates the "remote_signals" module, with the
+    // extern that receives them and the grant that allows other agents to call it
+    let remote_signals_file_path = crate_src_path.join("remote_signals.rs");
+    let first_remote_signal = !file_exists(&file_tree, &remote_signals_file_path);
+    if first_remote_signal {
+        insert_file(
+            &mut file_tree,
+            &remote_signals_file_path,
+            "use hdk::prelude::*;",
+        )?;
+        map_file(&mut file_tree, &crate_src_path.join("lib.rs"), |contents| {
+            Ok(format!(
+                r#"pub mod remote_signals;
+{contents}"#
+            ))
+        })?;
+    }
+
+    let create_grant_stmt: syn::Stmt =
+        parse_quote! { crate::remote_signals::create_remote_signals_cap_grant()?; };
+
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+    map_rust_files(
+        file_tree
+            .path_mut(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        |file_path, mut file| {
+            if file_path == Path::new("remote_signals.rs") {
+                if first_remote_signal {
+                    file.items.extend(base_items.items.clone());
+                }
+                let Some(remote_signal_enum) = file.items.iter_mut().find_map(|i| match i {
+                    syn::Item::Enum(e) if e.ident == "RemoteSignal" => Some(e),
+                    _ => None,
+                }) else {
+                    return Err(ScaffoldError::MalformedFile(
+                        file_path.to_path_buf(),
+                        "the RemoteSignal enum was not found".to_string(),
+                    ));
+                };
+                if remote_signal_enum
+                    .variants
+                    .iter()
+                    .any(|v| v.ident == variant_name)
+                {
+                    return Err(ScaffoldError::InvalidArguments(format!(
+                        "remote signal {signal_name} already exists in the RemoteSignal enum"
+                    )));
+                }
+                let payload_name = format_ident!("{variant_name}Payload");
+                remote_signal_enum
+                    .variants
+                    .push(parse_quote! { #variant_name(#payload_name) });
+
+                // Keep the signal specific items before the receiving side of the module
+                let index = file
+                    .items
+                    .iter()
+                    .position(|i| {
+                        item_ident(i).is_some_and(|ident| ident == "RemoteSignalReceived")
+                    })
+                    .unwrap_or(file.items.len());
+                file.items.splice(index..index, signal_items.items.clone());
+            }
+            Ok(file)
+        },
+    )
+    .map_err(|e| match e {
+        ScaffoldError::MalformedFile(path, error) => {
+            ScaffoldError::MalformedFile(crate_src_path.join(path), error)
+        }
+        _ => e,
+    })?;
+
+    // 2. Other agents can only send remote signals once the grant has been created
+    if first_remote_signal {
+        add_stmt_to_init(&mut file_tree, &crate_src_path, &create_grant_stmt)?;
+    }
+
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    let dna_role_name = dna_file_tree.dna_manifest.name();
+
+    let app_file_tree = AppFileTree::get_or_choose(dna_file_tree.file_tree(), None)?;
+    let app_name = app_file_tree.app_manifest.app_name().to_string();
+
+    let payload_ts_definition = EntryDefinition {
+        name: format!("{}Payload", signal_name.to_case(Case::Pascal)),
+        fields: payload_fields.to_vec(),
+        reference_entry_hash: false,
+    }
+    .ts_type_codegen();
+
+    scaffold_remote_signal_templates(
+        app_file_tree.file_tree(),
+        template_file_tree,
+        &app_name,
+        &dna_role_name,
+        &zome_manifest,
+        signal_name,
+        payload_fields,
+        &payload_ts_definition,
+        no_ui,
+        no_spec,
+    )
+}
+
+fn remote_signals_base_items() -> TokenStream {
+    quote! {
+        /// Signals sent to the other agents running this zome
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+        #[serde(tag = "type")]
+        pub enum RemoteSignal {}
+
+        /// Emitted to the UI when a remote signal is received from another agent
+        #[derive(Serialize, Deserialize, Debug)]
+        #[serde(tag = "type")]
+        pub struct RemoteSignalReceived {
+            pub from: AgentPubKey,
+            pub signal: RemoteSignal,
+        }
+
+        #[hdk_extern]
+        pub fn recv_remote_signal(signal: RemoteSignal) -> ExternResult<()> {
+            let from = call_info()?.provenance;
+            emit_signal(RemoteSignalReceived { from, signal })
+        }
+
+        /// Called from `init` so that other agents can send remote signals to this one
+        pub fn create_remote_signals_cap_grant() -> ExternResult<ActionHash> {
+            let mut functions = HashSet::new();
+            functions.insert((zome_info()?.name, FunctionName::from("recv_remote_signal")));
+            create_cap_grant(CapGrantEntry {
+                tag: "remote_signals".to_string(),
+                access: CapAccess::Unrestricted,
+                functions: GrantedFunctions::Listed(functions),
+            })
+        }
+    }
+}
+
+fn remote_signal_items(signal_name: &str, payload_fields: &[FieldDefinition]) -> TokenStream {
+    let pascal_name = signal_name.to_case(Case::Pascal);
+    let variant_name = format_ident!("{pascal_name}");
+    let payload_name = format_ident!("{pascal_name}Payload");
+    let input_name = format_ident!("Send{pascal_name}Input");
+    let send_fn_name = format_ident!("send_{}", signal_name.to_case(Case::Snake));
+
+    let type_definitions: Vec<TokenStream> = payload_fields
+        .iter()
+        .filter_map(|f| f.field_type.rust_type_definition())
+        .collect();
+    let fields: Vec<TokenStream> = payload_fields
+        .iter()
+        .map(|f| {
+            let field_name = format_ident!("{}", f.field_name);
+            let rust_type = f.rust_type();
+            quote! { pub #field_name: #rust_type }
+        })
+        .collect();
+
+    quote! {
+        #(#type_definitions)*
+
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+        pub struct #payload_name {
+            #(#fields),*
+        }
+
+        #[derive(Serialize, Deserialize, Debug)]
+        pub struct #input_name {
+            pub agents: Vec<AgentPubKey>,
+            pub payload: #payload_name,
+        }
+
+        /// Sends the payload to the given agents, without waiting for them to receive it
+        #[hdk_extern]
+        pub fn #send_fn_name(input: #input_name) -> ExternResult<()> {
+            send_remote_signal(RemoteSignal::#variant_name(input.payload), input.agents)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::utils::unparse_pretty;
+
+    #[test]
+    fn remote_signal_is_sent_with_its_payload() {
+        let generated = unparse_pretty(
+            &syn::parse2(remote_signal_items(
+                "new_message",
+                &[FieldDefinition::from_str("content:String").unwrap()],
+            ))
+            .unwrap(),
+        );
+
+        assert!(generated.contains("pub struct NewMessagePayload {"));
+        assert!(generated.contains("pub content: String,"));
+        assert!(generated
+            .contains("pub fn send_new_message(input: SendNewMessageInput) -> ExternResult<()>"));
+        assert!(generated
+            .contains("send_remote_signal(RemoteSignal::NewMessage(input.payload), input.agents)"));
+    }
+
+    #[test]
+    fn received_remote_signals_are_emitted_to_the_ui() {
+        let generated = unparse_pretty(&syn::parse2(remote_signals_base_items()).unwrap());
+
+        assert!(generated.contains("pub fn recv_remote_signal(signal: RemoteSignal)"));
+        assert!(generated.contains("let from = call_info()?.provenance;"));
+        assert!(generated.contains(r#"FunctionName::from("recv_remote_signal")"#));
+        assert!(generated.contains("access: CapAccess::Unrestricted,"));
+        assert!(generated.contains("functions: GrantedFunctions::Listed(functions),"));
+    }
+}
@@ -0,0 +1,328 @@
+use std::{ffi::OsString, path::Path, time::Duration};
+
+use dialoguer::{theme::ColorfulTheme, Input, Select};
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+use serde::Serialize;
+use syn::parse_quote;
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::{file_exists, insert_file, map_file, map_rust_files, FileTree},
+    reserved_words::check_for_reserved_keywords,
+    templates::{scheduled::scaffold_scheduled_templates, ScaffoldedTemplate},
+};
+
+use super::{
+    app::AppFileTree,
+    dna::DnaFileTree,
+    zome::{
+        coordinator::{add_stmt_to_init, find_extern_function_in_zome},
+        ZomeFileTree,
+    },
+};
+
+/// When a scheduled function runs again after each of its runs
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ScheduleTrigger {
+    /// Ephemeral schedule, which doesn't survive a restart of the conductor
+    Every { seconds: u64 },
+    /// Persisted schedule, defined by a crontab expression with seconds
+    Cron { expression: String },
+}
+
+impl ScheduleTrigger {
+    pub fn from_args(
+        maybe_every: Option<&str>,
+        maybe_cron: Option<&str>,
+    ) -> ScaffoldResult<ScheduleTrigger> {
+        match (maybe_every, maybe_cron) {
+            (Some(_), Some(_)) => Err(ScaffoldError::InvalidArguments(
+                "a scheduled function runs either --every <duration> or on a --cron <expression>, not both"
+                    .to_string(),
+            )),
+            (Some(every), None) => Ok(ScheduleTrigger::Every {
+                seconds: parse_duration(every)?.as_secs(),
+            }),
+            (None, Some(cron)) => {
+                check_cron_expression(cron)?;
+                Ok(ScheduleTrigger::Cron {
+                    expression: cron.to_string(),
+                })
+            }
+            (None, None) => ScheduleTrigger::choose(),
+        }
+    }
+
+    pub fn choose() -> ScaffoldResult<ScheduleTrigger> {
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("When should the function run?")
+            .default(0)
+            .item("Every given duration, until the conductor restarts (ephemeral)")
+            .item("On a cron schedule, surviving conductor restarts (persisted)")
+            .interact()?;
+
+        if selection == 0 {
+            let every: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Duration between runs (eg. \"30s\", \"5m\", \"1h\"):")
+                .validate_with(|s: &String| parse_duration(s).map(|_| ()))
+                .interact_text()?;
+            ScheduleTrigger::from_args(Some(&every), None)
+        } else {
+            let cron: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Cron expression (eg. \"0 */5 * * * *\" for every 5 minutes):")
+                .validate_with(|s: &String| check_cron_expression(s))
+                .interact_text()?;
+            ScheduleTrigger::from_args(None, Some(&cron))
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            ScheduleTrigger::Every { seconds } => format!(
+                " Runs every {seconds} seconds, until the conductor restarts or it returns `None`"
+            ),
+            ScheduleTrigger::Cron { expression } => format!(
+                " Runs on the \"{expression}\" cron schedule, also after the conductor restarts"
+            ),
+        }
+    }
+}
+
+/// Parses durations like "45s", "5m", "1h" or "2d" into a whole number of seconds
+pub fn parse_duration(s: &str) -> ScaffoldResult<Duration> {
+    let invalid_duration = || {
+        ScaffoldError::InvalidArguments(format!(
+            "invalid duration \"{s}\": use a number of seconds, minutes, hours or days, eg. \"30s\", \"5m\", \"1h\", \"2d\""
+        ))
+    };
+
+    let unit_index = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid_duration)?;
+    let (amount, unit) = s.split_at(unit_index);
+    let amount: u64 = amount.parse().map_err(|_| invalid_duration())?;
+    let unit_seconds: u64 = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        _ => return Err(invalid_duration()),
+    };
+    let seconds = amount
+        .checked_mul(unit_seconds)
+        .ok_or_else(invalid_duration)?;
+    if seconds == 0 {
+        return Err(invalid_duration());
+    }
+
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Holochain's scheduler expects cron expressions with seconds, and optionally years
+fn check_cron_expression(s: &str) -> ScaffoldResult<()> {
+    let fields = s.split_whitespace().count();
+    if !(6..=7).contains(&fields) {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "invalid cron expression \"{s}\": expected 6 or 7 fields \"<sec> <min> <hour> <day of month> <month> <day of week> [<year>]\", eg. \"0 */5 * * * *\""
+        )));
+    }
+    Ok(())
+}
+
+/// Scaffolds an infallible scheduled function into a coordinator zome, schedules it in `init`,
+/// and adds a sweettest that runs the scheduler to observe it
+pub fn scaffold_scheduled(
+    zome_file_tree: ZomeFileTree,
+    template_file_tree: &FileTree,
+    function_name: &str,
+    trigger: &ScheduleTrigger,
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    check_for_reserved_keywords(function_name)?;
+
+    if find_extern_function_in_zome(&zome_file_tree, function_name)?.is_some() {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "function {function_name} already exists in zome {}",
+            zome_file_tree.zome_manifest.name
+        )));
+    }
+
+    let dna_manifest_path = zome_file_tree.dna_file_tree.dna_manifest_path.clone();
+    let zome_manifest = zome_file_tree.zome_manifest.clone();
+    let crate_src_path = zome_file_tree.zome_crate_path.join("src");
+
+    let base_items: syn::File = syn::parse2(scheduled_base_items())?;
+    let scheduled_items: syn::File = syn::parse2(scheduled_items(function_name, trigger))?;
+
+    let mut file_tree = zome_file_tree.dna_file_tree.file_tree();
+
+    // 1. Add the scheduled function to the "scheduled" module of the zome
+    let scheduled_file_path = crate_src_path.join("scheduled.rs");
+    let first_scheduled_function = !file_exists(&file_tree, &scheduled_file_path);
+    if first_scheduled_function {
+        insert_file(&mut file_tree, &scheduled_file_path, "use hdk::prelude::*;")?;
+        map_file(&mut file_tree, &crate_src_path.join("lib.rs"), |contents| {
+            Ok(format!(
+                r#"pub mod scheduled;
+{contents}"#
+            ))
+        })?;
+    }
+
+    let schedule_stmt: syn::Stmt = parse_quote! { schedule(#function_name)?; };
+
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+    map_rust_files(
+        file_tree
+            .path_mut(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        |file_path, mut file| {
+            if file_path == Path::new("scheduled.rs") {
+                if first_scheduled_function {
+                    file.items.extend(base_items.items.clone());
+                }
+                file.items.extend(scheduled_items.items.clone());
+            }
+            Ok(file)
+        },
+    )
+    .map_err(|e| match e {
+        ScaffoldError::MalformedFile(path, error) => {
+            ScaffoldError::MalformedFile(crate_src_path.join(path), error)
+        }
+        _ => e,
+    })?;
+
+    // 2. Scheduled functions are registered with the scheduler when the cell is initialized
+    add_stmt_to_init(&mut file_tree, &crate_src_path, &schedule_stmt)?;
+
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    let dna_role_name = dna_file_tree.dna_manifest.name();
+
+    let app_file_tree = AppFileTree::get_or_choose(dna_file_tree.file_tree(), None)?;
+    let app_name = app_file_tree.app_manifest.app_name().to_string();
+
+    scaffold_scheduled_templates(
+        app_file_tree.file_tree(),
+        template_file_tree,
+        &app_name,
+        &dna_role_name,
+        &zome_manifest,
+        function_name,
+        trigger,
+        no_spec,
+    )
+}
+
+fn scheduled_base_items() -> TokenStream {
+    quote! {
+        /// Emitted every time a scheduled function runs
+        #[derive(Serialize, Deserialize, Debug)]
+        #[serde(tag = "type")]
+        pub struct ScheduledFunctionRan {
+            pub function_name: String,
+        }
+    }
+}
+
+fn scheduled_items(function_name: &str, trigger: &ScheduleTrigger) -> TokenStream {
+    let function_ident = format_ident!("{function_name}");
+    let run_fn_ident = format_ident!("run_{function_name}");
+    let description = trigger.description();
+    let failed_message = format!("Scheduled function {function_name} failed: {{e:?}}");
+    let run_description =
+        format!(" The work done on each run of {function_name}, whose errors can only be logged");
+
+    let next_schedule = match trigger {
+        ScheduleTrigger::Every { seconds } => {
+            let seconds = Literal::u64_unsuffixed(*seconds);
+            quote! { Schedule::Ephemeral(std::time::Duration::from_secs(#seconds)) }
+        }
+        ScheduleTrigger::Cron { expression } => quote! {
+            Schedule::Persisted(#expression.to_string())
+        },
+    };
+
+    quote! {
+        #[doc = #description]
+        #[hdk_extern(infallible)]
+        pub fn #function_ident(_schedule: Option<Schedule>) -> Option<Schedule> {
+            if let Err(e) = #run_fn_ident() {
+                error!(#failed_message);
+            }
+            Some(#next_schedule)
+        }
+
+        #[doc = #run_description]
+        fn #run_fn_ident() -> ExternResult<()> {
+            emit_signal(ScheduledFunctionRan {
+                function_name: #function_name.to_string(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::unparse_pretty;
+
+    #[test]
+    fn durations_are_parsed_into_seconds() {
+        assert_eq!(parse_duration("45s").unwrap().as_secs(), 45);
+        assert_eq!(parse_duration("5m").unwrap().as_secs(), 300);
+        assert_eq!(parse_duration("2h").unwrap().as_secs(), 7200);
+        assert_eq!(parse_duration("1d").unwrap().as_secs(), 86400);
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("5").is_err());
+        assert!(parse_duration("5 weeks").is_err());
+    }
+
+    #[test]
+    fn durations_overflowing_the_seconds_are_invalid() {
+        assert!(parse_duration("999999999999999999d").is_err());
+        assert!(parse_duration("99999999999999999999s").is_err());
+    }
+
+    #[test]
+    fn cron_expressions_need_seconds() {
+        assert!(ScheduleTrigger::from_args(None, Some("0 */5 * * * *")).is_ok());
+        assert!(ScheduleTrigger::from_args(None, Some("*/5 * * * *")).is_err());
+        assert!(ScheduleTrigger::from_args(Some("5m"), Some("0 */5 * * * *")).is_err());
+    }
+
+    #[test]
+    fn ephemeral_scheduled_function() {
+        let generated = unparse_pretty(
+            &syn::parse2(scheduled_items(
+                "cleanup",
+                &ScheduleTrigger::Every { seconds: 30 },
+            ))
+            .unwrap(),
+        );
+
+        assert!(generated.contains("#[hdk_extern(infallible)]"));
+        assert!(
+            generated.contains("pub fn cleanup(_schedule: Option<Schedule>) -> Option<Schedule> {")
+        );
+        assert!(generated.contains("if let Err(e) = run_cleanup() {"));
+        assert!(generated.contains("Some(Schedule::Ephemeral(std::time::Duration::from_secs(30)))"));
+    }
+
+    #[test]
+    fn persisted_scheduled_function() {
+        let generated = unparse_pretty(
+            &syn::parse2(scheduled_items(
+                "daily_digest",
+                &ScheduleTrigger::Cron {
+                    expression: "0 0 8 * * *".to_string(),
+                },
+            ))
+            .unwrap(),
+        );
+
+        assert!(generated.contains(r#"Some(Schedule::Persisted("0 0 8 * * *".to_string()))"#));
+    }
+}
@@ -12,15 +12,21 @@ use crate::reserved_words::check_for_reserved_keywords;
 use crate::templates::web_app::scaffold_web_app_template;
 use crate::templates::ScaffoldedTemplate;
 use crate::{error::ScaffoldError, file_tree::FileTree};
+use template_manifest::TemplateVars;
 
 pub mod npm;
+pub mod template_hooks;
+pub mod template_manifest;
+pub mod template_source;
 pub mod template_type;
+pub mod template_upgrade;
 
 pub fn scaffold_web_app(
     app_name: &str,
     description: Option<&str>,
     skip_nix: bool,
     template_file_tree: &FileTree,
+    vars: &TemplateVars,
 ) -> ScaffoldResult<ScaffoldedTemplate> {
     check_for_reserved_keywords(app_name)?;
 
@@ -42,7 +48,7 @@ pub fn scaffold_web_app(
     }
 
     let scaffold_template_result =
-        scaffold_web_app_template(app_file_tree, template_file_tree, app_name)?;
+        scaffold_web_app_template(app_file_tree, template_file_tree, app_name, vars)?;
 
     Ok(scaffold_template_result)
 }
@@ -0,0 +1,158 @@
+use std::{
+    path::Path,
+    process::{Command, ExitStatus},
+};
+
+use colored::Colorize;
+use convert_case::{Case, Casing};
+
+use super::template_manifest::{TemplateHook, TemplateManifest, TemplateVars};
+
+/// Prefix of the environment variables passed to the hooks
+const ENV_PREFIX: &str = "HC_SCAFFOLD_";
+
+/// The hooks declared by the template in use for the command being run
+#[derive(Debug, Default)]
+pub struct TemplateHooks {
+    hooks: Vec<TemplateHook>,
+    env: Vec<(String, String)>,
+}
+
+impl TemplateHooks {
+    /// Gets the hooks the manifest declares for the given command, which get the command name and
+    /// the template variables in their environment
+    pub fn new(manifest: Option<&TemplateManifest>, command: &str, vars: &TemplateVars) -> Self {
+        let hooks = manifest
+            .and_then(|manifest| manifest.hooks.get(command))
+            .cloned()
+            .unwrap_or_default();
+
+        let mut env = vec![(format!("{ENV_PREFIX}COMMAND"), command.to_string())];
+        env.extend(vars.iter().map(|(name, value)| {
+            let value = match value {
+                serde_json::Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+            (
+                format!("{ENV_PREFIX}VAR_{}", name.to_case(Case::UpperSnake)),
+                value,
+            )
+        }));
+
+        TemplateHooks { hooks, env }
+    }
+
+    /// Hooks that don't run anything, used with `--no-hooks`
+    pub fn disabled() -> Self {
+        TemplateHooks::default()
+    }
+
+    /// Runs the hooks in order from the root of the hApp, passing the names of what was scaffolded
+    /// as `HC_SCAFFOLD_<NAME>` environment variables
+    ///
+    /// A failing hook is reported and the rest are skipped, as the files are already scaffolded
+    pub fn run(&self, app_dir: &Path, names: &[(&str, &str)]) {
+        for (i, hook) in self.hooks.iter().enumerate() {
+            let working_dir = match &hook.working_dir {
+                Some(dir) => app_dir.join(dir),
+                None => app_dir.to_path_buf(),
+            };
+            println!(
+                "\n{} {} (in {})",
+                "Running hook:".bold(),
+                hook.command.italic(),
+                working_dir.display()
+            );
+
+            let names = names
+                .iter()
+                .map(|(name, value)| (format!("{ENV_PREFIX}{name}"), value.to_string()));
+            let result = shell_command(&hook.command)
+                .current_dir(&working_dir)
+                .envs(self.env.iter().cloned().chain(names))
+                .status();
+
+            let error = match result {
+                Ok(status) if status.success() => {
+                    println!("{} {}", "Hook succeeded:".green(), hook.command.italic());
+                    continue;
+                }
+                Ok(status) => exit_status_message(status),
+                Err(e) => format!("could not be run: {e}"),
+            };
+            println!("{} {} {error}", "Hook failed:".red(), hook.command.italic());
+
+            let remaining = self.hooks.len() - i - 1;
+            if remaining > 0 {
+                println!(
+                    "{}",
+                    format!("Skipping the {remaining} remaining hook(s)").yellow()
+                );
+            }
+            return;
+        }
+    }
+}
+
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
+fn exit_status_message(status: ExitStatus) -> String {
+    match status.code() {
+        Some(code) => format!("exited with status {code}"),
+        None => "was terminated by a signal".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hooks_get_the_scaffolded_names_and_variables_in_their_environment() {
+        let app_dir = std::env::temp_dir().join("hc_scaffold_template_hooks_test");
+        let _ = std::fs::remove_dir_all(&app_dir);
+        std::fs::create_dir_all(app_dir.join("ui")).unwrap();
+
+        let manifest: TemplateManifest = toml::from_str(
+            r#"
+name = "t"
+hc-scaffold-version = "*"
+
+[[hooks.entry-type]]
+command = "echo $HC_SCAFFOLD_COMMAND $HC_SCAFFOLD_ENTRY_TYPE_NAME $HC_SCAFFOLD_VAR_PAGE_SIZE > hook.txt"
+working-dir = "ui"
+
+[[hooks.entry-type]]
+command = "exit 3"
+
+[[hooks.entry-type]]
+command = "touch skipped.txt"
+"#,
+        )
+        .unwrap();
+        let vars = TemplateVars::from([("page_size".to_string(), 10.into())]);
+
+        TemplateHooks::new(Some(&manifest), "entry-type", &vars)
+            .run(&app_dir, &[("ENTRY_TYPE_NAME", "post")]);
+
+        if cfg!(unix) {
+            assert_eq!(
+                std::fs::read_to_string(app_dir.join("ui/hook.txt")).unwrap(),
+                "entry-type post 10\n"
+            );
+            assert!(!app_dir.join("skipped.txt").exists());
+        }
+
+        std::fs::remove_dir_all(&app_dir).unwrap();
+    }
+}
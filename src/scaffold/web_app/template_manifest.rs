@@ -0,0 +1,582 @@
+use std::{collections::BTreeMap, ffi::OsString, path::PathBuf};
+
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::{file_content, file_exists, FileTree},
+    utils::{input_with_custom_validation, input_yes_or_no},
+};
+
+/// Name of the manifest file at the root of a template
+pub const TEMPLATE_MANIFEST_FILE: &str = "template.toml";
+
+/// Commands whose UI a template can implement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TemplateCommand {
+    EntryType,
+    LinkType,
+    Collection,
+    Example,
+}
+
+impl std::fmt::Display for TemplateCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            TemplateCommand::EntryType => "entry-type",
+            TemplateCommand::LinkType => "link-type",
+            TemplateCommand::Collection => "collection",
+            TemplateCommand::Example => "example",
+        };
+        write!(f, "{str}")
+    }
+}
+
+/// Values of the variables declared by a template, keyed by variable name
+pub type TemplateVars = BTreeMap<String, serde_json::Value>;
+
+/// Type of the values a template variable accepts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VariableType {
+    #[default]
+    String,
+    Bool,
+    Number,
+}
+
+impl VariableType {
+    /// Parses the given input into a value of this type
+    pub fn parse(&self, input: &str) -> Result<serde_json::Value, String> {
+        match self {
+            VariableType::String => Ok(serde_json::Value::String(input.to_string())),
+            VariableType::Bool => input
+                .parse::<bool>()
+                .map(serde_json::Value::Bool)
+                .map_err(|_| format!("\"{input}\" is not a bool, expected true or false")),
+            VariableType::Number => input
+                .parse::<i64>()
+                .map(serde_json::Value::from)
+                .or_else(|_| input.parse::<f64>().map(serde_json::Value::from))
+                .map_err(|_| format!("\"{input}\" is not a number")),
+        }
+    }
+
+    fn matches(&self, value: &serde_json::Value) -> bool {
+        match self {
+            VariableType::String => value.is_string(),
+            VariableType::Bool => value.is_boolean(),
+            VariableType::Number => value.is_number(),
+        }
+    }
+}
+
+/// An extra input that a template needs, asked for when running a command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateVariable {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub variable_type: VariableType,
+    /// Text shown when prompting for the value, defaults to the name of the variable
+    pub prompt: Option<String>,
+    pub default: Option<serde_json::Value>,
+}
+
+/// A shell command to run after a command has scaffolded its files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TemplateHook {
+    pub command: String,
+    /// Directory to run the command in, relative to the root of the hApp
+    pub working_dir: Option<PathBuf>,
+}
+
+/// The `template.toml` manifest describing a template
+///
+/// ```toml
+/// name = "svelte"
+/// hc-scaffold-version = "0.700"
+/// commands = ["entry-type", "link-type", "collection", "example"]
+///
+/// [widgets]
+/// String = ["TextArea", "TextField"]
+///
+/// [[variables.web-app]]
+/// name = "analytics"
+/// type = "bool"
+/// prompt = "Do you want to enable analytics?"
+/// default = false
+///
+/// [[hooks.entry-type]]
+/// command = "npm run lint -- --fix"
+/// working-dir = "ui"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TemplateManifest {
+    pub name: String,
+    /// Built-in template name, or path relative to this template, of the template this one extends
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// Range of `hc-scaffold` versions this template can be used with
+    pub hc_scaffold_version: VersionReq,
+    /// Commands this template implements
+    #[serde(default)]
+    pub commands: Vec<TemplateCommand>,
+    /// Widgets this template provides, keyed by field type
+    #[serde(default)]
+    pub widgets: BTreeMap<String, Vec<String>>,
+    /// Extra variables this template asks for, keyed by the command that asks for them
+    #[serde(default)]
+    pub variables: BTreeMap<String, Vec<TemplateVariable>>,
+    /// Commands to run after scaffolding, keyed by the command that runs them
+    #[serde(default)]
+    pub hooks: BTreeMap<String, Vec<TemplateHook>>,
+}
+
+impl TemplateManifest {
+    /// Reads the manifest at the root of the given template, if there is one
+    pub fn from_template_file_tree(template_file_tree: &FileTree) -> ScaffoldResult<Option<Self>> {
+        let manifest_path = PathBuf::from(TEMPLATE_MANIFEST_FILE);
+        if !file_exists(template_file_tree, &manifest_path) {
+            return Ok(None);
+        }
+
+        let contents = file_content(template_file_tree, &manifest_path)?;
+        let manifest = toml::from_str(&contents).map_err(|e| {
+            ScaffoldError::MalformedTemplate(format!(
+                "Invalid {TEMPLATE_MANIFEST_FILE} manifest: {e}"
+            ))
+        })?;
+
+        Ok(Some(manifest))
+    }
+
+    /// Reads the `extends` key of the manifest at the root of the given template, if there is one
+    ///
+    /// Only this key is read, as the rest of the manifest may be inherited from the extended template
+    pub fn extended_template(template_file_tree: &FileTree) -> ScaffoldResult<Option<String>> {
+        let manifest_path = PathBuf::from(TEMPLATE_MANIFEST_FILE);
+        if !file_exists(template_file_tree, &manifest_path) {
+            return Ok(None);
+        }
+
+        let manifest = parse_manifest_table(&file_content(template_file_tree, &manifest_path)?)?;
+        match manifest.get("extends") {
+            None => Ok(None),
+            Some(toml::Value::String(extends)) => Ok(Some(extends.clone())),
+            Some(_) => Err(ScaffoldError::MalformedTemplate(format!(
+                "Invalid {TEMPLATE_MANIFEST_FILE} manifest: \"extends\" must be a string"
+            ))),
+        }
+    }
+
+    /// Layers the manifest of a template over the manifest of the template it extends
+    ///
+    /// Keys of the extending manifest take precedence, and `widgets` are overridden per field type.
+    /// The `extends` key is dropped, as the result describes the already layered template, and no
+    /// manifest is returned if nothing else is left.
+    pub fn layer(base_manifest: Option<&str>, manifest: &str) -> ScaffoldResult<Option<String>> {
+        let mut layered = match base_manifest {
+            Some(base_manifest) => parse_manifest_table(base_manifest)?,
+            None => toml::Table::new(),
+        };
+
+        for (key, value) in parse_manifest_table(manifest)? {
+            match (layered.get_mut(&key), value) {
+                (Some(toml::Value::Table(base_widgets)), toml::Value::Table(widgets))
+                    if key == "widgets" =>
+                {
+                    base_widgets.extend(widgets);
+                }
+                (_, value) => {
+                    layered.insert(key, value);
+                }
+            }
+        }
+        layered.remove("extends");
+
+        if layered.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(toml::to_string(&layered)?))
+    }
+
+    /// Sets the widgets declared for a field type in the given manifest, keeping the rest of it
+    /// untouched, comments and formatting included
+    pub fn declare_widgets(
+        manifest: &str,
+        field_type: &str,
+        widgets: &[String],
+    ) -> ScaffoldResult<String> {
+        let mut manifest: toml_edit::DocumentMut = manifest.parse().map_err(|e| {
+            ScaffoldError::MalformedTemplate(format!(
+                "Invalid {TEMPLATE_MANIFEST_FILE} manifest: {e}"
+            ))
+        })?;
+        let declared_widgets = manifest
+            .entry("widgets")
+            .or_insert_with(toml_edit::table)
+            .as_table_like_mut()
+            .ok_or(ScaffoldError::MalformedTemplate(format!(
+                "Invalid {TEMPLATE_MANIFEST_FILE} manifest: \"widgets\" must be a table"
+            )))?;
+        declared_widgets.insert(
+            field_type,
+            toml_edit::value(
+                widgets
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<toml_edit::Array>(),
+            ),
+        );
+        Ok(manifest.to_string())
+    }
+
+    /// Checks that the running version of `hc-scaffold` is within the supported version range
+    pub fn check_compatibility(&self) -> ScaffoldResult<()> {
+        let current_version = Version::parse(env!("CARGO_PKG_VERSION"))
+            .expect("CARGO_PKG_VERSION is a valid semver version");
+
+        if !self.hc_scaffold_version.matches(&current_version) {
+            return Err(ScaffoldError::IncompatibleTemplate(
+                self.name.clone(),
+                format!(
+                    "it supports hc-scaffold versions \"{}\", but this is version {current_version}",
+                    self.hc_scaffold_version
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that this template implements the given command
+    pub fn check_command(&self, command: TemplateCommand) -> ScaffoldResult<()> {
+        if !self.commands.contains(&command) {
+            return Err(ScaffoldError::IncompatibleTemplate(
+                self.name.clone(),
+                format!("it does not implement the \"{command}\" command"),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Sets the variables declared for the given command in `vars`
+    ///
+    /// Values assigned in `assigned` take precedence, variables already in `vars` are kept, and the
+    /// user is prompted for the rest
+    pub fn resolve_variables(
+        &self,
+        command: &str,
+        assigned: &[(String, String)],
+        vars: &mut TemplateVars,
+    ) -> ScaffoldResult<()> {
+        let declared = self
+            .variables
+            .get(command)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        for (name, input) in assigned {
+            let variable = declared.iter().find(|v| &v.name == name).ok_or_else(|| {
+                ScaffoldError::InvalidArguments(format!(
+                    "Template \"{}\" does not declare a \"{name}\" variable for the \"{command}\" command",
+                    self.name
+                ))
+            })?;
+            let value = variable.variable_type.parse(input).map_err(|e| {
+                ScaffoldError::InvalidArguments(format!("Invalid value for \"{name}\": {e}"))
+            })?;
+            vars.insert(name.clone(), value);
+        }
+
+        for variable in declared {
+            if !vars.contains_key(&variable.name) {
+                vars.insert(variable.name.clone(), variable.prompt_value()?);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the default values of the variables declared for all commands, using empty values for
+    /// the ones without a default
+    pub fn default_variables(&self) -> TemplateVars {
+        self.variables
+            .values()
+            .flatten()
+            .map(|variable| {
+                let value = variable
+                    .default
+                    .clone()
+                    .filter(|default| variable.variable_type.matches(default))
+                    .unwrap_or_else(|| match variable.variable_type {
+                        VariableType::String => serde_json::Value::String(String::new()),
+                        VariableType::Bool => serde_json::Value::Bool(false),
+                        VariableType::Number => serde_json::Value::from(0),
+                    });
+                (variable.name.clone(), value)
+            })
+            .collect()
+    }
+
+    /// Removes from the given `field-types` templates all the widgets that are not declared in this manifest
+    pub fn retain_declared_widgets(&self, field_types_templates: &mut FileTree) {
+        let Some(field_types) = field_types_templates.dir_content_mut() else {
+            return;
+        };
+
+        for (field_type, field_type_templates) in field_types.iter_mut() {
+            let declared_widgets = field_type
+                .to_str()
+                .and_then(|field_type| self.widgets.get(field_type));
+
+            if let Some(widgets) = field_type_templates.dir_content_mut() {
+                widgets.retain(|name: &OsString, template| {
+                    template.dir_content().is_none()
+                        || declared_widgets.is_some_and(|declared| {
+                            declared.iter().any(|w| name.to_str() == Some(w.as_str()))
+                        })
+                });
+            }
+        }
+    }
+}
+
+impl TemplateVariable {
+    fn prompt_value(&self) -> ScaffoldResult<serde_json::Value> {
+        let prompt = self
+            .prompt
+            .clone()
+            .unwrap_or_else(|| format!("{}:", self.name));
+        let default = self
+            .default
+            .as_ref()
+            .filter(|default| self.variable_type.matches(default));
+
+        if self.variable_type == VariableType::Bool {
+            let value = input_yes_or_no(&prompt, default.and_then(|d| d.as_bool()))?;
+            return Ok(serde_json::Value::Bool(value));
+        }
+
+        let initial_text = match default {
+            Some(serde_json::Value::String(default)) => Some(default.clone()),
+            Some(default) => Some(default.to_string()),
+            None => None,
+        };
+        let input = input_with_custom_validation(&prompt, initial_text.as_deref(), |input| {
+            self.variable_type.parse(&input).map(|_| ())
+        })?;
+
+        Ok(self
+            .variable_type
+            .parse(&input)
+            .expect("input was already validated"))
+    }
+}
+
+fn parse_manifest_table(contents: &str) -> ScaffoldResult<toml::Table> {
+    contents.parse().map_err(|e| {
+        ScaffoldError::MalformedTemplate(format!("Invalid {TEMPLATE_MANIFEST_FILE} manifest: {e}"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaffold::web_app::template_type::TemplateType;
+    use build_fs_tree::{dir, file};
+
+    #[test]
+    fn built_in_templates_are_compatible_with_this_version() {
+        for template_type in [TemplateType::Svelte, TemplateType::Headless] {
+            let manifest =
+                TemplateManifest::from_template_file_tree(&template_type.file_tree().unwrap())
+                    .unwrap()
+                    .expect("built-in templates have a manifest");
+            manifest.check_compatibility().unwrap();
+        }
+    }
+
+    #[test]
+    fn incompatible_version_and_missing_command_are_rejected() {
+        let template = dir! {
+            "template.toml" => file!(r#"
+name = "old-template"
+hc-scaffold-version = "^0.1"
+commands = ["entry-type"]
+"#)
+        };
+        let manifest = TemplateManifest::from_template_file_tree(&template)
+            .unwrap()
+            .unwrap();
+
+        assert!(manifest.check_compatibility().is_err());
+        assert!(manifest.check_command(TemplateCommand::EntryType).is_ok());
+        assert!(manifest.check_command(TemplateCommand::Collection).is_err());
+    }
+
+    #[test]
+    fn only_declared_widgets_are_retained() {
+        let manifest = TemplateManifest {
+            name: "t".to_string(),
+            extends: None,
+            hc_scaffold_version: VersionReq::STAR,
+            commands: vec![],
+            widgets: BTreeMap::from([("String".to_string(), vec!["TextArea".to_string()])]),
+            variables: BTreeMap::new(),
+            hooks: BTreeMap::new(),
+        };
+        let mut field_types = dir! {
+            "String" => dir! {
+                "type.hbs" => file!("string"),
+                "TextArea" => dir! {},
+                "TextField" => dir! {}
+            },
+            "bool" => dir! {
+                "type.hbs" => file!("boolean"),
+                "Checkbox" => dir! {}
+            }
+        };
+
+        manifest.retain_declared_widgets(&mut field_types);
+
+        assert_eq!(
+            field_types,
+            dir! {
+                "String" => dir! {
+                    "type.hbs" => file!("string"),
+                    "TextArea" => dir! {}
+                },
+                "bool" => dir! {
+                    "type.hbs" => file!("boolean")
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn extending_manifest_overrides_keys_and_widgets_per_field_type() {
+        let base = r#"
+name = "svelte"
+hc-scaffold-version = "0.700"
+commands = ["entry-type", "link-type"]
+
+[widgets]
+String = ["TextArea", "TextField"]
+bool = ["Checkbox"]
+"#;
+        let manifest = r#"
+name = "my-svelte"
+extends = "svelte"
+
+[widgets]
+String = ["Markdown"]
+"#;
+
+        let layered = TemplateManifest::layer(Some(base), manifest)
+            .unwrap()
+            .unwrap();
+        let layered: TemplateManifest = toml::from_str(&layered).unwrap();
+
+        assert_eq!(layered.name, "my-svelte");
+        assert_eq!(layered.extends, None);
+        assert_eq!(
+            layered.commands,
+            vec![TemplateCommand::EntryType, TemplateCommand::LinkType]
+        );
+        assert_eq!(
+            layered.widgets,
+            BTreeMap::from([
+                ("String".to_string(), vec!["Markdown".to_string()]),
+                ("bool".to_string(), vec!["Checkbox".to_string()]),
+            ])
+        );
+    }
+
+    #[test]
+    fn declaring_widgets_keeps_the_comments_of_the_manifest() {
+        let manifest = r#"# My template
+name = "my-template"
+
+[widgets]
+# Rich text editing
+String = ["TextArea"]
+"#;
+
+        let declared = TemplateManifest::declare_widgets(
+            manifest,
+            "Timestamp",
+            &["DateTimePicker".to_string()],
+        )
+        .unwrap();
+
+        assert!(declared.starts_with("# My template\nname = \"my-template\"\n"));
+        assert!(declared.contains("# Rich text editing\nString = [\"TextArea\"]\n"));
+        assert!(declared.contains("Timestamp = [\"DateTimePicker\"]"));
+
+        let redeclared =
+            TemplateManifest::declare_widgets(&declared, "String", &["TextField".to_string()])
+                .unwrap();
+        assert!(redeclared.contains("String = [\"TextField\"]"));
+        assert!(redeclared.contains("# My template"));
+    }
+
+    #[test]
+    fn assigned_and_existing_variables_are_resolved_without_prompting() {
+        let manifest: TemplateManifest = toml::from_str(
+            r#"
+name = "t"
+hc-scaffold-version = "*"
+
+[[variables.web-app]]
+name = "analytics"
+type = "bool"
+
+[[variables.web-app]]
+name = "theme"
+default = "light"
+
+[[variables.entry-type]]
+name = "page-size"
+type = "number"
+"#,
+        )
+        .unwrap();
+
+        let mut vars = TemplateVars::from([("theme".to_string(), "dark".into())]);
+        manifest
+            .resolve_variables(
+                "web-app",
+                &[("analytics".to_string(), "true".to_string())],
+                &mut vars,
+            )
+            .unwrap();
+        assert_eq!(
+            vars,
+            TemplateVars::from([
+                ("analytics".to_string(), true.into()),
+                ("theme".to_string(), "dark".into()),
+            ])
+        );
+
+        assert!(manifest
+            .resolve_variables(
+                "entry-type",
+                &[("page-size".to_string(), "many".to_string())],
+                &mut vars,
+            )
+            .is_err());
+        assert!(manifest
+            .resolve_variables(
+                "web-app",
+                &[("page-size".to_string(), "10".to_string())],
+                &mut vars,
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,350 @@
+use flate2::read::GzDecoder;
+use git2::{build::CheckoutBuilder, Direction, Oid, Repository};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use crate::error::{ScaffoldError, ScaffoldResult};
+
+/// Commits git templates were resolved to, keyed by their spec (`git+<url>#<ref>`)
+pub type TemplateCommits = BTreeMap<String, String>;
+
+/// A template living in a git repository, given as `git+<url>#<ref>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitTemplate {
+    pub url: String,
+    /// Branch, tag or commit to use, the default branch of the repository if none
+    pub reference: Option<String>,
+    /// The commit the reference was resolved to
+    pub commit: Option<String>,
+    /// The commits of the git templates it extends, directly or through other templates
+    pub base_commits: TemplateCommits,
+}
+
+impl GitTemplate {
+    /// Parses a `git+<url>#<ref>` template, returning `None` if the value is not one
+    pub fn parse(value: &str) -> Option<GitTemplate> {
+        let value = value.strip_prefix("git+")?;
+        let (url, reference) = match value.split_once('#') {
+            Some((url, reference)) => (url, Some(reference.to_string())),
+            None => (value, None),
+        };
+        Some(GitTemplate {
+            url: url.to_string(),
+            reference: reference.filter(|r| !r.is_empty()),
+            commit: None,
+            base_commits: TemplateCommits::new(),
+        })
+    }
+
+    /// The value the template was given with, without its resolved commit
+    pub fn spec(&self) -> String {
+        match &self.reference {
+            Some(reference) => format!("git+{}#{reference}", self.url),
+            None => format!("git+{}", self.url),
+        }
+    }
+
+    /// The value to get this exact template again, regardless of where its reference moves to
+    pub fn pinned_spec(&self) -> String {
+        match &self.commit {
+            Some(commit) => format!("git+{}#{commit}", self.url),
+            None => self.spec(),
+        }
+    }
+
+    /// Resolves the reference to a commit, fetching the repository if there isn't one yet
+    /// or if `update` is set
+    pub fn pin(mut self, cache_dir: &Path, update: bool) -> ScaffoldResult<GitTemplate> {
+        if self.commit.is_none() || update {
+            let (repo, default_branch) = self.fetch(cache_dir)?;
+            let reference = self.reference.clone().or(default_branch).ok_or_else(|| {
+                ScaffoldError::MalformedTemplate(format!(
+                    "Could not find the default branch of {}, add a #<branch> to the template",
+                    self.url
+                ))
+            })?;
+            self.commit = Some(resolve_reference(&repo, &reference)?.to_string());
+        }
+        Ok(self)
+    }
+
+    /// Checks out the pinned commit of the template into the cache, returning its directory
+    pub fn checkout(&self, cache_dir: &Path) -> ScaffoldResult<PathBuf> {
+        let template = match self.commit {
+            Some(_) => self.clone(),
+            None => self.clone().pin(cache_dir, false)?,
+        };
+        let commit = template.commit.as_deref().unwrap_or_default();
+        let checkout_dir = self.repo_cache_dir(cache_dir).join(commit);
+        if checkout_dir.exists() {
+            return Ok(checkout_dir);
+        }
+
+        let oid = Oid::from_str(commit)?;
+        let repo = match Repository::open_bare(self.repo_cache_dir(cache_dir).join("repo")) {
+            Ok(repo) if repo.find_commit(oid).is_ok() => repo,
+            _ => self.fetch(cache_dir)?.0,
+        };
+        let commit = repo.find_commit(oid).map_err(|_| {
+            ScaffoldError::MalformedTemplate(format!(
+                "Commit {commit} was not found in {}",
+                self.url
+            ))
+        })?;
+
+        // Checked out next to its final location so that an interrupted checkout is not reused
+        let partial_dir = checkout_dir.with_extension("partial");
+        if partial_dir.exists() {
+            fs::remove_dir_all(&partial_dir)?;
+        }
+        fs::create_dir_all(&partial_dir)?;
+        repo.checkout_tree(
+            commit.as_object(),
+            Some(CheckoutBuilder::new().target_dir(&partial_dir).force()),
+        )?;
+        fs::rename(&partial_dir, &checkout_dir)?;
+
+        Ok(checkout_dir)
+    }
+
+    /// Fetches all branches and tags of the repository into its bare clone in the cache,
+    /// returning it along with the name of the remote's default branch
+    fn fetch(&self, cache_dir: &Path) -> ScaffoldResult<(Repository, Option<String>)> {
+        let repo_dir = self.repo_cache_dir(cache_dir).join("repo");
+        let repo = match Repository::open_bare(&repo_dir) {
+            Ok(repo) => repo,
+            Err(_) => {
+                fs::create_dir_all(&repo_dir)?;
+                Repository::init_bare(&repo_dir)?
+            }
+        };
+
+        let default_branch = {
+            let mut remote = repo.remote_anonymous(&self.url)?;
+            remote.connect(Direction::Fetch)?;
+            let default_branch = remote.default_branch().ok().and_then(|branch| {
+                branch
+                    .as_str()
+                    .and_then(|b| b.strip_prefix("refs/heads/"))
+                    .map(String::from)
+            });
+            remote.disconnect()?;
+            remote.fetch(
+                &[
+                    "+refs/heads/*:refs/remotes/origin/*",
+                    "+refs/tags/*:refs/tags/*",
+                ],
+                None,
+                None,
+            )?;
+            default_branch
+        };
+
+        Ok((repo, default_branch))
+    }
+
+    fn repo_cache_dir(&self, cache_dir: &Path) -> PathBuf {
+        cache_dir.join("git").join(cache_key(&self.url))
+    }
+}
+
+/// Whether the given value points to a gzipped tarball
+pub fn is_tarball(value: &str) -> bool {
+    value.ends_with(".tar.gz") || value.ends_with(".tgz")
+}
+
+/// Extracts the tarball into the cache, returning the directory of the template it contains
+///
+/// The extraction is reused until the size or modification time of the tarball changes
+pub fn extract_tarball(path: &Path, cache_dir: &Path) -> ScaffoldResult<PathBuf> {
+    let canonical_path = path.canonicalize()?;
+    let metadata = fs::metadata(&canonical_path)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let tarball_dir = cache_dir
+        .join("tarballs")
+        .join(cache_key(&canonical_path.to_string_lossy()));
+    let extract_dir = tarball_dir.join(format!("{}-{modified}", metadata.len()));
+
+    if !extract_dir.exists() {
+        // Extractions of previous versions of the tarball are outdated
+        if tarball_dir.exists() {
+            fs::remove_dir_all(&tarball_dir)?;
+        }
+        // Extracted next to its final location so that an interrupted extraction is not reused
+        let partial_dir = extract_dir.with_extension("partial");
+        fs::create_dir_all(&partial_dir)?;
+        tar::Archive::new(GzDecoder::new(fs::File::open(&canonical_path)?)).unpack(&partial_dir)?;
+        fs::rename(&partial_dir, &extract_dir)?;
+    }
+
+    // Tarballs are usually created from the template directory itself
+    let entries = fs::read_dir(&extract_dir)?.collect::<Result<Vec<_>, _>>()?;
+    match entries.as_slice() {
+        [entry] if entry.file_type()?.is_dir() => Ok(entry.path()),
+        _ => Ok(extract_dir),
+    }
+}
+
+/// The directory where remote templates are cached, in the user's data directory
+pub fn template_cache_dir() -> ScaffoldResult<PathBuf> {
+    let data_dir = dirs::data_dir().ok_or_else(|| {
+        ScaffoldError::MalformedTemplate(
+            "Could not find the user's data directory to cache the template in".to_string(),
+        )
+    })?;
+    Ok(data_dir.join("hc-scaffold").join("templates"))
+}
+
+fn resolve_reference(repo: &Repository, reference: &str) -> ScaffoldResult<Oid> {
+    for name in [
+        format!("refs/remotes/origin/{reference}"),
+        format!("refs/tags/{reference}"),
+    ] {
+        if let Ok(reference) = repo.find_reference(&name) {
+            return Ok(reference.peel_to_commit()?.id());
+        }
+    }
+    let commit = repo
+        .revparse_single(reference)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|_| {
+            ScaffoldError::MalformedTemplate(format!(
+                "No branch, tag or commit named \"{reference}\" was found in the template repository"
+            ))
+        })?;
+    Ok(commit.id())
+}
+
+fn cache_key(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+
+    fn commit_file(repo: &Repository, content: &str) -> Oid {
+        let workdir = repo.workdir().unwrap();
+        fs::write(workdir.join("file.hbs"), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("file.hbs")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        let parents = repo
+            .head()
+            .ok()
+            .map(|head| head.peel_to_commit().unwrap())
+            .into_iter()
+            .collect::<Vec<_>>();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            content,
+            &tree,
+            &parents.iter().collect::<Vec<_>>(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn git_templates_are_pinned_to_a_commit() {
+        let test_dir = std::env::temp_dir().join("hc_scaffold_git_template_test");
+        let _ = fs::remove_dir_all(&test_dir);
+        let cache_dir = test_dir.join("cache");
+        let repo = Repository::init(test_dir.join("repo")).unwrap();
+
+        let first = commit_file(&repo, "first");
+        repo.tag_lightweight("v1", &repo.find_object(first, None).unwrap(), false)
+            .unwrap();
+        let url = format!("git+file://{}", test_dir.join("repo").display());
+
+        let tagged = GitTemplate::parse(&format!("{url}#v1"))
+            .unwrap()
+            .pin(&cache_dir, false)
+            .unwrap();
+        assert_eq!(tagged.commit, Some(first.to_string()));
+        let checkout = tagged.checkout(&cache_dir).unwrap();
+        assert_eq!(
+            fs::read_to_string(checkout.join("file.hbs")).unwrap(),
+            "first"
+        );
+
+        let branch = GitTemplate::parse(&url)
+            .unwrap()
+            .pin(&cache_dir, false)
+            .unwrap();
+        assert_eq!(branch.commit, Some(first.to_string()));
+
+        let second = commit_file(&repo, "second");
+        let branch = branch.pin(&cache_dir, false).unwrap();
+        assert_eq!(branch.commit, Some(first.to_string()));
+        let branch = branch.pin(&cache_dir, true).unwrap();
+        assert_eq!(branch.commit, Some(second.to_string()));
+        let checkout = branch.checkout(&cache_dir).unwrap();
+        assert_eq!(
+            fs::read_to_string(checkout.join("file.hbs")).unwrap(),
+            "second"
+        );
+
+        fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[test]
+    fn tarballs_are_extracted_into_the_cache_once() {
+        let test_dir = std::env::temp_dir().join("hc_scaffold_tarball_template_test");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(test_dir.join("my-template/web-app")).unwrap();
+        let tarball_path = test_dir.join("my-template.tar.gz");
+        let cache_dir = test_dir.join("cache");
+
+        let write_tarball = |readme: &str| {
+            fs::write(test_dir.join("my-template/web-app/README.md.hbs"), readme).unwrap();
+            let encoder = flate2::write::GzEncoder::new(
+                fs::File::create(&tarball_path).unwrap(),
+                flate2::Compression::default(),
+            );
+            let mut builder = tar::Builder::new(encoder);
+            builder
+                .append_dir_all("my-template", test_dir.join("my-template"))
+                .unwrap();
+            builder.into_inner().unwrap().finish().unwrap();
+        };
+
+        write_tarball("readme");
+        assert!(is_tarball(&tarball_path.to_string_lossy()));
+        let template_dir = extract_tarball(&tarball_path, &cache_dir).unwrap();
+        assert_eq!(
+            fs::read_to_string(template_dir.join("web-app/README.md.hbs")).unwrap(),
+            "readme"
+        );
+
+        // An unchanged tarball is not extracted again
+        fs::write(template_dir.join("marker"), "").unwrap();
+        let template_dir = extract_tarball(&tarball_path, &cache_dir).unwrap();
+        assert!(template_dir.join("marker").exists());
+
+        write_tarball("updated readme");
+        let template_dir = extract_tarball(&tarball_path, &cache_dir).unwrap();
+        assert!(!template_dir.join("marker").exists());
+        assert_eq!(
+            fs::read_to_string(template_dir.join("web-app/README.md.hbs")).unwrap(),
+            "updated readme"
+        );
+
+        fs::remove_dir_all(&test_dir).unwrap();
+    }
+}
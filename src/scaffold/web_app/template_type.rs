@@ -2,12 +2,21 @@ use colored::Colorize;
 use dialoguer::{theme::ColorfulTheme, Select};
 use include_dir::{include_dir, Dir};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::{ffi::OsString, path::PathBuf, str::FromStr};
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
+use super::template_manifest::{TemplateCommand, TemplateManifest, TEMPLATE_MANIFEST_FILE};
+use super::template_source::{
+    extract_tarball, is_tarball, template_cache_dir, GitTemplate, TemplateCommits,
+};
 use crate::{
     error::{ScaffoldError, ScaffoldResult},
     file_tree::{
-        file_content, file_exists, load_directory_into_memory, template_dirs_to_file_tree, FileTree,
+        file_content, file_exists, flatten_file_tree, load_directory_into_memory,
+        template_dirs_to_file_tree, unflatten_file_tree, FileTree,
     },
 };
 
@@ -22,6 +31,8 @@ pub enum TemplateType {
     Svelte,
     Headless,
     Custom(PathBuf),
+    Git(GitTemplate),
+    Tarball(PathBuf),
 }
 
 impl TemplateType {
@@ -30,19 +41,73 @@ impl TemplateType {
         let name = match self {
             TemplateType::Svelte => "svelte",
             TemplateType::Headless => "headless",
-            TemplateType::Custom(path) => return format!("{path:?}"),
+            TemplateType::Custom(path) | TemplateType::Tarball(path) => return format!("{path:?}"),
+            TemplateType::Git(git) => return git.spec(),
         };
         name.to_string()
     }
 
+    /// Parses a template given as `git+<url>#<ref>` or as a path to a `.tar.gz` tarball
+    fn parse_remote(value: &str) -> Option<TemplateType> {
+        if let Some(git) = GitTemplate::parse(value) {
+            return Some(TemplateType::Git(git));
+        }
+        (is_tarball(value) && PathBuf::from(value).is_file())
+            .then(|| TemplateType::Tarball(value.into()))
+    }
+
+    /// Gets the commit a git template is pinned to
+    pub fn commit(&self) -> Option<&str> {
+        match self {
+            TemplateType::Git(git) => git.commit.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Gets the commits the git templates extended by a git template are pinned to
+    pub fn base_commits(&self) -> TemplateCommits {
+        match self {
+            TemplateType::Git(git) => git.base_commits.clone(),
+            _ => TemplateCommits::new(),
+        }
+    }
+
+    /// Pins a git template to the given commit, and the git templates it extends to the given
+    /// base commits, resolving their references again where there is no commit or if `update`
+    /// is set. Other templates are returned as they are
+    pub fn pin(
+        self,
+        commit: Option<String>,
+        base_commits: TemplateCommits,
+        update: bool,
+    ) -> ScaffoldResult<TemplateType> {
+        match self {
+            TemplateType::Git(mut git) => {
+                git.commit = commit;
+                git.base_commits = base_commits;
+                Ok(TemplateType::Git(pin_git_template(
+                    git,
+                    update,
+                    &template_cache_dir()?,
+                )?))
+            }
+            template_type => Ok(template_type),
+        }
+    }
+
     pub fn check_valid_template(&self) -> ScaffoldResult<()> {
-        if file_content(&self.file_tree()?, &PathBuf::from("web-app/README.md.hbs")).is_err() {
+        let file_tree = self.file_tree()?;
+        if file_content(&file_tree, &PathBuf::from("web-app/README.md.hbs")).is_err() {
             Err(ScaffoldError::MalformedTemplate(
                 "Template does not contain a README.md.hbs file in its \"web-app\" directory"
                     .to_string(),
             ))?;
         }
 
+        if let Some(manifest) = TemplateManifest::from_template_file_tree(&file_tree)? {
+            manifest.check_compatibility()?;
+        }
+
         Ok(())
     }
 
@@ -50,11 +115,36 @@ impl TemplateType {
         let ui_framework_dir = match self {
             TemplateType::Svelte => &SVELTE_TEMPLATES,
             TemplateType::Headless => &HEADLESS_TEMPLATE,
-            TemplateType::Custom(path) => return load_directory_into_memory(path),
+            TemplateType::Custom(path) => {
+                let pinned = TemplateCommits::new();
+                return custom_template_file_tree(path, &mut Layering::new(&pinned, None));
+            }
+            TemplateType::Git(git) => {
+                return Ok(git_template_file_tree(git, &template_cache_dir()?)?.0)
+            }
+            TemplateType::Tarball(path) => {
+                let cache_dir = template_cache_dir()?;
+                let pinned = TemplateCommits::new();
+                let mut layering = Layering::new(&pinned, Some(cache_dir.clone()));
+                layering.extending.push(layer_key(path)?);
+                let path = extract_tarball(path, &cache_dir)?;
+                return custom_template_file_tree(&path, &mut layering);
+            }
         };
         template_dirs_to_file_tree(ui_framework_dir, &GENERIC_TEMPLATES)
     }
 
+    /// Gets the file tree of the template, checking first that its manifest (if any) declares
+    /// support for this version of hc-scaffold and for the given command
+    pub fn file_tree_for_command(&self, command: TemplateCommand) -> ScaffoldResult<FileTree> {
+        let file_tree = self.file_tree()?;
+        if let Some(manifest) = TemplateManifest::from_template_file_tree(&file_tree)? {
+            manifest.check_compatibility()?;
+            manifest.check_command(command)?;
+        }
+        Ok(file_tree)
+    }
+
     pub fn choose() -> ScaffoldResult<TemplateType> {
         let frameworks = [TemplateType::Svelte, TemplateType::Headless];
         let selection = Select::with_theme(&ColorfulTheme::default())
@@ -74,6 +164,145 @@ impl TemplateType {
     }
 }
 
+/// State of the loading of a template layered over the templates it extends
+struct Layering<'a> {
+    /// The templates extending the one being loaded, by canonical path or git spec, to detect
+    /// cycles
+    extending: Vec<String>,
+    /// Commits to use for the extended git templates, which are resolved again if missing
+    pinned: &'a TemplateCommits,
+    /// Commits the extended git templates were resolved to
+    resolved: TemplateCommits,
+    /// Where remote templates are cached, the user's data directory if none
+    cache_dir: Option<PathBuf>,
+}
+
+impl<'a> Layering<'a> {
+    fn new(pinned: &'a TemplateCommits, cache_dir: Option<PathBuf>) -> Self {
+        Layering {
+            extending: Vec::new(),
+            pinned,
+            resolved: TemplateCommits::new(),
+            cache_dir,
+        }
+    }
+
+    fn cache_dir(&self) -> ScaffoldResult<PathBuf> {
+        match &self.cache_dir {
+            Some(cache_dir) => Ok(cache_dir.clone()),
+            None => template_cache_dir(),
+        }
+    }
+
+    /// Adds a template to the chain of templates being extended, failing if it's already in it
+    fn enter(&mut self, key: String, path: &Path, extends: &str) -> ScaffoldResult<()> {
+        if self.extending.contains(&key) {
+            return Err(ScaffoldError::MalformedTemplate(format!(
+                "Template {path:?} extends itself through \"{extends}\""
+            )));
+        }
+        self.extending.push(key);
+        Ok(())
+    }
+}
+
+/// Identifies a local template or tarball in the chain of templates being extended
+fn layer_key(path: &Path) -> ScaffoldResult<String> {
+    Ok(path.canonicalize()?.to_string_lossy().into_owned())
+}
+
+/// Pins a git template and the git templates it extends, keeping the commits they are already
+/// pinned to unless `update` is set
+fn pin_git_template(
+    git: GitTemplate,
+    update: bool,
+    cache_dir: &Path,
+) -> ScaffoldResult<GitTemplate> {
+    let mut git = git.pin(cache_dir, update)?;
+    if update {
+        git.base_commits.clear();
+    }
+    let (_, base_commits) = git_template_file_tree(&git, cache_dir)?;
+    git.base_commits = base_commits;
+    Ok(git)
+}
+
+/// Loads a git template at its pinned commit, returning it along with the commits the git
+/// templates it extends were resolved to
+fn git_template_file_tree(
+    git: &GitTemplate,
+    cache_dir: &Path,
+) -> ScaffoldResult<(FileTree, TemplateCommits)> {
+    let path = git.checkout(cache_dir)?;
+    let mut layering = Layering::new(&git.base_commits, Some(cache_dir.to_path_buf()));
+    layering.extending.push(git.spec());
+    let file_tree = custom_template_file_tree(&path, &mut layering)?;
+    Ok((file_tree, layering.resolved))
+}
+
+/// Loads the custom template at the given path, layered over the template it extends (if any)
+fn custom_template_file_tree(path: &Path, layering: &mut Layering) -> ScaffoldResult<FileTree> {
+    let template_file_tree = load_directory_into_memory(path)?;
+
+    let Some(extends) = TemplateManifest::extended_template(&template_file_tree)? else {
+        return Ok(template_file_tree);
+    };
+    layering.enter(layer_key(path)?, path, &extends)?;
+
+    let base_file_tree = match extends.as_str() {
+        "svelte" => TemplateType::Svelte.file_tree()?,
+        "headless" => TemplateType::Headless.file_tree()?,
+        base => match GitTemplate::parse(base) {
+            Some(mut git) => {
+                layering.enter(git.spec(), path, &extends)?;
+                let cache_dir = layering.cache_dir()?;
+                git.commit = layering.pinned.get(&git.spec()).cloned();
+                let git = git.pin(&cache_dir, false)?;
+                if let Some(commit) = &git.commit {
+                    layering.resolved.insert(git.spec(), commit.clone());
+                }
+                custom_template_file_tree(&git.checkout(&cache_dir)?, layering)?
+            }
+            None if is_tarball(base) => {
+                let tarball_path = path.join(base);
+                layering.enter(layer_key(&tarball_path)?, path, &extends)?;
+                let base_path = extract_tarball(&tarball_path, &layering.cache_dir()?)?;
+                custom_template_file_tree(&base_path, layering)?
+            }
+            None => {
+                let base_path = path.join(base);
+                if !base_path.is_dir() {
+                    return Err(ScaffoldError::MalformedTemplate(format!(
+                        "Template {path:?} extends \"{extends}\", which is neither a built-in template nor an existing directory"
+                    )));
+                }
+                custom_template_file_tree(&base_path, layering)?
+            }
+        },
+    };
+
+    layer_template(&base_file_tree, &template_file_tree)
+}
+
+/// Merges the files of a template over the ones of the template it extends
+fn layer_template(
+    base_file_tree: &FileTree,
+    template_file_tree: &FileTree,
+) -> ScaffoldResult<FileTree> {
+    let manifest_path = PathBuf::from(TEMPLATE_MANIFEST_FILE);
+    let base_manifest = file_content(base_file_tree, &manifest_path).ok();
+    let manifest = file_content(template_file_tree, &manifest_path)?;
+
+    let mut layered = flatten_file_tree(base_file_tree);
+    layered.extend(flatten_file_tree(template_file_tree));
+    match TemplateManifest::layer(base_manifest.as_deref(), &manifest)? {
+        Some(manifest) => layered.insert(manifest_path, Some(manifest)),
+        None => layered.remove(&manifest_path),
+    };
+
+    unflatten_file_tree(&layered)
+}
+
 impl From<PathBuf> for TemplateType {
     fn from(path: PathBuf) -> Self {
         TemplateType::Custom(path)
@@ -111,7 +340,8 @@ impl std::fmt::Display for TemplateType {
         let str = match self {
             TemplateType::Svelte => "svelte".bright_red(),
             TemplateType::Headless => "headless (no ui)".italic(),
-            TemplateType::Custom(path) => format!("{path:?}").white(),
+            TemplateType::Custom(path) | TemplateType::Tarball(path) => format!("{path:?}").white(),
+            TemplateType::Git(git) => git.spec().white(),
         };
         write!(f, "{str}")
     }
@@ -121,6 +351,9 @@ impl FromStr for TemplateType {
     type Err = ScaffoldError;
 
     fn from_str(s: &str) -> ScaffoldResult<TemplateType> {
+        if let Some(template_type) = TemplateType::parse_remote(s) {
+            return Ok(template_type);
+        }
         match s.to_ascii_lowercase().as_str() {
             "svelte" => Ok(TemplateType::Svelte),
             "headless" => Ok(TemplateType::Headless),
@@ -128,7 +361,7 @@ impl FromStr for TemplateType {
                 Ok(TemplateType::Custom(path_str.into()))
             }
             value => Err(ScaffoldError::MalformedTemplate(format!(
-                "Invalid value: {value}, expected svelte, headless or a valid/ existing file path"
+                "Invalid value: {value}, expected svelte, headless, git+<url>#<ref> or a valid/ existing file path"
             ))),
         }
     }
@@ -142,7 +375,8 @@ impl Serialize for TemplateType {
         match self {
             TemplateType::Svelte => serializer.serialize_str("svelte"),
             TemplateType::Headless => serializer.serialize_str("headless"),
-            TemplateType::Custom(path) => path
+            TemplateType::Git(git) => serializer.serialize_str(&git.spec()),
+            TemplateType::Custom(path) | TemplateType::Tarball(path) => path
                 .to_str()
                 .ok_or_else(|| serde::ser::Error::custom("Invalid UTF-8 in path"))
                 .and_then(|s| serializer.serialize_str(s)),
@@ -156,6 +390,9 @@ impl<'de> Deserialize<'de> for TemplateType {
         D: Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
+        if let Some(template_type) = TemplateType::parse_remote(&s) {
+            return Ok(template_type);
+        }
         match s.as_str() {
             "svelte" => Ok(TemplateType::Svelte),
             "headless" => Ok(TemplateType::Headless),
@@ -163,8 +400,190 @@ impl<'de> Deserialize<'de> for TemplateType {
                 Ok(TemplateType::Custom(path_str.into()))
             }
             value => Err(serde::de::Error::custom(format!(
-                "Invalid value: {value}, expected svelte, headless or a valid/ existing file path"
+                "Invalid value: {value}, expected svelte, headless, git+<url>#<ref> or a valid/ existing file path"
             ))),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_tree::build_file_tree;
+    use build_fs_tree::{dir, file};
+    use git2::{IndexAddOption, Repository, Signature};
+
+    /// Writes the files in the work tree of the repository and commits them
+    fn commit_template(repo: &Repository, files: FileTree) -> String {
+        build_file_tree(files, repo.workdir().unwrap()).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], IndexAddOption::DEFAULT, None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        let parents = repo
+            .head()
+            .ok()
+            .map(|head| head.peel_to_commit().unwrap())
+            .into_iter()
+            .collect::<Vec<_>>();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "commit",
+            &tree,
+            &parents.iter().collect::<Vec<_>>(),
+        )
+        .unwrap()
+        .to_string()
+    }
+
+    #[test]
+    fn extending_templates_are_layered_in_order() {
+        let templates_dir = std::env::temp_dir().join("hc_scaffold_layered_templates_test");
+        let _ = std::fs::remove_dir_all(&templates_dir);
+        build_file_tree(
+            dir! {
+                "base" => dir! {
+                    "template.toml" => file!(r#"
+name = "base"
+extends = "svelte"
+"#),
+                    "web-app" => dir! {
+                        "README.md.hbs" => file!("base readme")
+                    },
+                    "base-only.hbs" => file!("base")
+                },
+                "custom" => dir! {
+                    "template.toml" => file!(r#"
+name = "custom"
+extends = "../base"
+"#),
+                    "base-only.hbs" => file!("custom")
+                }
+            },
+            &templates_dir,
+        )
+        .unwrap();
+
+        let template_type = TemplateType::Custom(templates_dir.join("custom"));
+        let file_tree = template_type.file_tree().unwrap();
+        template_type.check_valid_template().unwrap();
+
+        let manifest = TemplateManifest::from_template_file_tree(&file_tree)
+            .unwrap()
+            .unwrap();
+        assert_eq!(manifest.name, "custom");
+        assert_eq!(manifest.extends, None);
+        assert!(manifest.check_command(TemplateCommand::Collection).is_ok());
+
+        let content = |path: &str| file_content(&file_tree, &PathBuf::from(path)).unwrap();
+        assert_eq!(content("web-app/README.md.hbs"), "base readme");
+        assert_eq!(content("base-only.hbs"), "custom");
+        assert_eq!(
+            content("field-types/String/type.hbs"),
+            file_content(
+                &TemplateType::Svelte.file_tree().unwrap(),
+                &PathBuf::from("field-types/String/type.hbs")
+            )
+            .unwrap()
+        );
+
+        std::fs::remove_dir_all(&templates_dir).unwrap();
+    }
+
+    #[test]
+    fn templates_extending_themselves_are_rejected() {
+        let templates_dir = std::env::temp_dir().join("hc_scaffold_cyclic_templates_test");
+        let _ = std::fs::remove_dir_all(&templates_dir);
+        build_file_tree(
+            dir! {
+                "a" => dir! {
+                    "template.toml" => file!(r#"extends = "../b""#)
+                },
+                "b" => dir! {
+                    "template.toml" => file!(r#"extends = "../a""#)
+                }
+            },
+            &templates_dir,
+        )
+        .unwrap();
+
+        assert!(TemplateType::Custom(templates_dir.join("a"))
+            .file_tree()
+            .is_err());
+
+        std::fs::remove_dir_all(&templates_dir).unwrap();
+    }
+
+    #[test]
+    fn git_templates_extending_themselves_are_rejected() {
+        let test_dir = std::env::temp_dir().join("hc_scaffold_cyclic_git_templates_test");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        let url = |name: &str| format!("git+file://{}", test_dir.join(name).display());
+
+        for (name, extends) in [("a", "b"), ("b", "a")] {
+            let repo = Repository::init(test_dir.join(name)).unwrap();
+            commit_template(
+                &repo,
+                dir! {
+                    "template.toml" => file!(format!("extends = \"{}\"", url(extends)))
+                },
+            );
+        }
+
+        let result = pin_git_template(
+            GitTemplate::parse(&url("a")).unwrap(),
+            false,
+            &test_dir.join("cache"),
+        );
+        assert!(
+            matches!(&result, Err(ScaffoldError::MalformedTemplate(e)) if e.contains("extends itself")),
+            "{result:?}"
+        );
+
+        std::fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[test]
+    fn git_templates_extended_by_git_templates_are_pinned() {
+        let test_dir = std::env::temp_dir().join("hc_scaffold_pinned_git_bases_test");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        let cache_dir = test_dir.join("cache");
+        let base_url = format!("git+file://{}", test_dir.join("base").display());
+
+        let base_repo = Repository::init(test_dir.join("base")).unwrap();
+        let first = commit_template(&base_repo, dir! { "base.hbs" => file!("first") });
+        let repo = Repository::init(test_dir.join("custom")).unwrap();
+        commit_template(
+            &repo,
+            dir! {
+                "template.toml" => file!(format!("extends = \"{base_url}\""))
+            },
+        );
+        let url = format!("git+file://{}", test_dir.join("custom").display());
+
+        let pinned =
+            pin_git_template(GitTemplate::parse(&url).unwrap(), false, &cache_dir).unwrap();
+        assert_eq!(
+            pinned.base_commits,
+            TemplateCommits::from([(base_url.clone(), first.clone())])
+        );
+
+        let second = commit_template(&base_repo, dir! { "base.hbs" => file!("second") });
+        let content = |git: &GitTemplate| {
+            let (file_tree, _) = git_template_file_tree(git, &cache_dir).unwrap();
+            file_content(&file_tree, Path::new("base.hbs")).unwrap()
+        };
+        assert_eq!(content(&pinned), "first");
+        let pinned = pin_git_template(pinned, false, &cache_dir).unwrap();
+        assert_eq!(pinned.base_commits[&base_url], first);
+
+        let updated = pin_git_template(pinned, true, &cache_dir).unwrap();
+        assert_eq!(updated.base_commits[&base_url], second);
+        assert_eq!(content(&updated), "second");
+
+        std::fs::remove_dir_all(&test_dir).unwrap();
+    }
+}
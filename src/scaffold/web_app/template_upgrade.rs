@@ -0,0 +1,312 @@
+use build_fs_tree::dir;
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::{create_dir_all, file_content, flatten_file_tree, insert_file, FileTree},
+    templates::{web_app::scaffold_web_app_template, ScaffoldedTemplate},
+};
+
+use super::template_manifest::TemplateVars;
+
+/// Where the files rendered from the `web-app` folder of the template are stored in the hApp,
+/// to be the base of the three-way merge of the next upgrade
+pub const WEB_APP_RENDER_FILE: &str = ".hc-scaffold/web-app.json";
+
+/// Files rendered from the `web-app` folder of a template, keyed by their path in the hApp
+pub type RenderedFiles = BTreeMap<PathBuf, String>;
+
+/// What an upgrade did to a file of the hApp
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpgradedFile {
+    /// The file is new in the template
+    Added,
+    /// The changes of the template were merged into the file
+    Updated,
+    /// The changes of the template overlap with the changes made to the file, and the given
+    /// number of conflicts were marked in it
+    Conflicted(usize),
+    /// The file was removed from the template and hadn't been changed
+    Removed,
+    /// The file was removed from the template, but was kept as it had been changed
+    Kept,
+}
+
+/// Renders the `web-app` folder of the template on its own, without the rest of the hApp
+pub fn render_web_app(
+    template_file_tree: &FileTree,
+    app_name: &str,
+    vars: &TemplateVars,
+) -> ScaffoldResult<RenderedFiles> {
+    let ScaffoldedTemplate { file_tree, .. } =
+        scaffold_web_app_template(dir! {}, template_file_tree, app_name, vars)?;
+    Ok(flatten_file_tree(&file_tree)
+        .into_iter()
+        .filter_map(|(path, content)| Some((path, content?)))
+        .collect())
+}
+
+/// Gets the render stored in the hApp by the last `web-app` or `template upgrade` command
+pub fn stored_web_app_render(app_file_tree: &FileTree) -> ScaffoldResult<Option<RenderedFiles>> {
+    match file_content(app_file_tree, Path::new(WEB_APP_RENDER_FILE)) {
+        Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+        Err(_) => Ok(None),
+    }
+}
+
+pub fn store_web_app_render(
+    app_file_tree: &mut FileTree,
+    render: &RenderedFiles,
+) -> ScaffoldResult<()> {
+    let path = PathBuf::from(WEB_APP_RENDER_FILE);
+    if let Some(dir) = path.parent() {
+        create_dir_all(app_file_tree, dir)?;
+    }
+    insert_file(app_file_tree, &path, &serde_json::to_string_pretty(render)?)
+}
+
+/// Applies the changes between the `base` render of the template and its new `render` to the
+/// files of the hApp, and stores the new render as the base for the next upgrade
+///
+/// Without a base render, the lines that the files and the new render have in common are
+/// taken as the base, so that every difference between them is marked as a conflict.
+/// Removed files are only taken out of the file tree, it's up to the caller to delete them.
+pub fn upgrade_web_app(
+    app_file_tree: &mut FileTree,
+    base: Option<&RenderedFiles>,
+    render: &RenderedFiles,
+) -> ScaffoldResult<BTreeMap<PathBuf, UpgradedFile>> {
+    let mut upgraded_files = BTreeMap::new();
+
+    for (path, new_content) in render {
+        let base_content = base.and_then(|base| base.get(path));
+        let upgraded = match file_content(app_file_tree, path) {
+            Ok(current_content) => {
+                let (merged, conflicts) = merge_three_way(
+                    base_content.map(|b| b.as_str()),
+                    &current_content,
+                    new_content,
+                );
+                if merged == current_content {
+                    continue;
+                }
+                insert_file_creating_dirs(app_file_tree, path, &merged)?;
+                match conflicts {
+                    0 => UpgradedFile::Updated,
+                    conflicts => UpgradedFile::Conflicted(conflicts),
+                }
+            }
+            // Files of the previous render that are missing were deleted on purpose
+            Err(_) if base_content.is_some() => continue,
+            Err(_) => {
+                insert_file_creating_dirs(app_file_tree, path, new_content)?;
+                UpgradedFile::Added
+            }
+        };
+        upgraded_files.insert(path.clone(), upgraded);
+    }
+
+    for (path, base_content) in base.into_iter().flatten() {
+        if render.contains_key(path) {
+            continue;
+        }
+        let Ok(current_content) = file_content(app_file_tree, path) else {
+            continue;
+        };
+        let upgraded = if &current_content == base_content {
+            remove_file(app_file_tree, path)?;
+            UpgradedFile::Removed
+        } else {
+            UpgradedFile::Kept
+        };
+        upgraded_files.insert(path.clone(), upgraded);
+    }
+
+    store_web_app_render(app_file_tree, render)?;
+
+    Ok(upgraded_files)
+}
+
+fn insert_file_creating_dirs(
+    file_tree: &mut FileTree,
+    path: &Path,
+    content: &str,
+) -> ScaffoldResult<()> {
+    if let Some(dir) = path.parent() {
+        create_dir_all(file_tree, dir)?;
+    }
+    insert_file(file_tree, path, content)
+}
+
+fn remove_file(file_tree: &mut FileTree, path: &Path) -> ScaffoldResult<()> {
+    let mut folder_path = path.to_path_buf();
+    folder_path.pop();
+    let v: Vec<OsString> = folder_path.iter().map(|s| s.to_os_string()).collect();
+    file_tree
+        .path_mut(&mut v.iter())
+        .and_then(|folder| folder.dir_content_mut())
+        .ok_or(ScaffoldError::PathNotFound(folder_path))?
+        .remove(path.file_name().unwrap_or_default());
+    Ok(())
+}
+
+/// Merges the changes from `base` to `theirs` into `ours`, line by line, returning the merged
+/// content and the number of places where both changed the same lines
+///
+/// Conflicting changes are written between conflict markers, with the current content first.
+/// Without a base, the lines `ours` and `theirs` have in common are used instead.
+pub fn merge_three_way(base: Option<&str>, ours: &str, theirs: &str) -> (String, usize) {
+    let ours: Vec<&str> = ours.split_inclusive('\n').collect();
+    let theirs: Vec<&str> = theirs.split_inclusive('\n').collect();
+    let base: Vec<&str> = match base {
+        Some(base) => base.split_inclusive('\n').collect(),
+        None => diff::slice(&ours, &theirs)
+            .into_iter()
+            .filter_map(|line| match line {
+                diff::Result::Both(line, _) => Some(*line),
+                _ => None,
+            })
+            .collect(),
+    };
+
+    let in_ours = matching_lines(&base, &ours);
+    let in_theirs = matching_lines(&base, &theirs);
+
+    let mut merged = String::new();
+    let mut conflicts = 0;
+    let (mut b, mut o, mut t) = (0, 0, 0);
+    loop {
+        // The next base line that is unchanged in both, along with its position in each
+        let stable = (b..base.len()).find_map(|i| Some((i, in_ours[i]?, in_theirs[i]?)));
+        let (next_b, next_o, next_t) = stable.unwrap_or((base.len(), ours.len(), theirs.len()));
+
+        let (base_chunk, ours_chunk, theirs_chunk) =
+            (&base[b..next_b], &ours[o..next_o], &theirs[t..next_t]);
+        if ours_chunk == base_chunk {
+            merged.extend(theirs_chunk.iter().copied());
+        } else if theirs_chunk == base_chunk || ours_chunk == theirs_chunk {
+            merged.extend(ours_chunk.iter().copied());
+        } else {
+            conflicts += 1;
+            merged.push_str("<<<<<<< current\n");
+            push_lines(&mut merged, ours_chunk);
+            merged.push_str("=======\n");
+            push_lines(&mut merged, theirs_chunk);
+            merged.push_str(">>>>>>> template\n");
+        }
+
+        let Some((stable_b, stable_o, stable_t)) = stable else {
+            break;
+        };
+        merged.push_str(base[stable_b]);
+        (b, o, t) = (stable_b + 1, stable_o + 1, stable_t + 1);
+    }
+
+    (merged, conflicts)
+}
+
+/// For each line of `base`, gets the position of the same line in `other` if it was kept
+fn matching_lines(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let mut matching = vec![None; base.len()];
+    let (mut b, mut o) = (0, 0);
+    for line in diff::slice(base, other) {
+        match line {
+            diff::Result::Left(_) => b += 1,
+            diff::Result::Right(_) => o += 1,
+            diff::Result::Both(_, _) => {
+                matching[b] = Some(o);
+                b += 1;
+                o += 1;
+            }
+        }
+    }
+    matching
+}
+
+/// Pushes the lines, making sure they end with a new line so that a conflict marker can follow
+fn push_lines(merged: &mut String, lines: &[&str]) {
+    merged.extend(lines.iter().copied());
+    if !merged.is_empty() && !merged.ends_with('\n') {
+        merged.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use build_fs_tree::file;
+
+    #[test]
+    fn changes_are_merged_and_overlapping_changes_conflict() {
+        let base = "a\nb\nc\nd\ne\n";
+        let ours = "a\nb (ours)\nc\nd\ne\nf (ours)\n";
+
+        let theirs = "a\nb\nc\nd (theirs)\ne\n";
+        assert_eq!(
+            merge_three_way(Some(base), ours, theirs),
+            ("a\nb (ours)\nc\nd (theirs)\ne\nf (ours)\n".to_string(), 0)
+        );
+
+        let theirs = "a\nb (theirs)\nc\nd\ne\n";
+        assert_eq!(
+            merge_three_way(Some(base), ours, theirs),
+            (
+                "a\n<<<<<<< current\nb (ours)\n=======\nb (theirs)\n>>>>>>> template\nc\nd\ne\nf (ours)\n"
+                    .to_string(),
+                1
+            )
+        );
+
+        assert_eq!(
+            merge_three_way(None, "a\nb\nc", "a\nB\nc"),
+            (
+                "a\n<<<<<<< current\nb\n=======\nB\n>>>>>>> template\nc".to_string(),
+                1
+            )
+        );
+    }
+
+    #[test]
+    fn web_app_files_are_upgraded() {
+        let base = RenderedFiles::from([
+            ("unchanged".into(), "base\n".to_string()),
+            ("changed".into(), "a\nb\nc\n".to_string()),
+            ("deleted".into(), "base\n".to_string()),
+            ("removed".into(), "base\n".to_string()),
+            ("kept".into(), "base\n".to_string()),
+        ]);
+        let mut app_file_tree = dir! {
+            "unchanged" => file!("base\n"),
+            "changed" => file!("a (ours)\nb\nc\n"),
+            "removed" => file!("base\n"),
+            "kept" => file!("ours\n"),
+        };
+        let render = RenderedFiles::from([
+            ("unchanged".into(), "base\n".to_string()),
+            ("changed".into(), "a\nb\nc (theirs)\n".to_string()),
+            ("deleted".into(), "theirs\n".to_string()),
+            ("ui/added".into(), "theirs\n".to_string()),
+        ]);
+
+        let upgraded_files = upgrade_web_app(&mut app_file_tree, Some(&base), &render).unwrap();
+
+        assert_eq!(
+            upgraded_files,
+            BTreeMap::from([
+                ("changed".into(), UpgradedFile::Updated),
+                ("kept".into(), UpgradedFile::Kept),
+                ("removed".into(), UpgradedFile::Removed),
+                ("ui/added".into(), UpgradedFile::Added),
+            ])
+        );
+        let content = |path: &str| file_content(&app_file_tree, Path::new(path)).ok();
+        assert_eq!(content("changed").unwrap(), "a (ours)\nb\nc (theirs)\n");
+        assert_eq!(content("deleted"), None);
+        assert_eq!(content("removed"), None);
+        assert_eq!(stored_web_app_render(&app_file_tree).unwrap(), Some(render));
+    }
+}
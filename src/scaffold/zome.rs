@@ -7,7 +7,7 @@ use std::{
 };
 
 use crate::{
-    file_tree::{build_file_tree, file_exists, insert_file_tree_in_dir, FileTree},
+    file_tree::{file_exists, insert_file_tree_in_dir, FileTree},
     reserved_words::check_for_reserved_keywords,
     scaffold::app::cargo::add_workspace_dependency,
     templates::{
@@ -32,7 +32,7 @@ pub mod utils;
 use super::{
     app::cargo::{
         add_workspace_external_dependency, add_workspace_path_dependency, get_workspace_members,
-        get_workspace_packages_locations, workspace_package_path,
+        get_workspace_packages_locations, workspace_package_path, workspace_path_dependency,
     },
     dna::{
         coordinator::{add_coordinator_zome_to_manifest, new_coordinator_zome_manifest},
@@ -78,6 +78,35 @@ impl ZomeFileTree {
         ZomeFileTree::from_zome_manifest(dna_file_tree, zome_manifest)
     }
 
+    pub fn get_or_choose_coordinator(
+        dna_file_tree: DnaFileTree,
+        coordinator_zome_name: Option<&str>,
+    ) -> ScaffoldResult<ZomeFileTree> {
+        let coordinator_zomes = match dna_file_tree.dna_manifest.clone() {
+            DnaManifest::V0(v0) => v0.coordinator.zomes.clone(),
+        };
+
+        let zome_manifest = match (coordinator_zomes.len(), coordinator_zome_name) {
+            (0, None) => Err(ScaffoldError::NoCoordinatorZomesFound(
+                dna_file_tree.dna_manifest.name(),
+            )),
+            (1, None) => coordinator_zomes.into_iter().next_back().ok_or(
+                ScaffoldError::NoCoordinatorZomesFound(dna_file_tree.dna_manifest.name()),
+            ),
+            (_, None) => {
+                choose_coordinator_zome(&dna_file_tree.dna_manifest.name(), &coordinator_zomes)
+            }
+            (_, Some(name)) => coordinator_zomes
+                .into_iter()
+                .find(|zome| zome.name.0.to_string().eq(name))
+                .ok_or(ScaffoldError::CoordinatorZomeNotFound(
+                    name.to_owned(),
+                    dna_file_tree.dna_manifest.name(),
+                )),
+        }?;
+        ZomeFileTree::from_zome_manifest(dna_file_tree, zome_manifest)
+    }
+
     pub fn from_zome_manifest(
         dna_file_tree: DnaFileTree,
         zome_manifest: ZomeManifest,
@@ -111,6 +140,13 @@ fn zome_crate_path(
 
     let crate_name = file_name.split(".wasm").next().unwrap().to_string();
 
+    // Zomes scaffolded in this same run are not on disk yet for `cargo metadata` to find them
+    if let Some(path) = workspace_path_dependency(dna_file_tree.file_tree_ref(), &crate_name)? {
+        if file_exists(dna_file_tree.file_tree_ref(), &path.join("Cargo.toml")) {
+            return Ok(path);
+        }
+    }
+
     let mut manifest_path = workspace_package_path(dna_file_tree.file_tree_ref(), &crate_name)?
         .ok_or(ScaffoldError::IntegrityZomeNotFound(
             zome_manifest.name.0.to_string(),
@@ -152,6 +188,26 @@ fn choose_integrity_zome(
     Ok(integrity_zomes[selection].clone())
 }
 
+fn choose_coordinator_zome(
+    dna_name: &str,
+    coordinator_zomes: &[ZomeManifest],
+) -> ScaffoldResult<ZomeManifest> {
+    let coordinator_zome_names: Vec<String> = coordinator_zomes
+        .iter()
+        .map(|z| z.name.0.to_string())
+        .collect();
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!(
+            "Multiple coordinator zomes were found in DNA {dna_name}, choose one:",
+        ))
+        .default(0)
+        .items(&coordinator_zome_names[..])
+        .interact()?;
+
+    Ok(coordinator_zomes[selection].clone())
+}
+
 /// Tries to guess the location of the integrity zomes
 ///
 /// Procedure:
@@ -506,24 +562,30 @@ pub fn scaffold_coordinator_zome(
     )
 }
 
+/// Scaffolds a coordinator zome and its integrity zome, prompting for the name of the
+/// coordinator zome if it's not given
 pub fn scaffold_zome_pair(
     app_file_tree: FileTree,
-    template_file_tree: FileTree,
+    template_file_tree: &FileTree,
     dna_name: &str,
-) -> Result<(), ScaffoldError> {
+    zome_name: Option<&str>,
+) -> ScaffoldResult<FileTree> {
     let mut dna_file_tree = DnaFileTree::get_or_choose(app_file_tree, Some(dna_name))?;
     let dna_manifest_path = dna_file_tree.dna_manifest_path.clone();
 
-    let zome_name = input_with_case(
+    let zome_name = match zome_name {
+        Some(zome_name) => zome_name.to_string(),
+        None => input_with_case(
             "Enter coordinator zome name (snake_case):\n(The integrity zome will automatically be named '{name of coordinator zome}_integrity')\n",
             Some( dna_name ),
             Case::Snake,
-        )?;
+        )?,
+    };
 
     let integrity_zome_name = integrity_zome_name(&zome_name);
     let ScaffoldedTemplate { file_tree, .. } = scaffold_integrity_zome(
         dna_file_tree,
-        &template_file_tree,
+        template_file_tree,
         &integrity_zome_name,
         &None,
     )?;
@@ -531,14 +593,13 @@ pub fn scaffold_zome_pair(
 
     let ScaffoldedTemplate { file_tree, .. } = scaffold_coordinator_zome(
         dna_file_tree,
-        &template_file_tree,
+        template_file_tree,
         &zome_name,
         Some(&vec![integrity_zome_name]),
         &None,
     )?;
 
-    build_file_tree(file_tree, ".")?;
-    Ok(())
+    Ok(file_tree)
 }
 
 #[cfg(test)]
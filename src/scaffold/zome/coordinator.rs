@@ -1,14 +1,16 @@
-use std::{collections::BTreeMap, ffi::OsString};
+use std::{collections::BTreeMap, ffi::OsString, path::Path};
 
+use convert_case::{Case, Casing};
 use dialoguer::{theme::ColorfulTheme, Select};
-use holochain_types::prelude::ZomeManifest;
+use holochain_types::prelude::{DnaManifest, ZomeManifest};
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
+use serde::Serialize;
 use syn::ItemFn;
 
 use crate::{
     error::{ScaffoldError, ScaffoldResult},
-    file_tree::find_map_rust_files,
+    file_tree::{file_content, find_map_rust_files, map_rust_files, FileTree},
     scaffold::dna::DnaFileTree,
 };
 
@@ -236,10 +238,447 @@ pub fn find_all_extern_functions(zome_file_tree: &ZomeFileTree) -> ScaffoldResul
     Ok(hdk_extern_instances.values().flatten().cloned().collect())
 }
 
+/// Returns the path of the module of the zome crate that defines the given extern function
+pub fn find_extern_function_module_path(
+    zome_file_tree: &ZomeFileTree,
+    fn_name_to_find: &str,
+) -> ScaffoldResult<Option<String>> {
+    let crate_src_path = zome_file_tree.zome_crate_path.join("src");
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+    let files = find_map_rust_files(
+        zome_file_tree
+            .dna_file_tree
+            .file_tree_ref()
+            .path(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        &|_file_path, rust_file| {
+            rust_file
+                .items
+                .iter()
+                .any(|item| match item {
+                    syn::Item::Fn(item_fn) => {
+                        item_fn.sig.ident == fn_name_to_find
+                            && item_fn
+                                .attrs
+                                .iter()
+                                .any(|a| a.path().segments.iter().any(|s| s.ident == "hdk_extern"))
+                    }
+                    _ => false,
+                })
+                .then_some(())
+        },
+    );
+
+    Ok(files
+        .into_keys()
+        .next()
+        .map(|file_path| module_path(&file_path)))
+}
+
+/// Path of the module of a file of the zome crate, given relative to its `src` directory
+pub fn module_path(file_path: &Path) -> String {
+    let mut segments = vec!["crate".to_string()];
+    for component in file_path.with_extension("").iter() {
+        let component = component.to_string_lossy().to_string();
+        if component != "lib" && component != "mod" {
+            segments.push(component);
+        }
+    }
+    segments.join("::")
+}
+
+/// Adds the statement to the `init` callback of the zome crate, right before the result it returns
+pub fn add_stmt_to_init(
+    file_tree: &mut FileTree,
+    crate_src_path: &Path,
+    stmt: &syn::Stmt,
+) -> ScaffoldResult<()> {
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+    let mut path = v.iter();
+    let src_file_tree = file_tree
+        .path_mut(&mut path)
+        .ok_or(ScaffoldError::PathNotFound(crate_src_path.to_path_buf()))?;
+
+    let init_files = find_map_rust_files(src_file_tree, &|_file_path, rust_file| {
+        rust_file
+            .items
+            .iter()
+            .any(|item| matches!(item, syn::Item::Fn(item_fn) if item_fn.sig.ident == "init"))
+            .then_some(())
+    });
+    let Some(init_file_path) = init_files.into_keys().next() else {
+        return Err(ScaffoldError::MalformedFile(
+            crate_src_path.to_path_buf(),
+            "the init function of the zome was not found".to_string(),
+        ));
+    };
+
+    map_rust_files(src_file_tree, |file_path, mut file| {
+        if file_path == init_file_path {
+            for item in &mut file.items {
+                if let syn::Item::Fn(item_fn) = item {
+                    if item_fn.sig.ident == "init" {
+                        let index = item_fn.block.stmts.len().saturating_sub(1);
+                        item_fn.block.stmts.insert(index, stmt.clone());
+                    }
+                }
+            }
+        }
+        Ok(file)
+    })
+    .map_err(|e| match e {
+        ScaffoldError::MalformedFile(path, error) => {
+            ScaffoldError::MalformedFile(crate_src_path.join(path), error)
+        }
+        _ => e,
+    })
+}
+
+/// The signature of an extern function, as seen by the zomes calling it
+#[derive(Serialize, Debug, Clone)]
+pub struct ExternFunctionSignature {
+    pub name: String,
+    pub input_type: Option<String>,
+    pub output_type: String,
+}
+
+impl ExternFunctionSignature {
+    pub fn from_item_fn(item_fn: &ItemFn) -> ScaffoldResult<ExternFunctionSignature> {
+        let name = item_fn.sig.ident.to_string();
+        let input_type = item_fn.sig.inputs.first().and_then(|arg| match arg {
+            syn::FnArg::Typed(pat_type) => Some(pat_type.ty.to_token_stream().to_string()),
+            syn::FnArg::Receiver(_) => None,
+        });
+        let output_type = match &item_fn.sig.output {
+            syn::ReturnType::Type(_, ty) => extern_result_inner_type(ty),
+            syn::ReturnType::Default => None,
+        }
+        .ok_or(ScaffoldError::InvalidArguments(format!(
+            "function {name} doesn't return an ExternResult, so it can't be called from outside its zome"
+        )))?;
+
+        Ok(ExternFunctionSignature {
+            name,
+            input_type,
+            output_type: output_type.to_token_stream().to_string(),
+        })
+    }
+}
+
+pub fn extern_result_inner_type(ty: &syn::Type) -> Option<syn::Type> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "ExternResult" {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().find_map(|a| match a {
+            syn::GenericArgument::Type(t) => Some(t.clone()),
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+/// A struct or enum defined in a coordinator zome or in one of its integrity zomes
+#[derive(Debug, Clone)]
+pub struct TypeDefinition {
+    pub item: syn::Item,
+    /// The integrity zome that defines the type, if it's not the coordinator zome
+    pub integrity_zome: Option<ZomeManifest>,
+}
+
+/// Returns the structs and enums of the coordinator zome and its integrity zomes that the input
+/// and output of the function refer to, directly or through the fields of other such types
+pub fn find_referenced_type_definitions(
+    zome_file_tree: &ZomeFileTree,
+    signature: &ExternFunctionSignature,
+) -> ScaffoldResult<Vec<TypeDefinition>> {
+    let definitions = find_type_definitions(zome_file_tree)?;
+
+    let mut pending: Vec<String> = signature
+        .input_type
+        .iter()
+        .chain(std::iter::once(&signature.output_type))
+        .flat_map(|t| type_names(t))
+        .collect();
+    let mut referenced: Vec<TypeDefinition> = vec![];
+    while let Some(type_name) = pending.pop() {
+        if referenced
+            .iter()
+            .any(|d| item_ident(&d.item) == Some(type_name.clone()))
+        {
+            continue;
+        }
+        if let Some(definition) = definitions
+            .iter()
+            .find(|d| item_ident(&d.item) == Some(type_name.clone()))
+        {
+            pending.extend(type_names(&definition.item.to_token_stream().to_string()));
+            referenced.push(definition.clone());
+        }
+    }
+    referenced.reverse();
+
+    Ok(referenced)
+}
+
+/// Returns the structs and enums defined in the coordinator zome and its integrity zomes
+fn find_type_definitions(zome_file_tree: &ZomeFileTree) -> ScaffoldResult<Vec<TypeDefinition>> {
+    let mut crates = vec![(zome_file_tree.zome_crate_path.clone(), None)];
+    for dependency in zome_file_tree.zome_manifest.dependencies.iter().flatten() {
+        let integrity_zome = match &zome_file_tree.dna_file_tree.dna_manifest {
+            DnaManifest::V0(v0) => v0
+                .integrity
+                .zomes
+                .iter()
+                .find(|z| z.name == dependency.name)
+                .cloned(),
+        };
+        if let Some(integrity_zome) = integrity_zome {
+            let integrity_zome_file_tree = ZomeFileTree::from_zome_manifest(
+                zome_file_tree.dna_file_tree.clone(),
+                integrity_zome,
+            )?;
+            crates.push((
+                integrity_zome_file_tree.zome_crate_path,
+                Some(integrity_zome_file_tree.zome_manifest),
+            ));
+        }
+    }
+
+    let mut definitions: Vec<TypeDefinition> = vec![];
+    for (crate_path, integrity_zome) in crates {
+        let crate_src_path = crate_path.join("src");
+        let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+        let types = find_map_rust_files(
+            zome_file_tree
+                .dna_file_tree
+                .file_tree_ref()
+                .path(&mut v.iter())
+                .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+            &|_file_path, rust_file| {
+                let types: Vec<syn::Item> = rust_file
+                    .items
+                    .iter()
+                    .filter(|item| match item {
+                        syn::Item::Struct(_) => true,
+                        // The entry and link types enums only make sense in the integrity zome
+                        syn::Item::Enum(item_enum) => !item_enum.attrs.iter().any(|a| {
+                            a.path().is_ident("hdk_entry_types")
+                                || a.path().is_ident("hdk_link_types")
+                        }),
+                        _ => false,
+                    })
+                    .cloned()
+                    .collect();
+                Some(types)
+            },
+        );
+        definitions.extend(types.into_values().flatten().map(|item| TypeDefinition {
+            item,
+            integrity_zome: integrity_zome.clone(),
+        }));
+    }
+
+    Ok(definitions)
+}
+
+/// How a sweettest builds the input of the extern function it calls
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SweettestInput {
+    /// With `Default::default()`
+    Default,
+    /// With the `sample_*` function scaffolded for an entry type in `tests/common.rs`
+    Sample { function_name: String },
+    /// With a helper left for the developer to implement
+    Placeholder,
+}
+
+impl SweettestInput {
+    /// Picks how the tests of the zome build values of the given type, defined in the zome, in
+    /// one of its integrity zomes or in the standard library
+    pub fn for_type(
+        zome_file_tree: &ZomeFileTree,
+        input_type: &str,
+    ) -> ScaffoldResult<SweettestInput> {
+        let ty: syn::Type = syn::parse_str(input_type)?;
+        if implements_default(&ty, &find_type_definitions(zome_file_tree)?) {
+            return Ok(SweettestInput::Default);
+        }
+
+        let syn::Type::Path(type_path) = &ty else {
+            return Ok(SweettestInput::Placeholder);
+        };
+        let Some(type_ident) = type_path.path.get_ident() else {
+            return Ok(SweettestInput::Placeholder);
+        };
+        let function_name = format!("sample_{}", type_ident.to_string().to_case(Case::Snake));
+        let common_rs = file_content(
+            zome_file_tree.dna_file_tree.file_tree_ref(),
+            &zome_file_tree
+                .zome_crate_path
+                .join("tests")
+                .join("common.rs"),
+        )
+        .ok()
+        .and_then(|content| syn::parse_str::<syn::File>(&content).ok());
+        let has_sample = common_rs.is_some_and(|file| {
+            file.items.iter().any(
+                |item| matches!(item, syn::Item::Fn(item_fn) if item_fn.sig.ident == function_name),
+            )
+        });
+
+        Ok(match has_sample {
+            true => SweettestInput::Sample { function_name },
+            false => SweettestInput::Placeholder,
+        })
+    }
+}
+
+/// Standard types whose values can be built with `Default::default()`, whatever their parameters
+const DEFAULT_TYPES: [&str; 23] = [
+    "String", "bool", "char", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32",
+    "i64", "i128", "isize", "f32", "f64", "Vec", "Option", "HashMap", "HashSet", "BTreeMap",
+    "BTreeSet",
+];
+
+fn implements_default(ty: &syn::Type, definitions: &[TypeDefinition]) -> bool {
+    match ty {
+        syn::Type::Tuple(tuple) => tuple
+            .elems
+            .iter()
+            .all(|elem| implements_default(elem, definitions)),
+        syn::Type::Path(type_path) => {
+            let Some(segment) = type_path.path.segments.last() else {
+                return false;
+            };
+            DEFAULT_TYPES.iter().any(|t| segment.ident == t)
+                || definitions.iter().any(|d| {
+                    item_ident(&d.item).is_some_and(|ident| segment.ident == ident)
+                        && item_attrs(&d.item).iter().any(|a| {
+                            a.path().is_ident("derive")
+                                && type_names(&a.to_token_stream().to_string())
+                                    .iter()
+                                    .any(|t| t == "Default")
+                        })
+                })
+        }
+        _ => false,
+    }
+}
+
+fn item_attrs(item: &syn::Item) -> &[syn::Attribute] {
+    match item {
+        syn::Item::Struct(item_struct) => &item_struct.attrs,
+        syn::Item::Enum(item_enum) => &item_enum.attrs,
+        _ => &[],
+    }
+}
+
+/// Name of the crate of the zome, as it's imported from other crates
+pub fn zome_crate_name(zome_manifest: &ZomeManifest) -> String {
+    std::path::PathBuf::from(&zome_manifest.path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| zome_manifest.name.to_string())
+        .replace('-', "_")
+}
+
+fn type_names(tokens: &str) -> Vec<String> {
+    tokens
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| word.starts_with(|c: char| c.is_uppercase()))
+        .map(|word| word.to_string())
+        .collect()
+}
+
+pub fn item_ident(item: &syn::Item) -> Option<String> {
+    match item {
+        syn::Item::Struct(item_struct) => Some(item_struct.ident.to_string()),
+        syn::Item::Enum(item_enum) => Some(item_enum.ident.to_string()),
+        syn::Item::Fn(item_fn) => Some(item_fn.sig.ident.to_string()),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn statement_is_added_to_init_before_its_result() {
+        let lib_rs = crate::utils::unparse_pretty(&syn::parse2(initial_lib_rs(None)).unwrap());
+        let mut file_tree: FileTree = build_fs_tree::dir! {
+            "src" => build_fs_tree::dir! {
+                "lib.rs" => build_fs_tree::file!(lib_rs)
+            }
+        };
+
+        add_stmt_to_init(
+            &mut file_tree,
+            Path::new("src"),
+            &syn::parse_quote! { schedule("tick")?; },
+        )
+        .unwrap();
+
+        let lib_rs = crate::file_tree::file_content(&file_tree, Path::new("src/lib.rs")).unwrap();
+        let init = lib_rs.split("pub fn init()").nth(1).unwrap();
+        assert!(
+            init.find("schedule(\"tick\")?;").unwrap()
+                < init.find("Ok(InitCallbackResult::Pass)").unwrap()
+        );
+    }
+
+    #[test]
+    fn statement_is_not_added_to_a_zome_without_init() {
+        let mut file_tree: FileTree = build_fs_tree::dir! {
+            "src" => build_fs_tree::dir! {
+                "lib.rs" => build_fs_tree::file!("use hdk::prelude::*;")
+            }
+        };
+
+        let result = add_stmt_to_init(
+            &mut file_tree,
+            Path::new("src"),
+            &syn::parse_quote! { schedule("tick")?; },
+        );
+
+        assert!(matches!(result, Err(ScaffoldError::MalformedFile(..))));
+    }
+
+    #[test]
+    fn sweettests_build_std_types_and_types_deriving_default_with_default() {
+        let definitions = vec![
+            TypeDefinition {
+                item: syn::parse_quote! {
+                    #[derive(Serialize, Deserialize, Debug, Default)]
+                    pub struct Filter { pub tag: String }
+                },
+                integrity_zome: None,
+            },
+            TypeDefinition {
+                item: syn::parse_quote! {
+                    #[derive(Serialize, Deserialize, Debug)]
+                    pub struct CreatePostInput { pub title: String }
+                },
+                integrity_zome: None,
+            },
+        ];
+        let implements = |ty: &str| implements_default(&syn::parse_str(ty).unwrap(), &definitions);
+
+        assert!(implements("()"));
+        assert!(implements("(String, Vec<ActionHash>)"));
+        assert!(implements("Option<Record>"));
+        assert!(implements("Filter"));
+        assert!(!implements("ActionHash"));
+        assert!(!implements("CreatePostInput"));
+        assert!(!implements("(u32, ActionHash)"));
+    }
+
     #[test]
     fn initial_cargo_toml_contains_test_dependencies() {
         let toml = initial_cargo_toml("test_zome", None);
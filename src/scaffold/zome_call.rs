@@ -0,0 +1,256 @@
+use std::{ffi::OsString, path::Path};
+
+use convert_case::{Case, Casing};
+use holochain_types::prelude::{DnaManifest, ZomeManifest};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::parse_quote;
+
+use crate::{
+    error::{ScaffoldError, ScaffoldResult},
+    file_tree::{file_exists, insert_file, map_file, map_rust_files},
+};
+
+use super::{
+    dna::DnaFileTree,
+    zome::{
+        coordinator::{
+            find_extern_function_or_choose, find_referenced_type_definitions, item_ident,
+            zome_crate_name, ExternFunctionSignature, TypeDefinition,
+        },
+        ZomeFileTree,
+    },
+};
+
+/// Scaffolds a typed wrapper in the `from` coordinator zome that calls the given function of the
+/// `to` coordinator zome of the same DNA
+///
+/// The types of the input and output of the function are imported from the integrity zomes that
+/// both zomes depend on, and mirrored in the wrapper's module otherwise
+pub fn scaffold_zome_call(
+    dna_file_tree: DnaFileTree,
+    maybe_from: Option<&str>,
+    maybe_to: Option<&str>,
+    maybe_function: Option<&str>,
+) -> ScaffoldResult<ZomeFileTree> {
+    let dna_manifest_path = dna_file_tree.dna_manifest_path.clone();
+    let from_zome_file_tree = ZomeFileTree::get_or_choose_coordinator(dna_file_tree, maybe_from)?;
+    let from_zome_manifest = from_zome_file_tree.zome_manifest.clone();
+
+    let other_coordinator_zomes: Vec<ZomeManifest> =
+        match &from_zome_file_tree.dna_file_tree.dna_manifest {
+            DnaManifest::V0(v0) => v0
+                .coordinator
+                .zomes
+                .iter()
+                .filter(|z| z.name != from_zome_manifest.name)
+                .cloned()
+                .collect(),
+        };
+    let to_coordinator_zomes = match maybe_to {
+        Some(to) if to == from_zome_manifest.name.0.as_ref() => {
+            return Err(ScaffoldError::InvalidArguments(format!(
+                "zome {to} can call its own functions directly, choose another coordinator zome"
+            )));
+        }
+        Some(to) => vec![other_coordinator_zomes
+            .into_iter()
+            .find(|z| z.name.0.eq(to))
+            .ok_or(ScaffoldError::CoordinatorZomeNotFound(
+                to.to_string(),
+                from_zome_file_tree.dna_file_tree.dna_manifest.name(),
+            ))?],
+        None => other_coordinator_zomes,
+    };
+    if to_coordinator_zomes.is_empty() {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "there are no other coordinator zomes in the {} DNA to call",
+            from_zome_file_tree.dna_file_tree.dna_manifest.name()
+        )));
+    }
+
+    let (to_zome_manifest, item_fn) = find_extern_function_or_choose(
+        &from_zome_file_tree.dna_file_tree,
+        &to_coordinator_zomes,
+        maybe_function.unwrap_or_default(),
+        "Which function should be called?",
+    )?;
+    let signature = ExternFunctionSignature::from_item_fn(&item_fn)?;
+
+    let to_zome_file_tree = ZomeFileTree::from_zome_manifest(
+        from_zome_file_tree.dna_file_tree.clone(),
+        to_zome_manifest.clone(),
+    )?;
+    let (shared_types, mirrored_types): (Vec<TypeDefinition>, Vec<TypeDefinition>) =
+        find_referenced_type_definitions(&to_zome_file_tree, &signature)?
+            .into_iter()
+            .partition(|d| {
+                d.integrity_zome.as_ref().is_some_and(|integrity_zome| {
+                    from_zome_manifest
+                        .dependencies
+                        .iter()
+                        .flatten()
+                        .any(|dep| dep.name == integrity_zome.name)
+                })
+            });
+    let shared_type_imports: Vec<syn::Item> = shared_types
+        .iter()
+        .filter_map(|d| {
+            let crate_name = format_ident!("{}", zome_crate_name(d.integrity_zome.as_ref()?));
+            let type_name = format_ident!("{}", item_ident(&d.item)?);
+            Some(parse_quote! { use #crate_name::#type_name; })
+        })
+        .collect();
+
+    let to_zome_name = to_zome_manifest.name.to_string();
+    let wrapper_fn_name = zome_call_fn_name(&to_zome_name, &signature.name);
+    let wrapper_fn: syn::Item = syn::parse2(zome_call_fn(&to_zome_name, &signature))?;
+
+    let crate_src_path = from_zome_file_tree.zome_crate_path.join("src");
+    let mut file_tree = from_zome_file_tree.dna_file_tree.file_tree();
+
+    // 1. Add the wrapper to the "<zome>_calls" module of the caller zome
+    let module_name = format!("{}_calls", to_zome_name.to_case(Case::Snake));
+    let module_file_name = format!("{module_name}.rs");
+    let module_file_path = crate_src_path.join(&module_file_name);
+    if !file_exists(&file_tree, &module_file_path) {
+        insert_file(&mut file_tree, &module_file_path, "use hdk::prelude::*;")?;
+        map_file(&mut file_tree, &crate_src_path.join("lib.rs"), |contents| {
+            Ok(format!(
+                r#"pub mod {module_name};
+{contents}"#
+            ))
+        })?;
+    }
+
+    let v: Vec<OsString> = crate_src_path.iter().map(|s| s.to_os_string()).collect();
+    map_rust_files(
+        file_tree
+            .path_mut(&mut v.iter())
+            .ok_or(ScaffoldError::PathNotFound(crate_src_path.clone()))?,
+        |file_path, mut file| {
+            if file_path == Path::new(&module_file_name) {
+                if file
+                    .items
+                    .iter()
+                    .any(|i| item_ident(i).is_some_and(|ident| ident == wrapper_fn_name))
+                {
+                    return Err(ScaffoldError::InvalidArguments(format!(
+                        "function {wrapper_fn_name} already exists in {module_file_name}"
+                    )));
+                }
+
+                // 2. Reuse the types of the integrity zomes shared by both zomes, and mirror the rest
+                for import in &shared_type_imports {
+                    if !file.items.contains(import) {
+                        let index = file
+                            .items
+                            .iter()
+                            .take_while(|i| matches!(i, syn::Item::Use(_)))
+                            .count();
+                        file.items.insert(index, import.clone());
+                    }
+                }
+                for mirrored_type in &mirrored_types {
+                    let ident = item_ident(&mirrored_type.item);
+                    if !file.items.iter().any(|i| item_ident(i) == ident) {
+                        file.items.push(mirrored_type.item.clone());
+                    }
+                }
+                file.items.push(wrapper_fn.clone());
+            }
+            Ok(file)
+        },
+    )
+    .map_err(|e| match e {
+        ScaffoldError::MalformedFile(path, error) => {
+            ScaffoldError::MalformedFile(crate_src_path.join(path), error)
+        }
+        _ => e,
+    })?;
+
+    let dna_file_tree = DnaFileTree::from_dna_manifest_path(file_tree, &dna_manifest_path)?;
+    ZomeFileTree::from_zome_manifest(dna_file_tree, from_zome_manifest)
+}
+
+pub fn zome_call_fn_name(to_zome_name: &str, function_name: &str) -> String {
+    format!("call_{}_{function_name}", to_zome_name.to_case(Case::Snake))
+}
+
+fn zome_call_fn(to_zome_name: &str, signature: &ExternFunctionSignature) -> TokenStream {
+    let function_name = &signature.name;
+    let wrapper_fn_name = format_ident!("{}", zome_call_fn_name(to_zome_name, function_name));
+    let output_type: syn::Type =
+        syn::parse_str(&signature.output_type).expect("output type was parsed before");
+    let failed_call_message =
+        format!("Call to {function_name} in the {to_zome_name} zome failed: {{response:?}}");
+
+    let (params, payload) = match &signature.input_type {
+        Some(input_type) => {
+            let input_type: syn::Type =
+                syn::parse_str(input_type).expect("input type was parsed before");
+            (quote! { input: #input_type }, quote! { input })
+        }
+        None => (quote! {}, quote! { () }),
+    };
+
+    quote! {
+        pub fn #wrapper_fn_name(#params) -> ExternResult<#output_type> {
+            let response = call(
+                CallTargetCell::Local,
+                ZomeName::from(#to_zome_name),
+                FunctionName::from(#function_name),
+                None,
+                #payload,
+            )?;
+            match response {
+                ZomeCallResponse::Ok(result) => result
+                    .decode()
+                    .map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string()))),
+                _ => Err(wasm_error!(WasmErrorInner::Guest(format!(#failed_call_message)))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::unparse_pretty;
+
+    #[test]
+    fn zome_call_fn_calls_the_local_cell() {
+        let signature = ExternFunctionSignature::from_item_fn(&parse_quote! {
+            #[hdk_extern]
+            pub fn create_post(post: Post) -> ExternResult<Record> {
+                todo!()
+            }
+        })
+        .unwrap();
+        let generated = unparse_pretty(&syn::parse2(zome_call_fn("posts", &signature)).unwrap());
+
+        assert!(generated
+            .contains("pub fn call_posts_create_post(input: Post) -> ExternResult<Record>"));
+        assert!(!generated.contains("#[hdk_extern]"));
+        assert!(generated.contains("CallTargetCell::Local,"));
+        assert!(generated.contains(r#"ZomeName::from("posts"),"#));
+        assert!(generated.contains(r#"FunctionName::from("create_post"),"#));
+    }
+
+    #[test]
+    fn zome_call_fn_without_input_sends_unit() {
+        let signature = ExternFunctionSignature::from_item_fn(&parse_quote! {
+            #[hdk_extern]
+            pub fn get_all_posts() -> ExternResult<Vec<Link>> {
+                todo!()
+            }
+        })
+        .unwrap();
+        let generated =
+            unparse_pretty(&syn::parse2(zome_call_fn("post-comments", &signature)).unwrap());
+
+        assert!(generated
+            .contains("pub fn call_post_comments_get_all_posts() -> ExternResult<Vec<Link>>"));
+        assert!(generated.contains("        (),\n"));
+    }
+}
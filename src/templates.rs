@@ -11,18 +11,29 @@ use crate::error::{ScaffoldError, ScaffoldResult};
 use crate::file_tree::{
     file_content, find_files, flatten_file_tree, unflatten_file_tree, FileTree,
 };
+use crate::scaffold::config::ScaffoldConfig;
 use crate::utils::format_code;
 
 pub mod helpers;
 
+pub mod bridge;
+pub mod capability;
 pub mod collection;
 pub mod coordinator;
 pub mod dna;
+pub mod dna_properties;
 pub mod entry_type;
 pub mod example;
+pub mod file_storage;
+pub mod function;
 pub mod integrity;
 pub mod link_type;
+pub mod lint;
+pub mod membrane;
+pub mod remote_signal;
+pub mod scheduled;
 pub mod web_app;
+pub mod widget;
 
 static EACH_TEMPLATE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"(?P<c>(.)*)/\{\{#each (?P<b>([^\{\}])*)\}\}(?P<a>(.)*)\{\{/each\}\}.hbs\z")
@@ -110,6 +121,7 @@ pub fn render_template_file_tree<T: Serialize>(
     templates_file_tree: &FileTree,
     data: &T,
 ) -> ScaffoldResult<FileTree> {
+    let data = &with_template_vars(existing_app_file_tree, data)?;
     let flattened_templates = flatten_file_tree(templates_file_tree);
 
     let mut transformed_templates: BTreeMap<PathBuf, Option<String>> = BTreeMap::new();
@@ -182,6 +194,28 @@ pub fn render_template_file_tree<T: Serialize>(
     unflatten_file_tree(&transformed_templates)
 }
 
+/// Adds the values of the template variables persisted in the app to the data as `vars`, unless
+/// the data already has them
+fn with_template_vars<T: Serialize>(
+    existing_app_file_tree: &FileTree,
+    data: &T,
+) -> ScaffoldResult<serde_json::Value> {
+    let mut value = serde_json::to_value(data)?;
+
+    if let Some(object) = value.as_object_mut() {
+        if !object.contains_key("vars") {
+            let vars = ScaffoldConfig::from_app_file_tree(existing_app_file_tree)
+                .ok()
+                .flatten()
+                .map(|config| config.vars)
+                .unwrap_or_default();
+            object.insert("vars".into(), serde_json::to_value(vars)?);
+        }
+    }
+
+    Ok(value)
+}
+
 fn handle_each_regex_template<T: Serialize>(
     h: &Handlebars,
     path: &Path,
@@ -288,3 +322,35 @@ pub fn render_template_file_tree_and_merge_with_existing<T: Serialize>(
 
     unflatten_file_tree(&flattened_app_file_tree)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use build_fs_tree::{dir, file};
+
+    #[test]
+    fn template_vars_persisted_in_the_app_are_added_to_the_data() {
+        let app_file_tree = dir! {
+            "package.json" => file!(r#"{ "hcScaffold": { "vars": { "analytics": true, "theme": "dark" } } }"#)
+        };
+        let templates_file_tree = dir! {
+            "src" => dir! {
+                "{{#if vars.analytics}}analytics.ts{{¡if}}.hbs" => file!("const theme = '{{vars.theme}}';")
+            }
+        };
+
+        let h = build_handlebars(&templates_file_tree).unwrap();
+        let file_tree = render_template_file_tree(
+            &app_file_tree,
+            &h,
+            &templates_file_tree,
+            &serde_json::json!({ "app_name": "app" }),
+        )
+        .unwrap();
+
+        assert_eq!(
+            file_content(&file_tree, &PathBuf::from("src/analytics.ts")).unwrap(),
+            "const theme = \"dark\";\n"
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use std::{ffi::OsString, path::PathBuf};
+
+use holochain_types::prelude::ZomeManifest;
+use serde::Serialize;
+
+use crate::{
+    error::ScaffoldResult,
+    file_tree::{file_content, FileTree},
+    scaffold::zome::coordinator::{ExternFunctionSignature, SweettestInput},
+};
+
+use super::{
+    build_handlebars, render_template_file_tree_and_merge_with_existing, ScaffoldedTemplate,
+};
+
+#[derive(Serialize)]
+pub struct ScaffoldBridgeData<'a> {
+    pub app_name: &'a str,
+    pub dna_role_name: &'a str,
+    pub coordinator_zome_manifest: ZomeManifest,
+    pub to_dna_role_name: &'a str,
+    pub to_coordinator_zome_manifest: ZomeManifest,
+    /// Path of the bundle of the other DNA, relative to the crate of the caller zome
+    pub to_dna_bundle_path: &'a str,
+    pub bridged_function: &'a ExternFunctionSignature,
+    /// How the sweettest builds the input, if the bridged function has one
+    pub sweettest_input: Option<&'a SweettestInput>,
+}
+
+// TODO: group some params into a new-type or prefer builder pattern
+#[allow(unknown_lints, clippy::too_many_arguments, clippy::manual_inspect)]
+pub fn scaffold_bridge_templates(
+    mut app_file_tree: FileTree,
+    template_file_tree: &FileTree,
+    app_name: &str,
+    dna_role_name: &str,
+    coordinator_zome_manifest: &ZomeManifest,
+    to_dna_role_name: &str,
+    to_coordinator_zome_manifest: &ZomeManifest,
+    to_dna_bundle_path: &str,
+    bridged_function: &ExternFunctionSignature,
+    sweettest_input: Option<&SweettestInput>,
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    let data = ScaffoldBridgeData {
+        app_name,
+        dna_role_name,
+        coordinator_zome_manifest: coordinator_zome_manifest.clone(),
+        to_dna_role_name,
+        to_coordinator_zome_manifest: to_coordinator_zome_manifest.clone(),
+        to_dna_bundle_path,
+        bridged_function,
+        sweettest_input,
+    };
+
+    let h = build_handlebars(template_file_tree)?;
+
+    let bridge_path = PathBuf::from("bridge");
+    let v: Vec<OsString> = bridge_path.iter().map(|s| s.to_os_string()).collect();
+
+    if let Some(bridge_template) = template_file_tree.path(&mut v.iter()) {
+        let mut bridge_template = bridge_template.clone();
+        if no_spec {
+            bridge_template.dir_content_mut().map(|v| {
+                v.retain(|k, _| k != "tests" && k != "dnas");
+                v
+            });
+        }
+        app_file_tree = render_template_file_tree_and_merge_with_existing(
+            app_file_tree,
+            &h,
+            &bridge_template,
+            &data,
+        )?;
+    }
+
+    let next_instructions = match file_content(
+        template_file_tree,
+        &PathBuf::from("bridge.instructions.hbs"),
+    ) {
+        Ok(content) => Some(h.render_template(content.as_str(), &data)?),
+        Err(_) => None,
+    };
+
+    Ok(ScaffoldedTemplate {
+        file_tree: app_file_tree,
+        next_instructions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaffold::web_app::template_type::TemplateType;
+    use build_fs_tree::{dir, file};
+
+    fn zome_manifest(name: &str) -> ZomeManifest {
+        ZomeManifest {
+            name: name.into(),
+            hash: None,
+            path: "".to_string(),
+            dependencies: None,
+        }
+    }
+
+    fn render_bridge(input: Option<(&str, SweettestInput)>) -> String {
+        let template_file_tree = TemplateType::Svelte.file_tree().unwrap();
+        let app_file_tree: FileTree = dir! {
+            "dnas" => dir! {
+                "teams" => dir! {
+                    "zomes" => dir! {
+                        "coordinator" => dir! {
+                            "teams" => dir! {
+                                "src" => dir! {
+                                    "lib.rs" => file!("")
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        };
+        let file_tree = scaffold_bridge_templates(
+            app_file_tree,
+            &template_file_tree,
+            "test_app",
+            "teams",
+            &zome_manifest("teams"),
+            "forum",
+            &zome_manifest("posts"),
+            "../../../../../dnas/forum/workdir/forum.dna",
+            &ExternFunctionSignature {
+                name: "get_post".to_string(),
+                input_type: input.as_ref().map(|(t, _)| t.to_string()),
+                output_type: "Option<Record>".to_string(),
+            },
+            input.as_ref().map(|(_, i)| i),
+            false,
+        )
+        .unwrap()
+        .file_tree;
+
+        file_content(
+            &file_tree,
+            &PathBuf::from("dnas/teams/zomes/coordinator/teams/tests/call_forum_get_post.rs"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn bridge_sweettest_installs_both_roles() {
+        let sweettest = render_bridge(Some(("ActionHash", SweettestInput::Placeholder)));
+
+        assert!(sweettest.contains("use teams::forum_bridge::*;"));
+        assert!(sweettest.contains(r#"#[ignore = "build the input of get_post first"]"#));
+        assert!(sweettest.contains("fn build_get_post_input() -> ActionHash {"));
+        assert!(sweettest.contains("let input = build_get_post_input();"));
+        assert!(sweettest
+            .contains(r#"manifest_dir.join("../../../../../dnas/forum/workdir/forum.dna")"#));
+        assert!(sweettest.contains(r#"("forum".to_string(), forum_dna_file),"#));
+        assert!(sweettest.contains(r#"let _output: Option<Record> = conductor"#));
+        assert!(sweettest.contains(r#".call(&zome, "call_forum_get_post", input)"#));
+    }
+
+    #[test]
+    fn bridge_sweettest_builds_default_inputs() {
+        let sweettest = render_bridge(Some(("String", SweettestInput::Default)));
+
+        assert!(!sweettest.contains("#[ignore"));
+        assert!(!sweettest.contains("build_get_post_input"));
+        assert!(sweettest.contains("let input: String = Default::default();"));
+    }
+
+    #[test]
+    fn bridge_sweettest_without_input_is_not_ignored() {
+        let sweettest = render_bridge(None);
+
+        assert!(!sweettest.contains("#[ignore"));
+        assert!(sweettest.contains(r#".call(&zome, "call_forum_get_post", ())"#));
+    }
+}
@@ -0,0 +1,176 @@
+use std::{ffi::OsString, path::PathBuf};
+
+use holochain_types::prelude::ZomeManifest;
+use serde::Serialize;
+
+use crate::{
+    error::ScaffoldResult,
+    file_tree::{file_content, FileTree},
+    scaffold::capability::{CapabilityAccess, GrantedFunction},
+};
+
+use super::{
+    build_handlebars, render_template_file_tree_and_merge_with_existing, ScaffoldedTemplate,
+};
+
+#[derive(Serialize)]
+pub struct ScaffoldCapabilityData<'a> {
+    pub app_name: &'a str,
+    pub dna_role_name: &'a str,
+    pub coordinator_zome_manifest: ZomeManifest,
+    pub tag: &'a str,
+    pub access: CapabilityAccess,
+    pub granted_functions: &'a [GrantedFunction],
+}
+
+// TODO: group some params into a new-type or prefer builder pattern
+#[allow(unknown_lints, clippy::too_many_arguments, clippy::manual_inspect)]
+pub fn scaffold_capability_templates(
+    mut app_file_tree: FileTree,
+    template_file_tree: &FileTree,
+    app_name: &str,
+    dna_role_name: &str,
+    coordinator_zome_manifest: &ZomeManifest,
+    tag: &str,
+    access: CapabilityAccess,
+    granted_functions: &[GrantedFunction],
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    let data = ScaffoldCapabilityData {
+        app_name,
+        dna_role_name,
+        coordinator_zome_manifest: coordinator_zome_manifest.clone(),
+        tag,
+        access,
+        granted_functions,
+    };
+
+    let h = build_handlebars(template_file_tree)?;
+
+    let capability_path = PathBuf::from("capability");
+    let v: Vec<OsString> = capability_path.iter().map(|s| s.to_os_string()).collect();
+
+    if let Some(capability_template) = template_file_tree.path(&mut v.iter()) {
+        let mut capability_template = capability_template.clone();
+        if no_spec {
+            capability_template.dir_content_mut().map(|v| {
+                v.retain(|k, _| k != "tests" && k != "dnas");
+                v
+            });
+        }
+        app_file_tree = render_template_file_tree_and_merge_with_existing(
+            app_file_tree,
+            &h,
+            &capability_template,
+            &data,
+        )?;
+    }
+
+    let next_instructions = match file_content(
+        template_file_tree,
+        &PathBuf::from("capability.instructions.hbs"),
+    ) {
+        Ok(content) => Some(h.render_template(content.as_str(), &data)?),
+        Err(_) => None,
+    };
+
+    Ok(ScaffoldedTemplate {
+        file_tree: app_file_tree,
+        next_instructions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaffold::{
+        web_app::template_type::TemplateType,
+        zome::coordinator::{ExternFunctionSignature, SweettestInput},
+    };
+    use build_fs_tree::{dir, file};
+
+    fn render_capability(access: CapabilityAccess) -> String {
+        let template_file_tree = TemplateType::Svelte.file_tree().unwrap();
+        let app_file_tree: FileTree = dir! {
+            "dnas" => dir! {
+                "test_dna" => dir! {
+                    "zomes" => dir! {
+                        "coordinator" => dir! {
+                            "test_zome" => dir! {
+                                "src" => dir! {
+                                    "lib.rs" => file!("")
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        };
+        let file_tree = scaffold_capability_templates(
+            app_file_tree,
+            &template_file_tree,
+            "test_app",
+            "test_dna",
+            &ZomeManifest {
+                name: "test_zome".into(),
+                hash: None,
+                path: "".to_string(),
+                dependencies: None,
+            },
+            "readers",
+            access,
+            &[
+                GrantedFunction {
+                    module_path: "crate".to_string(),
+                    signature: ExternFunctionSignature {
+                        name: "ping".to_string(),
+                        input_type: None,
+                        output_type: "String".to_string(),
+                    },
+                    sweettest_input: None,
+                },
+                GrantedFunction {
+                    module_path: "crate::post".to_string(),
+                    signature: ExternFunctionSignature {
+                        name: "get_post".to_string(),
+                        input_type: Some("ActionHash".to_string()),
+                        output_type: "Option<Record>".to_string(),
+                    },
+                    sweettest_input: Some(SweettestInput::Placeholder),
+                },
+            ],
+            false,
+        )
+        .unwrap()
+        .file_tree;
+
+        file_content(
+            &file_tree,
+            &PathBuf::from("dnas/test_dna/zomes/coordinator/test_zome/tests/readers_capability.rs"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn assigned_capability_sweettest_grants_access_to_bob() {
+        let sweettest = render_capability(CapabilityAccess::Assigned);
+
+        assert!(sweettest.contains("vec![bob_zome.cell_id().agent_pubkey().clone()]"));
+        assert!(sweettest.contains("ClaimReadersAccessInput {"));
+        assert!(sweettest.contains("async fn bob_calls_ping_on_alice() {"));
+        assert!(sweettest.contains(r#"#[ignore = "build the input of get_post first"]"#));
+        assert!(sweettest.contains(
+            "fn build_remote_get_post_input(agent: AgentPubKey) -> RemoteGetPostInput {"
+        ));
+        assert!(sweettest.contains("let _output: Option<Record> = bob_conductor"));
+        assert!(!sweettest.contains("todo!("));
+    }
+
+    #[test]
+    fn unrestricted_capability_sweettest_needs_no_claim() {
+        let sweettest = render_capability(CapabilityAccess::Unrestricted);
+
+        assert!(!sweettest.contains("grant_and_claim_readers_access"));
+        assert!(sweettest.contains("RemotePingInput {"));
+    }
+}
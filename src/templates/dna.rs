@@ -15,16 +15,22 @@ use super::{
 pub struct ScaffoldDnaData {
     pub app_name: String,
     pub dna_name: String,
+    pub clone_limit: u32,
+    pub deferred: bool,
 }
 pub fn scaffold_dna_templates(
     mut app_file_tree: FileTree,
     template_file_tree: &FileTree,
     app_name: &str,
     dna_name: &str,
+    clone_limit: u32,
+    deferred: bool,
 ) -> ScaffoldResult<ScaffoldedTemplate> {
     let data = ScaffoldDnaData {
         app_name: app_name.to_owned(),
         dna_name: dna_name.to_owned(),
+        clone_limit,
+        deferred,
     };
 
     let h = build_handlebars(template_file_tree)?;
@@ -52,3 +58,55 @@ pub fn scaffold_dna_templates(
         next_instructions,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaffold::web_app::template_type::TemplateType;
+    use build_fs_tree::dir;
+
+    fn scaffold_dna(clone_limit: u32) -> ScaffoldedTemplate {
+        let template_file_tree = TemplateType::Svelte.file_tree().unwrap();
+        let app_file_tree: FileTree = dir! {
+            "ui" => dir! {
+                "src" => dir! {}
+            }
+        };
+
+        scaffold_dna_templates(
+            app_file_tree,
+            &template_file_tree,
+            "test_app",
+            "test_dna",
+            clone_limit,
+            false,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn clonable_dna_has_clone_helpers() {
+        let scaffolded = scaffold_dna(3);
+
+        let clones = file_content(
+            &scaffolded.file_tree,
+            &PathBuf::from("ui/src/test_dna/clones.ts"),
+        )
+        .unwrap();
+        assert!(clones.contains("export async function createTestDnaClone("));
+        assert!(clones.contains("export async function enableTestDnaClone("));
+        assert!(clones.contains("export async function disableTestDnaClone("));
+        assert!(clones.contains("export async function listTestDnaClones("));
+    }
+
+    #[test]
+    fn non_clonable_dna_has_no_clone_helpers() {
+        let scaffolded = scaffold_dna(0);
+
+        assert!(file_content(
+            &scaffolded.file_tree,
+            &PathBuf::from("ui/src/test_dna/clones.ts"),
+        )
+        .is_err());
+    }
+}
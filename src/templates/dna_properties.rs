@@ -0,0 +1,148 @@
+use std::{ffi::OsString, path::PathBuf};
+
+use holochain_types::prelude::ZomeManifest;
+use serde::Serialize;
+
+use crate::{
+    error::ScaffoldResult,
+    file_tree::{file_content, FileTree},
+    scaffold::entry_type::definitions::FieldDefinition,
+};
+
+use super::{
+    build_handlebars, render_template_file_tree_and_merge_with_existing, ScaffoldedTemplate,
+};
+
+#[derive(Serialize)]
+pub struct ScaffoldDnaPropertiesData<'a> {
+    pub app_name: &'a str,
+    pub dna_role_name: &'a str,
+    pub integrity_zome_manifest: ZomeManifest,
+    pub coordinator_zome_manifest: ZomeManifest,
+    pub fields: &'a [FieldDefinition],
+}
+
+#[allow(clippy::too_many_arguments, clippy::manual_inspect)]
+pub fn scaffold_dna_properties_templates(
+    mut app_file_tree: FileTree,
+    template_file_tree: &FileTree,
+    app_name: &str,
+    dna_role_name: &str,
+    integrity_zome_manifest: &ZomeManifest,
+    coordinator_zome_manifest: &ZomeManifest,
+    fields: &[FieldDefinition],
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    let data = ScaffoldDnaPropertiesData {
+        app_name,
+        dna_role_name,
+        integrity_zome_manifest: integrity_zome_manifest.clone(),
+        coordinator_zome_manifest: coordinator_zome_manifest.clone(),
+        fields,
+    };
+
+    let h = build_handlebars(template_file_tree)?;
+
+    let dna_properties_path = PathBuf::from("dna-properties");
+    let v: Vec<OsString> = dna_properties_path
+        .iter()
+        .map(|s| s.to_os_string())
+        .collect();
+
+    if let Some(dna_properties_template) = template_file_tree.path(&mut v.iter()) {
+        let mut dna_properties_template = dna_properties_template.clone();
+        if no_spec {
+            dna_properties_template.dir_content_mut().map(|v| {
+                v.retain(|k, _| k != "tests" && k != "dnas");
+                v
+            });
+        }
+        app_file_tree = render_template_file_tree_and_merge_with_existing(
+            app_file_tree,
+            &h,
+            &dna_properties_template,
+            &data,
+        )?;
+    }
+
+    let next_instructions = match file_content(
+        template_file_tree,
+        &PathBuf::from("dna-properties.instructions.hbs"),
+    ) {
+        Ok(content) => Some(h.render_template(content.as_str(), &data)?),
+        Err(_) => None,
+    };
+
+    Ok(ScaffoldedTemplate {
+        file_tree: app_file_tree,
+        next_instructions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::scaffold::web_app::template_type::TemplateType;
+    use build_fs_tree::{dir, file};
+
+    fn zome_manifest(name: &str) -> ZomeManifest {
+        ZomeManifest {
+            name: name.into(),
+            hash: None,
+            path: "".to_string(),
+            dependencies: None,
+        }
+    }
+
+    #[test]
+    fn dna_properties_sweettest_installs_custom_properties() {
+        let template_file_tree = TemplateType::Svelte.file_tree().unwrap();
+        let app_file_tree: FileTree = dir! {
+            "dnas" => dir! {
+                "test_dna" => dir! {
+                    "zomes" => dir! {
+                        "coordinator" => dir! {
+                            "test_zome" => dir! {
+                                "src" => dir! {
+                                    "lib.rs" => file!("")
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        };
+        let fields = vec![
+            FieldDefinition::from_str("max_posts:u32").unwrap(),
+            FieldDefinition::from_str("progenitor:AgentPubKey").unwrap(),
+            FieldDefinition::from_str("mode:Enum:Mode:open.closed").unwrap(),
+        ];
+
+        let file_tree = scaffold_dna_properties_templates(
+            app_file_tree,
+            &template_file_tree,
+            "test_app",
+            "test_dna",
+            &zome_manifest("test_zome_integrity"),
+            &zome_manifest("test_zome"),
+            &fields,
+            false,
+        )
+        .unwrap()
+        .file_tree;
+
+        let sweettest = file_content(
+            &file_tree,
+            &PathBuf::from("dnas/test_dna/zomes/coordinator/test_zome/tests/dna_properties.rs"),
+        )
+        .unwrap();
+        assert!(sweettest.contains("use test_zome_integrity::DnaProperties;"));
+        assert!(sweettest.contains("max_posts: 1,"));
+        assert!(sweettest.contains("progenitor: AgentPubKey::from_raw_36(vec![0; 36]),"));
+        assert!(sweettest.contains("mode: Mode::Open,"));
+        assert!(sweettest
+            .contains(".with_properties(SerializedBytes::try_from(properties.clone()).unwrap())"));
+    }
+}
@@ -6,7 +6,7 @@ use serde::Serialize;
 use crate::{
     error::ScaffoldResult,
     file_tree::{file_content, FileTree},
-    scaffold::entry_type::{crud::Crud, definitions::EntryDefinition},
+    scaffold::entry_type::{create_policy::CreatePolicy, crud::Crud, definitions::EntryDefinition},
 };
 
 use super::{
@@ -26,6 +26,12 @@ pub struct ScaffoldEntryTypeData<'a> {
     pub entry_type_ts_types: &'a str,
     pub crud: Crud,
     pub link_from_original_to_each_update: bool,
+    pub create_policy: CreatePolicy,
+    /// Number of agents countersigning each entry, if the entries are countersigned
+    pub countersigned: Option<u8>,
+    pub clone_limit: u32,
+    /// Coordinator zome of the DNA whose UI components upload and show the `File` fields
+    pub file_storage_zome: Option<&'a str>,
 }
 
 // TODO: group some params into a new-type or prefer builder pattern
@@ -41,6 +47,10 @@ pub fn scaffold_entry_type_templates(
     entry_type_ts_types: &str,
     crud: &Crud,
     link_from_original_to_each_update: bool,
+    create_policy: &CreatePolicy,
+    countersigned: Option<u8>,
+    clone_limit: u32,
+    file_storage_zome: Option<&str>,
     no_ui: bool,
     no_spec: bool,
 ) -> ScaffoldResult<ScaffoldedTemplate> {
@@ -53,6 +63,10 @@ pub fn scaffold_entry_type_templates(
         entry_type_ts_types,
         crud: *crud,
         link_from_original_to_each_update,
+        create_policy: create_policy.clone(),
+        countersigned,
+        clone_limit,
+        file_storage_zome,
     };
     let h = build_handlebars(template_file_tree)?;
 
@@ -36,6 +36,10 @@ fn render_template(entry_type: EntryDefinition) -> String {
         entry_type_ts_types: "",
         crud: Crud::default(),
         link_from_original_to_each_update: false,
+        create_policy: CreatePolicy::Anyone,
+        countersigned: None,
+        clone_limit: 0,
+        file_storage_zome: None,
     };
     h.render_template(&common_template_content, &data).unwrap()
 }
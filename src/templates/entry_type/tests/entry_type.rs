@@ -1,4 +1,5 @@
 use super::super::*;
+use crate::scaffold::dna::properties::DnaPropertyValue;
 use crate::scaffold::entry_type::definitions::{
     Cardinality, EntryTypeReference, FieldDefinition, Referenceable,
 };
@@ -538,6 +539,10 @@ fn scaffold_entry_type_creates_test_files() {
         "",
         &crud,
         false,
+        &CreatePolicy::Anyone,
+        None,
+        0,
+        None,
         false,
         false,
     )
@@ -551,6 +556,286 @@ fn scaffold_entry_type_creates_test_files() {
         &result.file_tree,
         &coordinator_zome_path.join("tests/common.rs")
     ));
+    assert!(!file_exists(
+        &result.file_tree,
+        &coordinator_zome_path.join("tests/test-post-progenitor.rs")
+    ));
+}
+
+#[test]
+fn scaffold_entry_type_progenitor_create_policy() {
+    let TestCase {
+        app_file_tree,
+        template_file_tree,
+        integrity_zome_manifest,
+        coordinator_zome_manifest,
+        coordinator_zome_path,
+        entry_type,
+    } = scaffold_test_entry_type();
+    let create_policy = CreatePolicy::Progenitor {
+        other_dna_properties: vec![DnaPropertyValue {
+            field_name: "max_posts".to_string(),
+            value: "0".to_string(),
+        }],
+    };
+
+    let result = scaffold_entry_type_templates(
+        app_file_tree,
+        &template_file_tree,
+        "test_app",
+        "test_dna",
+        &integrity_zome_manifest,
+        &coordinator_zome_manifest,
+        &entry_type,
+        "",
+        &Crud::default(),
+        false,
+        &create_policy,
+        None,
+        0,
+        None,
+        false,
+        false,
+    )
+    .unwrap();
+
+    let progenitor_test_file = file_content(
+        &result.file_tree,
+        &coordinator_zome_path.join("tests/test-post-progenitor.rs"),
+    )
+    .unwrap();
+    assert!(progenitor_test_file.contains("async fn only_the_progenitor_can_create_test_posts()"));
+    assert!(progenitor_test_file.contains("progenitor_dna_file(&dna_path, &alice)"));
+
+    let test_file = file_content(
+        &result.file_tree,
+        &coordinator_zome_path.join("tests/test-post.rs"),
+    )
+    .unwrap();
+    assert!(!test_file.contains("SweetDnaFile::from_bundle"));
+    assert!(test_file
+        .contains(".setup_app_for_zipped_agents(\"test-app\", &[alice, bob], &[dna_file])"));
+
+    let common_file = file_content(
+        &result.file_tree,
+        &coordinator_zome_path.join("tests/common.rs"),
+    )
+    .unwrap();
+    assert!(common_file.contains("pub async fn progenitor_dna_file("));
+    assert!(common_file.contains("max_posts: 0,"));
+    assert!(!common_file.contains("todo!("));
+}
+
+#[test]
+fn scaffold_entry_type_clonable_dna() {
+    let TestCase {
+        app_file_tree,
+        template_file_tree,
+        integrity_zome_manifest,
+        coordinator_zome_manifest,
+        coordinator_zome_path,
+        entry_type,
+    } = scaffold_test_entry_type();
+
+    let result = scaffold_entry_type_templates(
+        app_file_tree,
+        &template_file_tree,
+        "test_app",
+        "test_dna",
+        &integrity_zome_manifest,
+        &coordinator_zome_manifest,
+        &entry_type,
+        "",
+        &Crud::default(),
+        false,
+        &CreatePolicy::Anyone,
+        None,
+        2,
+        None,
+        false,
+        false,
+    )
+    .unwrap();
+
+    let clones_test_file = file_content(
+        &result.file_tree,
+        &coordinator_zome_path.join("tests/test-post-clones.rs"),
+    )
+    .unwrap();
+    assert!(clones_test_file.contains("async fn test_posts_are_isolated_between_clones()"));
+    assert!(clones_test_file.contains("role_name: \"test_dna\".to_string(),"));
+    assert!(clones_test_file.contains("second_clone_zome,\n            \"get_test_post\","));
+}
+
+#[test]
+fn scaffold_entry_type_countersigned() {
+    let TestCase {
+        app_file_tree,
+        template_file_tree,
+        integrity_zome_manifest,
+        coordinator_zome_manifest,
+        coordinator_zome_path,
+        entry_type,
+    } = scaffold_test_entry_type();
+
+    let result = scaffold_entry_type_templates(
+        app_file_tree,
+        &template_file_tree,
+        "test_app",
+        "test_dna",
+        &integrity_zome_manifest,
+        &coordinator_zome_manifest,
+        &entry_type,
+        "",
+        &Crud::default(),
+        false,
+        &CreatePolicy::Anyone,
+        Some(2),
+        2,
+        None,
+        false,
+        false,
+    )
+    .unwrap();
+
+    let countersigning_test_file = file_content(
+        &result.file_tree,
+        &coordinator_zome_path.join("tests/test-post-countersigning.rs"),
+    )
+    .unwrap();
+    assert!(countersigning_test_file.contains("async fn countersign_test_post()"));
+    assert!(countersigning_test_file.contains("SweetConductorBatch::standard(2)"));
+    assert!(countersigning_test_file.contains("\"commit_countersigned_test_post\","));
+    assert!(countersigning_test_file.contains("SystemSignal::SuccessfulCountersigning(_)"));
+
+    // The other tests create entries on their own, which countersigned entries can't
+    for test_file in ["test-post.rs", "test-post-clones.rs"] {
+        assert!(!file_exists(
+            &result.file_tree,
+            &coordinator_zome_path.join("tests").join(test_file)
+        ));
+    }
+    let common_file = file_content(
+        &result.file_tree,
+        &coordinator_zome_path.join("tests/common.rs"),
+    )
+    .unwrap();
+    assert!(common_file.contains("pub async fn sample_test_post("));
+    assert!(!common_file.contains("pub async fn create_test_post("));
+}
+
+#[test]
+fn scaffold_entry_type_with_file_fields() {
+    let TestCase {
+        app_file_tree,
+        template_file_tree,
+        integrity_zome_manifest,
+        coordinator_zome_manifest,
+        coordinator_zome_path,
+        mut entry_type,
+    } = scaffold_test_entry_type();
+    entry_type.fields.push(FieldDefinition {
+        field_name: "cover".to_string(),
+        field_type: FieldType::File,
+        widget: Some("ImagePreview".to_string()),
+        cardinality: Cardinality::Single,
+        linked_from: None,
+    });
+
+    let result = scaffold_entry_type_templates(
+        app_file_tree,
+        &template_file_tree,
+        "test_app",
+        "test_dna",
+        &integrity_zome_manifest,
+        &coordinator_zome_manifest,
+        &entry_type,
+        "",
+        &Crud::default(),
+        false,
+        &CreatePolicy::Anyone,
+        None,
+        2,
+        Some("file_storage"),
+        false,
+        false,
+    )
+    .unwrap();
+
+    let common_file = file_content(
+        &result.file_tree,
+        &coordinator_zome_path.join("tests/common.rs"),
+    )
+    .unwrap();
+    assert!(common_file.contains("cover: EntryHash::from_raw_36(vec![0; 36]),"));
+
+    let ui_path = PathBuf::from("ui/src/test_dna/test_zome");
+    let create_component =
+        file_content(&result.file_tree, &ui_path.join("CreateTestPost.svelte")).unwrap();
+    assert!(
+        create_component.contains("import UploadFile from \"../file_storage/UploadFile.svelte\";")
+    );
+    assert!(
+        create_component.contains("import ShowImage from \"../file_storage/ShowImage.svelte\";")
+    );
+    assert!(create_component.contains("let cover: EntryHash = $state(undefined!);"));
+    assert!(create_component.contains("<ShowImage fileHash={cover} />"));
+
+    let detail_component =
+        file_content(&result.file_tree, &ui_path.join("TestPostDetail.svelte")).unwrap();
+    assert!(
+        detail_component.contains("import ShowImage from \"../file_storage/ShowImage.svelte\";")
+    );
+    assert!(!detail_component.contains("DownloadFile"));
+}
+
+#[test]
+fn scaffold_entry_type_with_vector_widget_fields() {
+    let TestCase {
+        app_file_tree,
+        template_file_tree,
+        integrity_zome_manifest,
+        coordinator_zome_manifest,
+        mut entry_type,
+        ..
+    } = scaffold_test_entry_type();
+    entry_type.fields.push(FieldDefinition {
+        field_name: "tags".to_string(),
+        field_type: FieldType::String,
+        widget: Some("TextField".to_string()),
+        cardinality: Cardinality::Vector,
+        linked_from: None,
+    });
+
+    let result = scaffold_entry_type_templates(
+        app_file_tree,
+        &template_file_tree,
+        "test_app",
+        "test_dna",
+        &integrity_zome_manifest,
+        &coordinator_zome_manifest,
+        &entry_type,
+        "",
+        &Crud::default(),
+        false,
+        &CreatePolicy::Anyone,
+        None,
+        2,
+        None,
+        false,
+        false,
+    )
+    .unwrap();
+
+    let ui_path = PathBuf::from("ui/src/test_dna/test_zome");
+    let create_component =
+        file_content(&result.file_tree, &ui_path.join("CreateTestPost.svelte")).unwrap();
+    assert!(create_component.contains("{#each tags as _, i}"));
+    assert!(create_component.contains("bind:value={tags[i]}"));
+
+    let detail_component =
+        file_content(&result.file_tree, &ui_path.join("TestPostDetail.svelte")).unwrap();
+    assert!(detail_component.contains("{#each testPost?.tags ?? [] as item}"));
 }
 
 #[test]
@@ -579,6 +864,10 @@ fn scaffold_entry_type_create() {
         "",
         &crud,
         false,
+        &CreatePolicy::Anyone,
+        None,
+        0,
+        None,
         false,
         false,
     )
@@ -629,6 +918,10 @@ fn scaffold_entry_type_create_with_entry_hash_reference() {
         "",
         &crud,
         false,
+        &CreatePolicy::Anyone,
+        None,
+        0,
+        None,
         false,
         false,
     )
@@ -678,6 +971,10 @@ fn scaffold_entry_type_create_update() {
         "",
         &crud,
         false,
+        &CreatePolicy::Anyone,
+        None,
+        0,
+        None,
         false,
         false,
     )
@@ -721,6 +1018,10 @@ fn scaffold_entry_type_create_update_link_from_original_to_each_update() {
         "",
         &crud,
         true,
+        &CreatePolicy::Anyone,
+        None,
+        0,
+        None,
         false,
         false,
     )
@@ -764,6 +1065,10 @@ fn scaffold_entry_type_create_update_delete() {
         "",
         &crud,
         false,
+        &CreatePolicy::Anyone,
+        None,
+        0,
+        None,
         false,
         false,
     )
@@ -813,6 +1118,10 @@ fn scaffold_entry_type_create_delete() {
         "",
         &crud,
         false,
+        &CreatePolicy::Anyone,
+        None,
+        0,
+        None,
         false,
         false,
     )
@@ -879,6 +1188,10 @@ fn scaffold_entry_type_create_linked_from() {
         "",
         &crud,
         false,
+        &CreatePolicy::Anyone,
+        None,
+        0,
+        None,
         false,
         false,
     )
@@ -950,6 +1263,10 @@ fn scaffold_entry_type_create_linked_from_with_entry_hash_reference() {
         "",
         &crud,
         false,
+        &CreatePolicy::Anyone,
+        None,
+        0,
+        None,
         false,
         false,
     )
@@ -1022,6 +1339,10 @@ fn scaffold_entry_type_create_linked_from_vector() {
         "",
         &crud,
         false,
+        &CreatePolicy::Anyone,
+        None,
+        0,
+        None,
         false,
         false,
     )
@@ -1095,6 +1416,10 @@ fn scaffold_entry_type_create_delete_linked_from() {
         "",
         &crud,
         false,
+        &CreatePolicy::Anyone,
+        None,
+        0,
+        None,
         false,
         false,
     )
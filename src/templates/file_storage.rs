@@ -0,0 +1,81 @@
+use std::{ffi::OsString, path::PathBuf};
+
+use holochain_types::prelude::ZomeManifest;
+use serde::Serialize;
+
+use crate::{
+    error::ScaffoldResult,
+    file_tree::{file_content, FileTree},
+};
+
+use super::{
+    build_handlebars, render_template_file_tree_and_merge_with_existing, ScaffoldedTemplate,
+};
+
+#[derive(Serialize)]
+pub struct ScaffoldFileStorageData<'a> {
+    pub app_name: &'a str,
+    pub dna_role_name: &'a str,
+    pub integrity_zome_manifest: ZomeManifest,
+    pub coordinator_zome_manifest: ZomeManifest,
+}
+
+// TODO: group some params into a new-type or prefer builder pattern
+#[allow(unknown_lints, clippy::too_many_arguments, clippy::manual_inspect)]
+pub fn scaffold_file_storage_templates(
+    mut app_file_tree: FileTree,
+    template_file_tree: &FileTree,
+    app_name: &str,
+    dna_role_name: &str,
+    integrity_zome_manifest: &ZomeManifest,
+    coordinator_zome_manifest: &ZomeManifest,
+    no_ui: bool,
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    let data = ScaffoldFileStorageData {
+        app_name,
+        dna_role_name,
+        integrity_zome_manifest: integrity_zome_manifest.clone(),
+        coordinator_zome_manifest: coordinator_zome_manifest.clone(),
+    };
+
+    let h = build_handlebars(template_file_tree)?;
+
+    let file_storage_path = PathBuf::from("file-storage");
+    let v: Vec<OsString> = file_storage_path.iter().map(|s| s.to_os_string()).collect();
+
+    if let Some(file_storage_template) = template_file_tree.path(&mut v.iter()) {
+        let mut file_storage_template = file_storage_template.clone();
+        if no_ui {
+            file_storage_template.dir_content_mut().map(|v| {
+                v.retain(|k, _| k != "ui");
+                v
+            });
+        }
+        if no_spec {
+            file_storage_template.dir_content_mut().map(|v| {
+                v.retain(|k, _| k != "tests" && k != "dnas");
+                v
+            });
+        }
+        app_file_tree = render_template_file_tree_and_merge_with_existing(
+            app_file_tree,
+            &h,
+            &file_storage_template,
+            &data,
+        )?;
+    }
+
+    let next_instructions = match file_content(
+        template_file_tree,
+        &PathBuf::from("file-storage.instructions.hbs"),
+    ) {
+        Ok(content) => Some(h.render_template(content.as_str(), &data)?),
+        Err(_) => None,
+    };
+
+    Ok(ScaffoldedTemplate {
+        file_tree: app_file_tree,
+        next_instructions,
+    })
+}
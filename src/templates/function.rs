@@ -0,0 +1,232 @@
+use std::{ffi::OsString, path::PathBuf};
+
+use holochain_types::prelude::ZomeManifest;
+use serde::Serialize;
+
+use crate::{
+    error::ScaffoldResult,
+    file_tree::{file_content, FileTree},
+    scaffold::zome::coordinator::SweettestInput,
+};
+
+use super::{
+    build_handlebars, render_template_file_tree_and_merge_with_existing, ScaffoldedTemplate,
+};
+
+#[derive(Serialize)]
+pub struct ScaffoldFunctionData<'a> {
+    pub app_name: &'a str,
+    pub dna_role_name: &'a str,
+    pub coordinator_zome_manifest: ZomeManifest,
+    pub function_name: &'a str,
+    /// Module of the coordinator zome the function was scaffolded in, `None` if it's in `lib.rs`
+    pub module_name: Option<&'a str>,
+    pub input_type: Option<&'a str>,
+    pub input_ts_type: Option<&'a str>,
+    /// TypeScript interface for the input struct, if one was scaffolded
+    pub input_ts_definition: Option<&'a str>,
+    pub output_type: &'a str,
+    pub output_ts_type: &'a str,
+    /// How the sweettest builds the input, if the function has one
+    pub sweettest_input: Option<&'a SweettestInput>,
+}
+
+// TODO: group some params into a new-type or prefer builder pattern
+#[allow(unknown_lints, clippy::too_many_arguments, clippy::manual_inspect)]
+pub fn scaffold_function_templates(
+    mut app_file_tree: FileTree,
+    template_file_tree: &FileTree,
+    app_name: &str,
+    dna_role_name: &str,
+    coordinator_zome_manifest: &ZomeManifest,
+    function_name: &str,
+    module_name: Option<&str>,
+    input_type: Option<&str>,
+    input_ts_type: Option<&str>,
+    input_ts_definition: Option<&str>,
+    output_type: &str,
+    output_ts_type: &str,
+    sweettest_input: Option<&SweettestInput>,
+    no_ui: bool,
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    let data = ScaffoldFunctionData {
+        app_name,
+        dna_role_name,
+        coordinator_zome_manifest: coordinator_zome_manifest.clone(),
+        function_name,
+        module_name,
+        input_type,
+        input_ts_type,
+        input_ts_definition,
+        output_type,
+        output_ts_type,
+        sweettest_input,
+    };
+
+    let h = build_handlebars(template_file_tree)?;
+
+    let function_path = PathBuf::from("function");
+    let v: Vec<OsString> = function_path.iter().map(|s| s.to_os_string()).collect();
+
+    if let Some(function_template) = template_file_tree.path(&mut v.iter()) {
+        let mut function_template = function_template.clone();
+        if no_ui {
+            function_template.dir_content_mut().map(|v| {
+                v.retain(|k, _| k != "ui");
+                v
+            });
+        }
+        if no_spec {
+            function_template.dir_content_mut().map(|v| {
+                v.retain(|k, _| k != "tests" && k != "dnas");
+                v
+            });
+        }
+        app_file_tree = render_template_file_tree_and_merge_with_existing(
+            app_file_tree,
+            &h,
+            &function_template,
+            &data,
+        )?;
+    }
+
+    let next_instructions = match file_content(
+        template_file_tree,
+        &PathBuf::from("function.instructions.hbs"),
+    ) {
+        Ok(content) => Some(h.render_template(content.as_str(), &data)?),
+        Err(_) => None,
+    };
+
+    Ok(ScaffoldedTemplate {
+        file_tree: app_file_tree,
+        next_instructions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_tree::{create_dir_all, insert_file};
+    use crate::scaffold::web_app::template_type::TemplateType;
+    use build_fs_tree::{dir, file};
+
+    fn render_function(app_file_tree: FileTree) -> FileTree {
+        let template_file_tree = TemplateType::Svelte.file_tree().unwrap();
+        scaffold_function_templates(
+            app_file_tree,
+            &template_file_tree,
+            "test_app",
+            "test_dna",
+            &ZomeManifest {
+                name: "test_zome".into(),
+                hash: None,
+                path: "".to_string(),
+                dependencies: None,
+            },
+            "publish_post",
+            Some("post"),
+            Some("PublishPostInput"),
+            Some("PublishPostInput"),
+            Some("export interface PublishPostInput {\n  title: string;\n}"),
+            "Record",
+            "Record",
+            Some(&SweettestInput::Placeholder),
+            false,
+            false,
+        )
+        .unwrap()
+        .file_tree
+    }
+
+    fn empty_app_file_tree() -> FileTree {
+        dir! {
+            "dnas" => dir! {
+                "test_dna" => dir! {
+                    "zomes" => dir! {
+                        "coordinator" => dir! {
+                            "test_zome" => dir! {
+                                "src" => dir! {
+                                    "lib.rs" => file!("")
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "ui" => dir! {
+                "src" => dir! {}
+            }
+        }
+    }
+
+    #[test]
+    fn function_sweettest_and_client_call_are_rendered() {
+        let file_tree = render_function(empty_app_file_tree());
+
+        let sweettest = file_content(
+            &file_tree,
+            &PathBuf::from("dnas/test_dna/zomes/coordinator/test_zome/tests/publish_post.rs"),
+        )
+        .unwrap();
+        assert!(sweettest.contains("use test_zome::post::*;"));
+        assert!(sweettest.contains("fn build_publish_post_input() -> PublishPostInput {"));
+        assert!(sweettest.contains("let input = build_publish_post_input();"));
+        assert!(!sweettest.contains("todo!("));
+        assert!(sweettest.contains("let _output: Record = conductor"));
+
+        let client = file_content(
+            &file_tree,
+            &PathBuf::from("ui/src/test_dna/test_zome/client.ts"),
+        )
+        .unwrap();
+        assert!(client.contains("export interface PublishPostInput {"));
+        assert!(client.contains(
+            "export async function publishPost(client: AppClient, input: PublishPostInput): Promise<Record> {"
+        ));
+        assert!(client.contains(r#"fn_name: "publish_post","#));
+    }
+
+    #[test]
+    fn client_calls_are_appended_to_the_existing_client_module() {
+        let file_tree = render_function(render_function(empty_app_file_tree()));
+
+        let client = file_content(
+            &file_tree,
+            &PathBuf::from("ui/src/test_dna/test_zome/client.ts"),
+        )
+        .unwrap();
+        assert_eq!(client.matches(r#"from "@holochain/client";"#).count(), 1);
+        assert_eq!(
+            client.matches("export async function publishPost").count(),
+            1
+        );
+        assert_eq!(
+            client.matches("export interface PublishPostInput").count(),
+            1
+        );
+    }
+
+    #[test]
+    fn client_calls_of_other_functions_are_kept() {
+        let mut app_file_tree = empty_app_file_tree();
+        let client_path = PathBuf::from("ui/src/test_dna/test_zome/client.ts");
+        create_dir_all(&mut app_file_tree, client_path.parent().unwrap()).unwrap();
+        insert_file(
+            &mut app_file_tree,
+            &client_path,
+            "export async function publishPosts(client: AppClient): Promise<void> {}\n",
+        )
+        .unwrap();
+        let file_tree = render_function(app_file_tree);
+
+        let client = file_content(
+            &file_tree,
+            &PathBuf::from("ui/src/test_dna/test_zome/client.ts"),
+        )
+        .unwrap();
+        assert!(client.contains("export async function publishPosts("));
+        assert!(client.contains("export async function publishPost("));
+    }
+}
@@ -5,13 +5,21 @@ use handlebars::{
 use package_manager_command::register_package_manager_command;
 use serde_json::Value;
 
+pub mod collection;
+pub mod field_type;
 pub mod filter;
+pub mod format;
 pub mod merge;
 pub mod package_manager_command;
+pub mod pluralize;
 pub mod uniq_lines;
 
+use collection::register_collection_helpers;
+use field_type::register_lookup_field_type;
 use filter::register_filter;
+use format::register_format_helpers;
 use merge::register_merge;
+pub use pluralize::register_pluralize_helpers;
 use uniq_lines::register_uniq_lines;
 
 pub fn register_helpers(h: Handlebars) -> Handlebars {
@@ -25,6 +33,9 @@ pub fn register_helpers(h: Handlebars) -> Handlebars {
     let h = register_uniq_lines(h);
     let h = register_filter(h);
     let h = register_package_manager_command(h);
+    let h = register_collection_helpers(h);
+    let h = register_format_helpers(h);
+    let h = register_lookup_field_type(h);
 
     h
 }
@@ -76,15 +87,6 @@ pub fn register_replace_helper(mut h: Handlebars) -> Handlebars {
     h
 }
 
-pub fn register_pluralize_helpers(mut h: Handlebars) -> Handlebars {
-    handlebars_helper!(singular: |s: String| pluralizer::pluralize(s.as_str(), 1, false));
-    h.register_helper("singular", Box::new(singular));
-    handlebars_helper!(plural: |s: String| pluralizer::pluralize(s.as_str(), 2, false));
-    h.register_helper("plural", Box::new(plural));
-
-    h
-}
-
 pub fn register_case_helpers(mut h: Handlebars) -> Handlebars {
     handlebars_helper!(title_case: |s: String| s.to_case(Case::Title));
     h.register_helper("title_case", Box::new(title_case));
@@ -0,0 +1,162 @@
+use std::cmp::Ordering;
+
+use handlebars::{handlebars_helper, Handlebars, JsonRender};
+use serde_json::{Map, Value};
+
+/// Gets the value at the given dot separated path of the item, or the item itself for "this"
+fn value_at<'a>(item: &'a Value, path: &str) -> &'a Value {
+    if path.is_empty() || path == "this" {
+        return item;
+    }
+    path.split('.')
+        .try_fold(item, |value, key| match value {
+            Value::Object(object) => object.get(key),
+            Value::Array(array) => key.parse::<usize>().ok().and_then(|i| array.get(i)),
+            _ => None,
+        })
+        .unwrap_or(&Value::Null)
+}
+
+/// Compares numbers and booleans by value, and everything else by how it's rendered
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        _ => a.render().cmp(&b.render()),
+    }
+}
+
+fn sorted_by_key(list: &[Value], key: &str, reverse: bool) -> Vec<Value> {
+    let mut sorted = list.to_vec();
+    sorted.sort_by(|a, b| {
+        let ordering = compare_values(value_at(a, key), value_at(b, key));
+        if reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+    sorted
+}
+
+fn grouped_by_key(list: &[Value], key: &str) -> Map<String, Value> {
+    let mut groups = Map::new();
+    for item in list {
+        if let Value::Array(group) = groups
+            .entry(value_at(item, key).render())
+            .or_insert_with(|| Value::Array(Vec::new()))
+        {
+            group.push(item.clone());
+        }
+    }
+    groups
+}
+
+pub fn register_collection_helpers(mut h: Handlebars) -> Handlebars {
+    handlebars_helper!(join: |list: array, separator: str| list
+        .iter()
+        .map(|item| item.render())
+        .collect::<Vec<String>>()
+        .join(separator));
+    h.register_helper("join", Box::new(join));
+
+    handlebars_helper!(split: |s: str, separator: str| s.split(separator).collect::<Vec<&str>>());
+    h.register_helper("split", Box::new(split));
+
+    handlebars_helper!(length: |value: Json| match value {
+        Value::Array(array) => array.len(),
+        Value::Object(object) => object.len(),
+        Value::String(s) => s.chars().count(),
+        _ => 0,
+    });
+    h.register_helper("length", Box::new(length));
+
+    handlebars_helper!(first: |list: array| list.first().cloned().unwrap_or_default());
+    h.register_helper("first", Box::new(first));
+
+    handlebars_helper!(last: |list: array| list.last().cloned().unwrap_or_default());
+    h.register_helper("last", Box::new(last));
+
+    handlebars_helper!(sort_by: |list: array, key: str, {reverse: bool = false}| sorted_by_key(list, key, reverse));
+    h.register_helper("sort_by", Box::new(sort_by));
+
+    handlebars_helper!(group_by: |list: array, key: str| grouped_by_key(list, key));
+    h.register_helper("group_by", Box::new(group_by));
+
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::register_collection_helpers;
+    use handlebars::Handlebars;
+    use serde_json::json;
+
+    fn setup_handlebars<'a>() -> Handlebars<'a> {
+        let hbs = Handlebars::new();
+        register_collection_helpers(hbs)
+    }
+
+    #[test]
+    fn joins_and_splits() {
+        let hbs = setup_handlebars();
+        let value = json!({"variants": ["Draft", "Published", 3], "path": "ui/src/app"});
+        match hbs.render_template(
+            "{{join variants \" | \"}} {{#each (split path \"/\")}}[{{this}}]{{/each}}",
+            &value,
+        ) {
+            Ok(s) => assert_eq!(s, "Draft | Published | 3 [ui][src][app]"),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn gets_length_first_and_last() {
+        let hbs = setup_handlebars();
+        let value = json!({"fields": ["title", "content", "author"], "name": "posts"});
+        match hbs.render_template(
+            "{{length fields}} {{length name}} {{length missing}} {{first fields}} {{last fields}}",
+            &value,
+        ) {
+            Ok(s) => assert_eq!(s, "3 5 0 title author"),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn sorts_by_nested_key() {
+        let hbs = setup_handlebars();
+        let value = json!([
+            {"name": "b", "field_type": {"size": 10}},
+            {"name": "c", "field_type": {"size": 2}},
+            {"name": "a", "field_type": {"size": 33}}
+        ]);
+        match hbs.render_template(
+            "{{#each (sort_by this \"field_type.size\")}}{{name}}{{/each}} {{#each (sort_by this \"name\" reverse=true)}}{{name}}{{/each}}",
+            &value,
+        ) {
+            Ok(s) => assert_eq!(s, "cba cba"),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn groups_by_key_in_order() {
+        let hbs = setup_handlebars();
+        let value = json!([
+            {"field_name": "title", "field_type": {"type": "String"}},
+            {"field_name": "likes", "field_type": {"type": "u32"}},
+            {"field_name": "content", "field_type": {"type": "String"}}
+        ]);
+        match hbs.render_template(
+            "{{#each (group_by this \"field_type.type\")}}{{@key}}: {{#each this}}{{field_name}} {{/each}}; {{/each}}",
+            &value,
+        ) {
+            Ok(s) => assert_eq!(s, "String: title content ; u32: likes ; "),
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::str::FromStr;
+
+use handlebars::{handlebars_helper, Handlebars, RenderError, RenderErrorReason};
+use serde_json::Value;
+
+use crate::scaffold::entry_type::definitions::FieldType;
+
+/// Gets the typescript or rust type of a field type, given either as its name or as the
+/// `field_type` of a field definition
+fn field_type_to_type(field_type: &Value, language: &str) -> Result<String, RenderError> {
+    let field_type = match field_type {
+        Value::String(name) => FieldType::from_str(name)
+            .map_err(|e| RenderErrorReason::Other(format!("lookup_field_type helper: {e}")))?,
+        value => serde_json::from_value(value.clone()).map_err(|e| {
+            RenderErrorReason::Other(format!("lookup_field_type helper: invalid field type: {e}"))
+        })?,
+    };
+
+    match language {
+        "ts" => Ok(field_type.ts_type().to_string()),
+        "rust" => Ok(field_type.rust_type().to_string()),
+        _ => Err(RenderErrorReason::Other(format!(
+            "lookup_field_type helper: language must be \"ts\" or \"rust\", but was \"{language}\""
+        ))
+        .into()),
+    }
+}
+
+pub fn register_lookup_field_type(mut h: Handlebars) -> Handlebars {
+    handlebars_helper!(lookup_field_type: |field_type: Json, language: str| field_type_to_type(field_type, language)?);
+    h.register_helper("lookup_field_type", Box::new(lookup_field_type));
+
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::register_lookup_field_type;
+    use handlebars::Handlebars;
+    use serde_json::json;
+
+    fn setup_handlebars<'a>() -> Handlebars<'a> {
+        let hbs = Handlebars::new();
+        register_lookup_field_type(hbs)
+    }
+
+    #[test]
+    fn maps_field_types_to_ts_and_rust_types() {
+        let hbs = setup_handlebars();
+        let value = json!({
+            "fields": [
+                {"field_type": {"type": "bool"}},
+                {"field_type": {"type": "File"}},
+                {"field_type": {"type": "Enum", "label": "Status", "variants": ["Draft"]}}
+            ]
+        });
+        match hbs.render_template(
+            "{{#each fields}}{{lookup_field_type field_type \"ts\"}}/{{lookup_field_type field_type \"rust\"}} {{/each}}{{lookup_field_type \"u32\" \"ts\"}}",
+            &value,
+        ) {
+            Ok(s) => assert_eq!(s, "boolean/bool EntryHash/EntryHash Status/Status number"),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_languages_and_field_types() {
+        let hbs = setup_handlebars();
+        assert!(hbs
+            .render_template("{{lookup_field_type \"String\" \"python\"}}", &json!({}))
+            .is_err());
+        assert!(hbs
+            .render_template("{{lookup_field_type \"u64\" \"ts\"}}", &json!({}))
+            .is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use handlebars::{handlebars_helper, Handlebars};
+use serde_json::Value;
+
+/// Indents every line that isn't blank by the given number of spaces
+fn indent_lines(s: &str, width: usize) -> String {
+    let indentation = " ".repeat(width);
+    s.split_inclusive('\n')
+        .map(|line| {
+            if line.trim().is_empty() {
+                line.to_string()
+            } else {
+                format!("{indentation}{line}")
+            }
+        })
+        .collect()
+}
+
+pub fn register_format_helpers(mut h: Handlebars) -> Handlebars {
+    handlebars_helper!(json: |value: Json, {pretty: bool = false}| if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    }
+    .unwrap_or_default());
+    h.register_helper("json", Box::new(json));
+
+    handlebars_helper!(indent: |s: str, width: u64| indent_lines(s, width as usize));
+    h.register_helper("indent", Box::new(indent));
+
+    handlebars_helper!(default: |value: Json, fallback: Json| match value {
+        Value::Null => fallback.clone(),
+        Value::String(s) if s.is_empty() => fallback.clone(),
+        value => value.clone(),
+    });
+    h.register_helper("default", Box::new(default));
+
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::register_format_helpers;
+    use handlebars::Handlebars;
+    use serde_json::json;
+
+    fn setup_handlebars<'a>() -> Handlebars<'a> {
+        let mut hbs = Handlebars::new();
+        hbs.register_escape_fn(handlebars::no_escape);
+        register_format_helpers(hbs)
+    }
+
+    #[test]
+    fn serializes_to_json() {
+        let hbs = setup_handlebars();
+        let value = json!({"vars": {"analytics": true, "license": "MIT"}});
+        match hbs.render_template("{{json vars}}|{{json vars pretty=true}}", &value) {
+            Ok(s) => assert_eq!(
+                s,
+                "{\"analytics\":true,\"license\":\"MIT\"}|{\n  \"analytics\": true,\n  \"license\": \"MIT\"\n}"
+            ),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn indents_non_blank_lines() {
+        let hbs = setup_handlebars();
+        let value = json!({"code": "fn a() {\n\n  b();\n}"});
+        match hbs.render_template("{{indent code 4}}", &value) {
+            Ok(s) => assert_eq!(s, "    fn a() {\n\n      b();\n    }"),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn defaults_missing_and_empty_values() {
+        let hbs = setup_handlebars();
+        let value = json!({"description": "", "name": "forum", "count": 0});
+        match hbs.render_template(
+            "{{default description \"none\"}} {{default name \"none\"}} {{default missing 5}} {{default count 5}}",
+            &value,
+        ) {
+            Ok(s) => assert_eq!(s, "none forum 5 0"),
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use handlebars::{
+    handlebars_helper, Handlebars, JsonRender, JsonValue, RenderError, RenderErrorReason,
+};
+
+/// Registers `singular` and `plural`, which render their value as a string first, so that they
+/// also take e.g. numbers, but fail on missing values instead of rendering an empty string
+///
+/// `plural` also takes an optional count, rendering the singular if it's 1.
+pub fn register_pluralize_helpers(mut h: Handlebars) -> Handlebars {
+    handlebars_helper!(singular: |value: Json| {
+        pluralizer::pluralize(&word("singular", value)?, 1, false)
+    });
+    h.register_helper("singular", Box::new(singular));
+
+    handlebars_helper!(plural: |value: Json, *args| {
+        let count = args.get(1).and_then(|count| count.as_i64()).unwrap_or(2);
+        pluralizer::pluralize(&word("plural", value)?, count as isize, false)
+    });
+    h.register_helper("plural", Box::new(plural));
+
+    h
+}
+
+fn word(helper_name: &'static str, value: &JsonValue) -> Result<String, RenderError> {
+    match value {
+        JsonValue::Null => Err(RenderErrorReason::ParamTypeMismatchForName(
+            helper_name,
+            "0".to_string(),
+            "a value that is not null".to_string(),
+        )
+        .into()),
+        value => Ok(value.render()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::register_pluralize_helpers;
+    use handlebars::{Handlebars, RenderErrorReason};
+    use serde_json::json;
+
+    fn setup_handlebars<'a>() -> Handlebars<'a> {
+        let hbs = Handlebars::new();
+        register_pluralize_helpers(hbs)
+    }
+
+    #[test]
+    fn pluralizes_the_last_word_of_any_case() {
+        let hbs = setup_handlebars();
+        let value = json!(["blog_post", "BlogPost", "blogPost", "blog post", "category"]);
+        match hbs.render_template("{{#each this}}{{plural this}} {{/each}}", &value) {
+            Ok(s) => assert_eq!(s, "blog_posts BlogPosts blogPosts blog posts categories "),
+            Err(e) => panic!("{}", e),
+        }
+        match hbs.render_template("{{#each this}}{{singular (plural this)}} {{/each}}", &value) {
+            Ok(s) => assert_eq!(s, "blog_post BlogPost blogPost blog post category "),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn takes_count_and_non_string_values() {
+        let hbs = setup_handlebars();
+        let value = json!({"name": "person", "one": 1, "many": 3});
+        match hbs.render_template(
+            "{{plural name one}} {{plural name many}} {{singular many}}",
+            &value,
+        ) {
+            Ok(s) => assert_eq!(s, "person people 3"),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn fails_on_missing_values() {
+        let hbs = setup_handlebars();
+        let value = json!({"name": null});
+        for template in [
+            "{{plural missing}}",
+            "{{singular missing}}",
+            "{{plural name 3}}",
+        ] {
+            let error = hbs.render_template(template, &value).unwrap_err();
+            assert!(matches!(
+                error.reason(),
+                RenderErrorReason::ParamTypeMismatchForName(..)
+            ));
+        }
+    }
+}
@@ -26,6 +26,7 @@ pub struct ScaffoldLinkTypeData<'a> {
     pub to_referenceable: Option<Referenceable>,
     pub delete: bool,
     pub bidirectional: Option<&'a str>,
+    pub query_functions: bool,
 }
 
 // TODO: group some params into a new-type or prefer builder pattern
@@ -41,6 +42,7 @@ pub fn scaffold_link_type_templates(
     to_referenceable: &Option<Referenceable>,
     delete: bool,
     bidirectional: Option<&str>,
+    query_functions: bool,
     no_ui: bool,
     no_spec: bool,
 ) -> ScaffoldResult<ScaffoldedTemplate> {
@@ -53,6 +55,7 @@ pub fn scaffold_link_type_templates(
         to_referenceable: to_referenceable.clone(),
         delete,
         bidirectional,
+        query_functions,
     };
 
     // This is a measure to prevent UI from getting scaffolded for link-types where the base
@@ -51,6 +51,7 @@ fn scaffold_link_template(from_role: &str, to: &Option<Referenceable>) -> Scaffo
         to,
         false,
         None,
+        false,
         true,
         false,
     )
@@ -95,11 +96,58 @@ fn link_type_test_file_is_generated() {
     );
 }
 
+#[test]
+fn bidirectional_link_type_generates_components_for_both_directions() {
+    let app_file_tree: FileTree = dir! {
+        "ui" => dir! {
+            "src" => dir! {}
+        }
+    };
+    let scaffolded_template = scaffold_link_type_templates(
+        app_file_tree,
+        &TemplateType::Svelte.file_tree().unwrap(),
+        "test-app",
+        "test-dna",
+        &ZomeManifest {
+            name: "test-zome".into(),
+            hash: None,
+            path: "".to_string(),
+            dependencies: None,
+        },
+        "post-to-tag",
+        &Referenceable::EntryType(EntryTypeReference {
+            entry_type: "post".to_string(),
+            reference_entry_hash: false,
+        }),
+        &Some(Referenceable::EntryType(EntryTypeReference {
+            entry_type: "tag".to_string(),
+            reference_entry_hash: false,
+        })),
+        false,
+        Some("tag-to-post"),
+        false,
+        false,
+        true,
+    )
+    .unwrap();
+
+    let ui_path = PathBuf::from("ui/src/test-dna/test-zome");
+    assert!(file_exists(
+        &scaffolded_template.file_tree,
+        &ui_path.join("TagsForPost.svelte")
+    ));
+    assert!(file_exists(
+        &scaffolded_template.file_tree,
+        &ui_path.join("PostsForTag.svelte")
+    ));
+}
+
 fn render_template(
     from: &Referenceable,
     to: &Referenceable,
     delete: bool,
     bidirectional: bool,
+    query_functions: bool,
 ) -> String {
     let template_file_tree = TemplateType::Svelte.file_tree().unwrap();
     let h = build_handlebars(&template_file_tree).unwrap();
@@ -124,6 +172,7 @@ fn render_template(
         to_referenceable: Some(to.to_owned()),
         delete,
         bidirectional,
+        query_functions,
     };
     h.render_template(&common_template_content, &data).unwrap()
 }
@@ -327,7 +376,7 @@ fn render_and_assert_eq(
     delete: bool,
     bidirectional: bool,
 ) {
-    let rendered_test = render_template(from, to, delete, bidirectional);
+    let rendered_test = render_template(from, to, delete, bidirectional, false);
 
     let mut expected_test = test_header(from, to);
     expected_test.push_str(expected_addresses);
@@ -831,3 +880,43 @@ fn external_hash_to_external_hash_deletable_bidirectional() {
 
     render_and_assert_eq(&from, &to, expected_addresses, true, true);
 }
+
+#[test]
+fn entry_type_to_agent_with_query_functions() {
+    let from = Referenceable::EntryType(EntryTypeReference {
+        entry_type: "frommer".to_string(),
+        reference_entry_hash: false,
+    });
+    let to = Referenceable::Agent {
+        role: "toer".to_string(),
+    };
+
+    let rendered_test = render_template(&from, &to, false, false, true);
+
+    assert!(rendered_test.contains(r#""count_toers_for_frommer","#));
+    assert!(rendered_test.contains(r#""is_frommer_linked_to_toer","#));
+    assert!(rendered_test.contains("IsFrommerLinkedToToerInput {"));
+    assert!(rendered_test.contains("assert_eq!(links_count, 0);"));
+    assert!(rendered_test.contains("assert_eq!(links_count, 1);"));
+    assert!(rendered_test.contains("assert!(is_linked);"));
+    assert!(!rendered_test.contains(r#""count_frommers_for_toer","#));
+}
+
+#[test]
+fn entry_type_to_entry_type_deletable_bidirectional_with_query_functions() {
+    let from = Referenceable::EntryType(EntryTypeReference {
+        entry_type: "frommer".to_string(),
+        reference_entry_hash: false,
+    });
+    let to = Referenceable::EntryType(EntryTypeReference {
+        entry_type: "toer".to_string(),
+        reference_entry_hash: false,
+    });
+
+    let rendered_test = render_template(&from, &to, true, true, true);
+
+    assert!(rendered_test.contains(r#""count_frommers_for_toer","#));
+    assert!(rendered_test
+        .contains("// Bob counts the links in the inverse direction after the deletion"));
+    assert_eq!(rendered_test.matches("assert!(!is_linked);").count(), 2);
+}
@@ -0,0 +1,639 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use handlebars::template::{Parameter, TemplateElement};
+use handlebars::{Handlebars, PathSeg, Template};
+use holochain_types::prelude::ZomeManifest;
+
+use crate::file_tree::{dir_content, find_files, flatten_file_tree, FileTree};
+use crate::scaffold::capability::CapabilityAccess;
+use crate::scaffold::collection::CollectionType;
+use crate::scaffold::entry_type::create_policy::CreatePolicy;
+use crate::scaffold::entry_type::crud::Crud;
+use crate::scaffold::entry_type::definitions::{EntryDefinition, FieldType, Referenceable};
+use crate::scaffold::membrane::MembraneKind;
+use crate::scaffold::scheduled::ScheduleTrigger;
+use crate::scaffold::web_app::template_manifest::{
+    TemplateManifest, TemplateVars, TEMPLATE_MANIFEST_FILE,
+};
+use crate::scaffold::zome::coordinator::ExternFunctionSignature;
+
+use super::{
+    bridge::ScaffoldBridgeData, build_handlebars, capability::ScaffoldCapabilityData,
+    collection::ScaffoldCollectionData, coordinator::ScaffoldCoordinatorZomeData,
+    dna::ScaffoldDnaData, dna_properties::ScaffoldDnaPropertiesData,
+    entry_type::ScaffoldEntryTypeData, example::ScaffoldExampleData,
+    file_storage::ScaffoldFileStorageData, function::ScaffoldFunctionData, helpers,
+    integrity::ScaffoldIntegrityZomeData, link_type::ScaffoldLinkTypeData,
+    membrane::ScaffoldMembraneData, remote_signal::ScaffoldRemoteSignalData,
+    scheduled::ScaffoldScheduledData, web_app::ScaffoldWebAppData, EACH_TEMPLATE_REGEX,
+    IF_TEMPLATE_REGEX,
+};
+
+/// A problem found in a template, along with the path of the template file it was found in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintProblem {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl std::fmt::Display for LintProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
+
+/// Checks the whole template without rendering it, returning every problem found
+pub fn lint_template(template_file_tree: &FileTree) -> Vec<LintProblem> {
+    let mut problems = Vec::new();
+
+    let mut h = build_handlebars(template_file_tree)
+        .unwrap_or_else(|_| helpers::register_helpers(Handlebars::new()));
+
+    let field_types_templates = template_file_tree
+        .path(&mut [OsString::from("field-types")].iter())
+        .cloned()
+        .unwrap_or(FileTree::Directory(BTreeMap::new()));
+    let partials: BTreeSet<String> = find_files(&field_types_templates, &|path, _contents| {
+        path.extension().is_some_and(|e| e == "hbs")
+    })
+    .into_keys()
+    .filter_map(|path| path.with_extension("").to_str().map(|s| s.to_string()))
+    .collect();
+    let field_type_widgets = field_type_widgets(&field_types_templates);
+
+    match TemplateManifest::from_template_file_tree(template_file_tree) {
+        Ok(Some(manifest)) => {
+            for (field_type, widgets) in &manifest.widgets {
+                for widget in widgets {
+                    if !field_type_widgets
+                        .get(field_type)
+                        .is_some_and(|w| w.contains(widget))
+                    {
+                        problems.push(LintProblem {
+                            path: PathBuf::from(TEMPLATE_MANIFEST_FILE),
+                            message: format!(
+                                "declares the widget \"{widget}\" for \"{field_type}\", but there is no \"field-types/{field_type}/{widget}\" folder"
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(None) => {}
+        Err(e) => problems.push(LintProblem {
+            path: PathBuf::from(TEMPLATE_MANIFEST_FILE),
+            message: e.to_string(),
+        }),
+    }
+
+    for (path, maybe_contents) in flatten_file_tree(template_file_tree) {
+        problems.extend(lint_file_name(&path, maybe_contents.is_some()));
+
+        let Some(contents) = maybe_contents else {
+            continue;
+        };
+        if path.extension().is_none_or(|e| e != "hbs") {
+            continue;
+        }
+
+        let name = path.to_string_lossy().to_string();
+        if let Err(e) = h.register_template_string(&name, &contents) {
+            problems.push(LintProblem {
+                path,
+                message: format!("invalid template: {}", e.reason()),
+            });
+            continue;
+        }
+        let Some(template) = h.get_template(&name) else {
+            continue;
+        };
+
+        let mut references = PartialReferences::default();
+        references.collect(template);
+
+        for partial in references.partials {
+            let missing: Vec<String> = match partial {
+                PartialReference::Static(name) => (!partials.contains(&name)
+                    && !references.inline_partials.contains(&name))
+                .then_some(name)
+                .into_iter()
+                .collect(),
+                PartialReference::FieldTypeLookup(parts) => {
+                    expand_field_type_lookup(&parts, &field_type_widgets)
+                        .into_iter()
+                        .filter(|name| !partials.contains(name))
+                        .collect()
+                }
+            };
+            for name in missing {
+                problems.push(LintProblem {
+                    path: path.clone(),
+                    message: format!(
+                        "references the partial \"{name}\", but there is no \"field-types/{name}.hbs\" file"
+                    ),
+                });
+            }
+        }
+    }
+
+    problems.dedup();
+    problems
+}
+
+/// Checks that the handlebars in the file name of the given template path parse, match the
+/// `{{#each}}`/`{{#if}}` patterns when they use them, and only reference data available to the command
+fn lint_file_name(path: &Path, is_file: bool) -> Vec<LintProblem> {
+    let mut problems = Vec::new();
+
+    let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+        return problems;
+    };
+    if !file_name.contains("{{") {
+        return problems;
+    }
+    let file_name = file_name.replace('¡', "/").replace('\'', "\"");
+
+    let template = match Template::compile(&file_name) {
+        Ok(t) => t,
+        Err(e) => {
+            problems.push(LintProblem {
+                path: path.to_path_buf(),
+                message: format!("invalid file name template: {}", e.reason()),
+            });
+            return problems;
+        }
+    };
+
+    let path_str = PathBuf::from("/").join(path).with_file_name(&file_name);
+    let path_str = path_str.to_string_lossy();
+    if is_file && file_name.starts_with("{{#each") && !EACH_TEMPLATE_REGEX.is_match(&path_str) {
+        problems.push(LintProblem {
+            path: path.to_path_buf(),
+            message: "file name must have the form \"{{#each ...}}<NAME>{{¡each}}.hbs\"".into(),
+        });
+    } else if is_file
+        && file_name.starts_with("{{#if")
+        && !EACH_TEMPLATE_REGEX.is_match(&path_str)
+        && !IF_TEMPLATE_REGEX.is_match(&path_str)
+    {
+        problems.push(LintProblem {
+            path: path.to_path_buf(),
+            message: "file name must have the form \"{{#if ...}}<NAME>{{¡if}}.hbs\"".into(),
+        });
+    }
+
+    let command = path
+        .components()
+        .next()
+        .and_then(|c| c.as_os_str().to_str())
+        .unwrap_or_default();
+    if let Some(fields) = command_data_fields(command) {
+        let mut variables = BTreeSet::new();
+        collect_root_variables(&template, &mut variables);
+
+        for variable in variables {
+            // The template variables are added to the data of every command
+            if variable != "vars" && !fields.contains(&variable) {
+                problems.push(LintProblem {
+                    path: path.to_path_buf(),
+                    message: format!(
+                        "file name references \"{variable}\", which is not available to the \"{command}\" templates (available: {})",
+                        fields.join(", ")
+                    ),
+                });
+            }
+        }
+    }
+
+    problems
+}
+
+/// Top-level fields of the data the templates of each command folder are rendered with
+///
+/// They are taken from a serialized instance of the data struct of the command, so that adding a
+/// field to the struct doesn't go unnoticed here.
+fn command_data_fields(command: &str) -> Option<Vec<String>> {
+    let zome_manifest = || ZomeManifest {
+        name: "".into(),
+        hash: None,
+        path: String::new(),
+        dependencies: None,
+    };
+    let referenceable = || Referenceable::Agent {
+        role: String::new(),
+    };
+
+    let data = match command {
+        "web-app" => serde_json::to_value(ScaffoldWebAppData {
+            app_name: "",
+            holochain_version: "",
+            hdk_version: "",
+            hdi_version: "",
+            holochain_client_version: "",
+            hc_spin_version: "",
+            vars: &TemplateVars::new(),
+        }),
+        "dna" => serde_json::to_value(ScaffoldDnaData {
+            app_name: String::new(),
+            dna_name: String::new(),
+            clone_limit: 0,
+            deferred: false,
+        }),
+        "coordinator-zome" => serde_json::to_value(ScaffoldCoordinatorZomeData {
+            dna_role_name: String::new(),
+            zome_manifest: zome_manifest(),
+        }),
+        "integrity-zome" => serde_json::to_value(ScaffoldIntegrityZomeData {
+            dna_role_name: String::new(),
+            zome_manifest: zome_manifest(),
+        }),
+        "entry-type" => serde_json::to_value(ScaffoldEntryTypeData {
+            app_name: String::new(),
+            dna_role_name: String::new(),
+            integrity_zome_manifest: zome_manifest(),
+            coordinator_zome_manifest: zome_manifest(),
+            entry_type: EntryDefinition {
+                name: String::new(),
+                fields: vec![],
+                reference_entry_hash: false,
+            },
+            entry_type_ts_types: "",
+            crud: Crud::default(),
+            link_from_original_to_each_update: false,
+            create_policy: CreatePolicy::default(),
+            countersigned: None,
+            clone_limit: 0,
+            file_storage_zome: None,
+        }),
+        "link-type" => serde_json::to_value(ScaffoldLinkTypeData {
+            app_name: "",
+            dna_role_name: "",
+            coordinator_zome_manifest: zome_manifest(),
+            link_type_name: "",
+            from_referenceable: referenceable(),
+            to_referenceable: None,
+            delete: false,
+            bidirectional: None,
+            query_functions: false,
+        }),
+        "collection" => serde_json::to_value(ScaffoldCollectionData {
+            app_name: String::new(),
+            dna_role_name: String::new(),
+            coordinator_zome_manifest: zome_manifest(),
+            collection_type: CollectionType::Global,
+            collection_name: String::new(),
+            referenceable: referenceable(),
+            deletable: false,
+        }),
+        "example" => serde_json::to_value(ScaffoldExampleData {
+            example: "",
+            holochain_client_version: "",
+            hdk_version: "",
+            hdi_version: "",
+        }),
+        "bridge" => serde_json::to_value(ScaffoldBridgeData {
+            app_name: "",
+            dna_role_name: "",
+            coordinator_zome_manifest: zome_manifest(),
+            to_dna_role_name: "",
+            to_coordinator_zome_manifest: zome_manifest(),
+            to_dna_bundle_path: "",
+            bridged_function: &ExternFunctionSignature {
+                name: String::new(),
+                input_type: None,
+                output_type: String::new(),
+            },
+            sweettest_input: None,
+        }),
+        "capability" => serde_json::to_value(ScaffoldCapabilityData {
+            app_name: "",
+            dna_role_name: "",
+            coordinator_zome_manifest: zome_manifest(),
+            tag: "",
+            access: CapabilityAccess::Unrestricted,
+            granted_functions: &[],
+        }),
+        "dna-properties" => serde_json::to_value(ScaffoldDnaPropertiesData {
+            app_name: "",
+            dna_role_name: "",
+            integrity_zome_manifest: zome_manifest(),
+            coordinator_zome_manifest: zome_manifest(),
+            fields: &[],
+        }),
+        "file-storage" => serde_json::to_value(ScaffoldFileStorageData {
+            app_name: "",
+            dna_role_name: "",
+            integrity_zome_manifest: zome_manifest(),
+            coordinator_zome_manifest: zome_manifest(),
+        }),
+        "function" => serde_json::to_value(ScaffoldFunctionData {
+            app_name: "",
+            dna_role_name: "",
+            coordinator_zome_manifest: zome_manifest(),
+            function_name: "",
+            module_name: None,
+            input_type: None,
+            input_ts_type: None,
+            input_ts_definition: None,
+            output_type: "",
+            output_ts_type: "",
+            sweettest_input: None,
+        }),
+        "membrane" => serde_json::to_value(ScaffoldMembraneData {
+            app_name: "",
+            dna_role_name: "",
+            integrity_zome_manifest: zome_manifest(),
+            coordinator_zome_manifest: zome_manifest(),
+            membrane_kind: MembraneKind::InviteCode,
+            other_dna_properties: &[],
+        }),
+        "remote-signal" => serde_json::to_value(ScaffoldRemoteSignalData {
+            app_name: "",
+            dna_role_name: "",
+            coordinator_zome_manifest: zome_manifest(),
+            signal_name: "",
+            payload_fields: &[],
+            payload_ts_definition: "",
+        }),
+        "scheduled" => serde_json::to_value(ScaffoldScheduledData {
+            app_name: "",
+            dna_role_name: "",
+            coordinator_zome_manifest: zome_manifest(),
+            function_name: "",
+            trigger: &ScheduleTrigger::Every { seconds: 0 },
+        }),
+        _ => return None,
+    };
+
+    Some(data.ok()?.as_object()?.keys().cloned().collect())
+}
+
+/// Collects the variables referenced from the root of the data, skipping the ones inside
+/// `{{#each}}` and `{{#with}}` blocks since those are relative to another context
+fn collect_root_variables(template: &Template, variables: &mut BTreeSet<String>) {
+    for element in &template.elements {
+        collect_root_variables_in_element(element, variables);
+    }
+}
+
+fn collect_root_variables_in_element(element: &TemplateElement, variables: &mut BTreeSet<String>) {
+    match element {
+        TemplateElement::Expression(helper)
+        | TemplateElement::HtmlExpression(helper)
+        | TemplateElement::HelperBlock(helper) => {
+            for param in std::iter::once(&helper.name)
+                .chain(&helper.params)
+                .chain(helper.hash.values())
+            {
+                collect_root_variables_in_param(param, variables);
+            }
+            let changes_context = matches!(helper.name.as_name(), Some("each" | "with"));
+            if let (Some(template), false) = (&helper.template, changes_context) {
+                collect_root_variables(template, variables);
+            }
+            if let Some(inverse) = &helper.inverse {
+                collect_root_variables(inverse, variables);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn collect_root_variables_in_param(param: &Parameter, variables: &mut BTreeSet<String>) {
+    match param {
+        Parameter::Path(handlebars::Path::Relative((segments, _))) => {
+            if let Some(PathSeg::Named(name)) = segments.first() {
+                variables.insert(name.clone());
+            }
+        }
+        Parameter::Subexpression(subexpression) => {
+            collect_root_variables_in_element(subexpression.as_element(), variables)
+        }
+        _ => {}
+    }
+}
+
+/// Part of a `(concat ...)` partial lookup into the `field-types` folder
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LookupPart {
+    Literal(String),
+    FieldType,
+    Widget,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PartialReference {
+    Static(String),
+    /// eg. `{{> (concat field_type.type "/" widget "/edit/render") }}`
+    FieldTypeLookup(Vec<LookupPart>),
+}
+
+#[derive(Default)]
+struct PartialReferences {
+    partials: Vec<PartialReference>,
+    inline_partials: BTreeSet<String>,
+}
+
+impl PartialReferences {
+    fn collect(&mut self, template: &Template) {
+        for element in &template.elements {
+            match element {
+                TemplateElement::Expression(helper)
+                | TemplateElement::HtmlExpression(helper)
+                | TemplateElement::HelperBlock(helper) => {
+                    for t in [&helper.template, &helper.inverse].into_iter().flatten() {
+                        self.collect(t);
+                    }
+                }
+                TemplateElement::PartialExpression(partial)
+                | TemplateElement::PartialBlock(partial) => {
+                    match &partial.name {
+                        Parameter::Name(name) => {
+                            self.partials.push(PartialReference::Static(name.clone()))
+                        }
+                        Parameter::Subexpression(subexpression) => {
+                            if let Some(parts) = field_type_lookup(subexpression.as_element()) {
+                                self.partials.push(PartialReference::FieldTypeLookup(parts));
+                            }
+                        }
+                        _ => {}
+                    }
+                    if let Some(t) = &partial.template {
+                        self.collect(t);
+                    }
+                }
+                TemplateElement::DecoratorBlock(decorator) => {
+                    if decorator.name.as_name() == Some("inline") {
+                        if let Some(Parameter::Literal(serde_json::Value::String(name))) =
+                            decorator.params.first()
+                        {
+                            self.inline_partials.insert(name.clone());
+                        }
+                    }
+                    if let Some(t) = &decorator.template {
+                        self.collect(t);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Parses `(concat field_type.type "/" widget "/edit/render")`-like lookups, returning
+/// `None` if the lookup depends on anything other than the field type and the widget
+fn field_type_lookup(element: &TemplateElement) -> Option<Vec<LookupPart>> {
+    let TemplateElement::Expression(helper) = element else {
+        return None;
+    };
+    if helper.name.as_name() != Some("concat") {
+        return None;
+    }
+
+    helper
+        .params
+        .iter()
+        .map(|param| match param {
+            Parameter::Literal(serde_json::Value::String(s)) => {
+                Some(LookupPart::Literal(s.clone()))
+            }
+            Parameter::Path(handlebars::Path::Relative((_, raw))) => match raw.as_str() {
+                "field_type.type" => Some(LookupPart::FieldType),
+                "widget" => Some(LookupPart::Widget),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Gets the partial names a lookup can resolve to for all the field types and their widgets
+fn expand_field_type_lookup(
+    parts: &[LookupPart],
+    field_type_widgets: &BTreeMap<String, Vec<String>>,
+) -> Vec<String> {
+    let uses_widget = parts.contains(&LookupPart::Widget);
+
+    FieldType::list()
+        .into_iter()
+        .map(|field_type| field_type.to_string())
+        .flat_map(|field_type| {
+            let widgets = match uses_widget {
+                true => field_type_widgets
+                    .get(&field_type)
+                    .cloned()
+                    .unwrap_or_default(),
+                false => vec![String::new()],
+            };
+            widgets
+                .into_iter()
+                .map(|widget| {
+                    parts
+                        .iter()
+                        .map(|part| match part {
+                            LookupPart::Literal(s) => s.as_str(),
+                            LookupPart::FieldType => field_type.as_str(),
+                            LookupPart::Widget => widget.as_str(),
+                        })
+                        .collect::<String>()
+                })
+                .collect::<Vec<String>>()
+        })
+        .collect()
+}
+
+/// Widgets provided for each field type, ie. the folders inside `field-types/<FIELD TYPE>`
+fn field_type_widgets(field_types_templates: &FileTree) -> BTreeMap<String, Vec<String>> {
+    field_types_templates
+        .dir_content()
+        .into_iter()
+        .flatten()
+        .filter_map(|(field_type, _)| {
+            let widgets = dir_content(field_types_templates, Path::new(field_type))
+                .ok()?
+                .into_iter()
+                .filter(|(_, t)| t.dir_content().is_some())
+                .filter_map(|(widget, _)| widget.to_str().map(|s| s.to_string()))
+                .collect();
+            Some((field_type.to_str()?.to_string(), widgets))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaffold::web_app::template_type::TemplateType;
+    use build_fs_tree::{dir, file};
+
+    #[test]
+    fn built_in_templates_have_no_problems() {
+        for template_type in [TemplateType::Svelte, TemplateType::Headless] {
+            let problems = lint_template(&template_type.file_tree().unwrap());
+            assert_eq!(problems, vec![], "{}", template_type.name());
+        }
+    }
+
+    #[test]
+    fn every_problem_is_reported_with_its_path() {
+        let template = dir! {
+            "field-types" => dir! {
+                "String" => dir! {
+                    "type.hbs" => file!("string"),
+                    "TextField" => dir! {
+                        "edit" => dir! {
+                            "render.hbs" => file!("<input />")
+                        }
+                    }
+                }
+            },
+            "entry-type" => dir! {
+                "{{dna_name}}" => dir! {
+                    "Broken.svelte.hbs" => file!("{{#if entry_type}}"),
+                    "Missing.svelte.hbs" => file!(r#"{{> String/Missing/render}}{{> (concat field_type.type "/" widget "/detail/render")}}"#),
+                    "Inline.svelte.hbs" => file!(r#"{{#*inline "local"}}x{{/inline}}{{> local}}"#),
+                    "{{#if crud.update}}Edit.svelte{{¡each}}.hbs" => file!("")
+                }
+            }
+        };
+
+        let problems: Vec<String> = lint_template(&template)
+            .into_iter()
+            .map(|p| p.to_string())
+            .collect();
+
+        assert_eq!(problems.len(), 5, "{problems:#?}");
+        assert!(
+            problems[0].starts_with("entry-type/{{dna_name}}: file name references \"dna_name\"")
+        );
+        assert!(
+            problems[1].starts_with("entry-type/{{dna_name}}/Broken.svelte.hbs: invalid template")
+        );
+        assert!(problems[2].starts_with("entry-type/{{dna_name}}/Missing.svelte.hbs: references the partial \"String/Missing/render\""));
+        assert!(problems[3].starts_with("entry-type/{{dna_name}}/Missing.svelte.hbs: references the partial \"String/TextField/detail/render\""));
+        assert!(problems[4].starts_with("entry-type/{{dna_name}}/{{#if crud.update}}Edit.svelte{{¡each}}.hbs: invalid file name template"));
+    }
+
+    #[test]
+    fn every_command_folder_has_its_data_fields() {
+        for template_type in [TemplateType::Svelte, TemplateType::Headless] {
+            let template_file_tree = template_type.file_tree().unwrap();
+            for folder in dir_content(&template_file_tree, Path::new(""))
+                .unwrap()
+                .into_iter()
+                .filter(|(_, node)| node.dir_content().is_some())
+                .filter_map(|(name, _)| name.into_string().ok())
+                .filter(|name| name != "field-types")
+            {
+                let fields = command_data_fields(&folder);
+                assert!(fields.is_some_and(|f| !f.is_empty()), "{folder}");
+            }
+        }
+
+        let fields = command_data_fields("link-type").unwrap();
+        assert!(fields.contains(&"to_referenceable".to_string()));
+        assert!(fields.contains(&"query_functions".to_string()));
+    }
+}
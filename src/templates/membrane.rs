@@ -0,0 +1,188 @@
+use std::{ffi::OsString, path::PathBuf};
+
+use holochain_types::prelude::ZomeManifest;
+use serde::Serialize;
+
+use crate::{
+    error::ScaffoldResult,
+    file_tree::{file_content, FileTree},
+    scaffold::{dna::properties::DnaPropertyValue, membrane::MembraneKind},
+};
+
+use super::{
+    build_handlebars, render_template_file_tree_and_merge_with_existing, ScaffoldedTemplate,
+};
+
+#[derive(Serialize)]
+pub struct ScaffoldMembraneData<'a> {
+    pub app_name: &'a str,
+    pub dna_role_name: &'a str,
+    pub integrity_zome_manifest: ZomeManifest,
+    pub coordinator_zome_manifest: ZomeManifest,
+    pub membrane_kind: MembraneKind,
+    /// The DNA properties other than the one the membrane proofs are checked against
+    pub other_dna_properties: &'a [DnaPropertyValue],
+}
+
+#[allow(clippy::too_many_arguments, clippy::manual_inspect)]
+pub fn scaffold_membrane_templates(
+    mut app_file_tree: FileTree,
+    template_file_tree: &FileTree,
+    app_name: &str,
+    dna_role_name: &str,
+    integrity_zome_manifest: &ZomeManifest,
+    coordinator_zome_manifest: &ZomeManifest,
+    membrane_kind: MembraneKind,
+    other_dna_properties: &[DnaPropertyValue],
+    no_ui: bool,
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    let data = ScaffoldMembraneData {
+        app_name,
+        dna_role_name,
+        integrity_zome_manifest: integrity_zome_manifest.clone(),
+        coordinator_zome_manifest: coordinator_zome_manifest.clone(),
+        membrane_kind,
+        other_dna_properties,
+    };
+
+    let h = build_handlebars(template_file_tree)?;
+
+    let membrane_path = PathBuf::from("membrane");
+    let v: Vec<OsString> = membrane_path.iter().map(|s| s.to_os_string()).collect();
+
+    if let Some(membrane_template) = template_file_tree.path(&mut v.iter()) {
+        let mut membrane_template = membrane_template.clone();
+        if no_ui {
+            membrane_template.dir_content_mut().map(|v| {
+                v.retain(|k, _| k != "ui");
+                v
+            });
+        }
+        if no_spec {
+            membrane_template.dir_content_mut().map(|v| {
+                v.retain(|k, _| k != "tests" && k != "dnas");
+                v
+            });
+        }
+        app_file_tree = render_template_file_tree_and_merge_with_existing(
+            app_file_tree,
+            &h,
+            &membrane_template,
+            &data,
+        )?;
+    }
+
+    let next_instructions = match file_content(
+        template_file_tree,
+        &PathBuf::from("membrane.instructions.hbs"),
+    ) {
+        Ok(content) => Some(h.render_template(content.as_str(), &data)?)
+            .filter(|instructions| !instructions.trim().is_empty()),
+        Err(_) => None,
+    };
+
+    Ok(ScaffoldedTemplate {
+        file_tree: app_file_tree,
+        next_instructions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaffold::web_app::template_type::TemplateType;
+    use build_fs_tree::{dir, file};
+
+    fn zome_manifest(name: &str) -> ZomeManifest {
+        ZomeManifest {
+            name: name.into(),
+            hash: None,
+            path: "".to_string(),
+            dependencies: None,
+        }
+    }
+
+    fn scaffold_membrane(membrane_kind: MembraneKind, no_ui: bool) -> ScaffoldedTemplate {
+        let template_file_tree = TemplateType::Svelte.file_tree().unwrap();
+        let app_file_tree: FileTree = dir! {
+            "dnas" => dir! {
+                "test_dna" => dir! {
+                    "zomes" => dir! {
+                        "coordinator" => dir! {
+                            "test_zome" => dir! {
+                                "src" => dir! {
+                                    "lib.rs" => file!("")
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "ui" => dir! {
+                "src" => dir! {}
+            }
+        };
+
+        scaffold_membrane_templates(
+            app_file_tree,
+            &template_file_tree,
+            "test_app",
+            "test_dna",
+            &zome_manifest("test_zome_integrity"),
+            &zome_manifest("test_zome"),
+            membrane_kind,
+            &[DnaPropertyValue {
+                field_name: "max_posts".to_string(),
+                value: "0".to_string(),
+            }],
+            no_ui,
+            false,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn invite_code_membrane_sweettest_and_form() {
+        let scaffolded = scaffold_membrane(MembraneKind::InviteCode, false);
+
+        let sweettest = file_content(
+            &scaffolded.file_tree,
+            &PathBuf::from("dnas/test_dna/zomes/coordinator/test_zome/tests/membrane.rs"),
+        )
+        .unwrap();
+        assert!(sweettest.contains("async fn only_agents_with_a_valid_invite_code_can_join()"));
+        assert!(sweettest.contains("max_posts: 0,"));
+        assert!(!sweettest.contains("todo!("));
+        assert!(!sweettest.contains("create_membrane_proof_for"));
+
+        let form = file_content(
+            &scaffolded.file_tree,
+            &PathBuf::from("ui/src/test_dna/InviteCodeForm.svelte"),
+        )
+        .unwrap();
+        assert!(form.contains("\"test_dna\": encode({ invite_code: inviteCode }),"));
+        assert!(scaffolded.next_instructions.is_some());
+    }
+
+    #[test]
+    fn signed_by_progenitor_membrane_has_no_form() {
+        let scaffolded = scaffold_membrane(MembraneKind::SignedByProgenitor, false);
+
+        let sweettest = file_content(
+            &scaffolded.file_tree,
+            &PathBuf::from("dnas/test_dna/zomes/coordinator/test_zome/tests/membrane.rs"),
+        )
+        .unwrap();
+        assert!(sweettest.contains("async fn only_agents_invited_by_the_progenitor_can_join()"));
+        assert!(
+            sweettest.contains(".call(&alice_zome, \"create_membrane_proof_for\", bob.clone())")
+        );
+        assert!(file_content(
+            &scaffolded.file_tree,
+            &PathBuf::from("ui/src/test_dna/InviteCodeForm.svelte"),
+        )
+        .is_err());
+        assert!(scaffolded.next_instructions.is_none());
+    }
+}
@@ -0,0 +1,194 @@
+use std::{ffi::OsString, path::PathBuf};
+
+use holochain_types::prelude::ZomeManifest;
+use serde::Serialize;
+
+use crate::{
+    error::ScaffoldResult,
+    file_tree::{file_content, FileTree},
+    scaffold::entry_type::definitions::FieldDefinition,
+};
+
+use super::{
+    build_handlebars, render_template_file_tree_and_merge_with_existing, ScaffoldedTemplate,
+};
+
+#[derive(Serialize)]
+pub struct ScaffoldRemoteSignalData<'a> {
+    pub app_name: &'a str,
+    pub dna_role_name: &'a str,
+    pub coordinator_zome_manifest: ZomeManifest,
+    pub signal_name: &'a str,
+    pub payload_fields: &'a [FieldDefinition],
+    /// TypeScript interface for the payload of the signal
+    pub payload_ts_definition: &'a str,
+}
+
+// TODO: group some params into a new-type or prefer builder pattern
+#[allow(unknown_lints, clippy::too_many_arguments, clippy::manual_inspect)]
+pub fn scaffold_remote_signal_templates(
+    mut app_file_tree: FileTree,
+    template_file_tree: &FileTree,
+    app_name: &str,
+    dna_role_name: &str,
+    coordinator_zome_manifest: &ZomeManifest,
+    signal_name: &str,
+    payload_fields: &[FieldDefinition],
+    payload_ts_definition: &str,
+    no_ui: bool,
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    let data = ScaffoldRemoteSignalData {
+        app_name,
+        dna_role_name,
+        coordinator_zome_manifest: coordinator_zome_manifest.clone(),
+        signal_name,
+        payload_fields,
+        payload_ts_definition,
+    };
+
+    let h = build_handlebars(template_file_tree)?;
+
+    let remote_signal_path = PathBuf::from("remote-signal");
+    let v: Vec<OsString> = remote_signal_path
+        .iter()
+        .map(|s| s.to_os_string())
+        .collect();
+
+    if let Some(remote_signal_template) = template_file_tree.path(&mut v.iter()) {
+        let mut remote_signal_template = remote_signal_template.clone();
+        if no_ui {
+            remote_signal_template.dir_content_mut().map(|v| {
+                v.retain(|k, _| k != "ui");
+                v
+            });
+        }
+        if no_spec {
+            remote_signal_template.dir_content_mut().map(|v| {
+                v.retain(|k, _| k != "tests" && k != "dnas");
+                v
+            });
+        }
+        app_file_tree = render_template_file_tree_and_merge_with_existing(
+            app_file_tree,
+            &h,
+            &remote_signal_template,
+            &data,
+        )?;
+    }
+
+    let next_instructions = match file_content(
+        template_file_tree,
+        &PathBuf::from("remote-signal.instructions.hbs"),
+    ) {
+        Ok(content) => Some(h.render_template(content.as_str(), &data)?),
+        Err(_) => None,
+    };
+
+    Ok(ScaffoldedTemplate {
+        file_tree: app_file_tree,
+        next_instructions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::scaffold::web_app::template_type::TemplateType;
+    use build_fs_tree::{dir, file};
+
+    fn render_remote_signal(app_file_tree: FileTree, signal_name: &str) -> FileTree {
+        let template_file_tree = TemplateType::Svelte.file_tree().unwrap();
+        scaffold_remote_signal_templates(
+            app_file_tree,
+            &template_file_tree,
+            "test_app",
+            "test_dna",
+            &ZomeManifest {
+                name: "test_zome".into(),
+                hash: None,
+                path: "".to_string(),
+                dependencies: None,
+            },
+            signal_name,
+            &[FieldDefinition::from_str("content:String").unwrap()],
+            "export interface NewMessagePayload {\n  content: string;\n}",
+            false,
+            false,
+        )
+        .unwrap()
+        .file_tree
+    }
+
+    fn empty_app_file_tree() -> FileTree {
+        dir! {
+            "dnas" => dir! {
+                "test_dna" => dir! {
+                    "zomes" => dir! {
+                        "coordinator" => dir! {
+                            "test_zome" => dir! {
+                                "src" => dir! {
+                                    "lib.rs" => file!("")
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "ui" => dir! {
+                "src" => dir! {}
+            }
+        }
+    }
+
+    #[test]
+    fn remote_signal_sweettest_and_listener_are_rendered() {
+        let file_tree = render_remote_signal(empty_app_file_tree(), "new_message");
+
+        let sweettest = file_content(
+            &file_tree,
+            &PathBuf::from(
+                "dnas/test_dna/zomes/coordinator/test_zome/tests/new_message_remote_signal.rs",
+            ),
+        )
+        .unwrap();
+        assert!(sweettest.contains("SweetConductorBatch::standard(2)"));
+        assert!(sweettest.contains("subscribe_to_app_signals"));
+        assert!(sweettest.contains(r#""send_new_message","#));
+        assert!(sweettest.contains("content: String::from(\"Lorem ipsum\"),"));
+
+        let listener = file_content(
+            &file_tree,
+            &PathBuf::from("ui/src/test_dna/test_zome/remote-signals.ts"),
+        )
+        .unwrap();
+        assert!(listener.contains("export interface NewMessagePayload {"));
+        assert!(listener.contains("export function onNewMessage("));
+        assert!(listener.contains(r#"payload.signal.type !== "NewMessage""#));
+    }
+
+    #[test]
+    fn listeners_are_appended_to_the_existing_module() {
+        let file_tree = render_remote_signal(
+            render_remote_signal(empty_app_file_tree(), "new_message"),
+            "typing",
+        );
+
+        let listener = file_content(
+            &file_tree,
+            &PathBuf::from("ui/src/test_dna/test_zome/remote-signals.ts"),
+        )
+        .unwrap();
+        assert_eq!(listener.matches(r#"from "@holochain/client";"#).count(), 1);
+        assert_eq!(
+            listener
+                .matches("export type RemoteSignalReceived<T> =")
+                .count(),
+            1
+        );
+        assert!(listener.contains("export async function sendNewMessage("));
+        assert!(listener.contains("export async function sendTyping("));
+    }
+}
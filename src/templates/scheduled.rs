@@ -0,0 +1,146 @@
+use std::{ffi::OsString, path::PathBuf};
+
+use holochain_types::prelude::ZomeManifest;
+use serde::Serialize;
+
+use crate::{
+    error::ScaffoldResult,
+    file_tree::{file_content, FileTree},
+    scaffold::scheduled::ScheduleTrigger,
+};
+
+use super::{
+    build_handlebars, render_template_file_tree_and_merge_with_existing, ScaffoldedTemplate,
+};
+
+#[derive(Serialize)]
+pub struct ScaffoldScheduledData<'a> {
+    pub app_name: &'a str,
+    pub dna_role_name: &'a str,
+    pub coordinator_zome_manifest: ZomeManifest,
+    pub function_name: &'a str,
+    pub trigger: &'a ScheduleTrigger,
+}
+
+// TODO: group some params into a new-type or prefer builder pattern
+#[allow(unknown_lints, clippy::too_many_arguments, clippy::manual_inspect)]
+pub fn scaffold_scheduled_templates(
+    mut app_file_tree: FileTree,
+    template_file_tree: &FileTree,
+    app_name: &str,
+    dna_role_name: &str,
+    coordinator_zome_manifest: &ZomeManifest,
+    function_name: &str,
+    trigger: &ScheduleTrigger,
+    no_spec: bool,
+) -> ScaffoldResult<ScaffoldedTemplate> {
+    let data = ScaffoldScheduledData {
+        app_name,
+        dna_role_name,
+        coordinator_zome_manifest: coordinator_zome_manifest.clone(),
+        function_name,
+        trigger,
+    };
+
+    let h = build_handlebars(template_file_tree)?;
+
+    let scheduled_path = PathBuf::from("scheduled");
+    let v: Vec<OsString> = scheduled_path.iter().map(|s| s.to_os_string()).collect();
+
+    if let Some(scheduled_template) = template_file_tree.path(&mut v.iter()) {
+        let mut scheduled_template = scheduled_template.clone();
+        if no_spec {
+            scheduled_template.dir_content_mut().map(|v| {
+                v.retain(|k, _| k != "tests" && k != "dnas");
+                v
+            });
+        }
+        app_file_tree = render_template_file_tree_and_merge_with_existing(
+            app_file_tree,
+            &h,
+            &scheduled_template,
+            &data,
+        )?;
+    }
+
+    let next_instructions = match file_content(
+        template_file_tree,
+        &PathBuf::from("scheduled.instructions.hbs"),
+    ) {
+        Ok(content) => Some(h.render_template(content.as_str(), &data)?),
+        Err(_) => None,
+    };
+
+    Ok(ScaffoldedTemplate {
+        file_tree: app_file_tree,
+        next_instructions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaffold::web_app::template_type::TemplateType;
+    use build_fs_tree::{dir, file};
+
+    fn render_scheduled(trigger: &ScheduleTrigger) -> String {
+        let app_file_tree = dir! {
+            "dnas" => dir! {
+                "test_dna" => dir! {
+                    "zomes" => dir! {
+                        "coordinator" => dir! {
+                            "test_zome" => dir! {
+                                "src" => dir! {
+                                    "lib.rs" => file!("")
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        };
+        let file_tree = scaffold_scheduled_templates(
+            app_file_tree,
+            &TemplateType::Headless.file_tree().unwrap(),
+            "test_app",
+            "test_dna",
+            &ZomeManifest {
+                name: "test_zome".into(),
+                hash: None,
+                path: "".to_string(),
+                dependencies: None,
+            },
+            "cleanup",
+            trigger,
+            false,
+        )
+        .unwrap()
+        .file_tree;
+
+        file_content(
+            &file_tree,
+            &PathBuf::from("dnas/test_dna/zomes/coordinator/test_zome/tests/cleanup_schedule.rs"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn ephemeral_schedule_sweettest_advances_the_scheduler() {
+        let sweettest = render_scheduled(&ScheduleTrigger::Every { seconds: 3600 });
+
+        assert!(sweettest.contains("Some(Schedule::Ephemeral(Duration::from_secs(3600)))"));
+        assert!(sweettest.contains(
+            ".dispatch_scheduled_fns((Timestamp::now() + Duration::from_secs(3600)).unwrap())"
+        ));
+    }
+
+    #[test]
+    fn persisted_schedule_sweettest_waits_for_the_scheduler() {
+        let sweettest = render_scheduled(&ScheduleTrigger::Cron {
+            expression: "0 0 8 * * *".to_string(),
+        });
+
+        assert!(sweettest.contains(r#"Some(Schedule::Persisted("0 0 8 * * *".to_string()))"#));
+        assert!(!sweettest.contains("dispatch_scheduled_fns((Timestamp::now() +"));
+    }
+}
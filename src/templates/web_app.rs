@@ -5,6 +5,7 @@ use serde::Serialize;
 use crate::{
     error::ScaffoldResult,
     file_tree::{file_content, FileTree},
+    scaffold::web_app::template_manifest::TemplateVars,
     versions,
 };
 
@@ -20,12 +21,14 @@ pub struct ScaffoldWebAppData<'a> {
     pub hdi_version: &'a str,
     pub holochain_client_version: &'a str,
     pub hc_spin_version: &'a str,
+    pub vars: &'a TemplateVars,
 }
 
 pub fn scaffold_web_app_template(
     mut app_file_tree: FileTree,
     template_file_tree: &FileTree,
     app_name: &str,
+    vars: &TemplateVars,
 ) -> ScaffoldResult<ScaffoldedTemplate> {
     let data = ScaffoldWebAppData {
         app_name,
@@ -34,6 +37,7 @@ pub fn scaffold_web_app_template(
         hdi_version: versions::HDI_VERSION,
         holochain_client_version: versions::HOLOCHAIN_CLIENT_VERSION,
         hc_spin_version: versions::HC_SPIN_VERSION,
+        vars,
     };
 
     let h = build_handlebars(template_file_tree)?;
@@ -0,0 +1,152 @@
+use std::path::PathBuf;
+
+use build_fs_tree::{dir, file};
+
+use crate::error::{ScaffoldError, ScaffoldResult};
+use crate::file_tree::{dir_content, file_content, FileTree};
+use crate::scaffold::entry_type::definitions::FieldType;
+
+const FIELD_VARIABLES: &str = r#"  - field_name: name of the field, e.g. "due_date"
+  - field_type: the type of the field, with `field_type.type` being "{field_type}"{enum_variants}
+  - cardinality: "single", "option" or "vector"
+  - widget: "{widget}""#;
+
+/// Partials of a widget, along with the description of what they render and the variables
+/// passed to them, and the content used when there is no other widget to start from
+const WIDGET_PARTIALS: [(&str, &str, &str); 4] = [
+    (
+        "edit/render.hbs",
+        r#"Renders the input to create or edit the field.
+
+  Available variables:
+  - label: title cased name of the field, e.g. "Due Date"
+  - variable_to_read: expression holding the current value of the field
+  - variable_to_change: variable to assign the new value of the field to
+  - required: whether the field must have a value, false for fields with "option" cardinality
+"#,
+        r#"<label for="{{label}}">{{label}}</label>
+<input name="{{label}}" {{#if required}}required{{/if}} />"#,
+    ),
+    (
+        "detail/render.hbs",
+        r#"Renders the value of the field in the detail of the entry.
+
+  Available variables:
+  - variable_to_read: expression holding the value of the field
+"#,
+        "{{variable_to_read}}",
+    ),
+    (
+        "initial-value.hbs",
+        r#"Renders the expression initializing the field when creating a new entry.
+
+  Available variables:
+"#,
+        "undefined",
+    ),
+    (
+        "is-valid.hbs",
+        r#"Renders the boolean expression checking whether the field has a valid value.
+
+  Available variables:
+  - variable_to_validate: expression holding the value to check
+"#,
+        "true",
+    ),
+];
+
+/// Creates the partials of a new widget for the given field type
+///
+/// The content of each partial is taken from another widget for the same field type if the
+/// template has one, preceded by a comment documenting the variables available to it.
+pub fn widget_file_tree(
+    template_file_tree: &FileTree,
+    field_type: &FieldType,
+    widget: &str,
+) -> ScaffoldResult<FileTree> {
+    let field_type_path = PathBuf::from("field-types").join(field_type.to_string());
+    let existing_widgets: Vec<String> = dir_content(template_file_tree, &field_type_path)
+        .map(|content| {
+            content
+                .into_iter()
+                .filter(|(_, template)| template.dir_content().is_some())
+                .filter_map(|(name, _)| name.to_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if existing_widgets.iter().any(|w| w == widget) {
+        return Err(ScaffoldError::InvalidArguments(format!(
+            "The template already has a \"{widget}\" widget for \"{field_type}\""
+        )));
+    }
+
+    let enum_variants = match field_type {
+        FieldType::Enum { .. } => ", and `field_type.variants` holding the names of its variants",
+        _ => "",
+    };
+    let field_variables = FIELD_VARIABLES
+        .replace("{field_type}", &field_type.to_string())
+        .replace("{enum_variants}", enum_variants)
+        .replace("{widget}", widget);
+
+    let [edit, detail, initial_value, is_valid] =
+        WIDGET_PARTIALS.map(|(path, description, default_content)| {
+            let content = existing_widgets
+                .first()
+                .and_then(|existing_widget| {
+                    file_content(
+                        template_file_tree,
+                        &field_type_path.join(existing_widget).join(path),
+                    )
+                    .ok()
+                })
+                .unwrap_or_else(|| default_content.to_string());
+            format!("{{{{!--\n  {description}{field_variables}\n--}}}}{content}")
+        });
+
+    Ok(dir! {
+        "edit" => dir! {
+            "render.hbs" => file!(edit)
+        },
+        "detail" => dir! {
+            "render.hbs" => file!(detail)
+        },
+        "initial-value.hbs" => file!(initial_value),
+        "is-valid.hbs" => file!(is_valid)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_tree::insert_file_tree_in_dir;
+    use crate::scaffold::web_app::template_type::TemplateType;
+    use crate::templates::lint::lint_template;
+
+    #[test]
+    fn new_widgets_start_from_an_existing_one_and_pass_the_lint() {
+        let mut template_file_tree = TemplateType::Svelte.file_tree().unwrap();
+        let widget = widget_file_tree(&template_file_tree, &FieldType::String, "RichText").unwrap();
+
+        let edit = file_content(&widget, &PathBuf::from("edit/render.hbs")).unwrap();
+        assert!(edit.contains("- variable_to_change:"));
+        assert!(edit.ends_with(
+            &file_content(
+                &template_file_tree,
+                &PathBuf::from("field-types/String/TextArea/edit/render.hbs")
+            )
+            .unwrap()
+        ));
+
+        insert_file_tree_in_dir(
+            &mut template_file_tree,
+            &PathBuf::from("field-types/String"),
+            ("RichText".into(), widget),
+        )
+        .unwrap();
+        assert_eq!(lint_template(&template_file_tree), vec![]);
+
+        assert!(widget_file_tree(&template_file_tree, &FieldType::String, "TextArea").is_err());
+    }
+}